@@ -46,6 +46,14 @@ struct Cli {
     #[arg(long, global = true)]
     sequence: Option<u32>,
 
+    /// Maximum amount of gas the transaction may consume [default: estimate by simulation]
+    #[arg(long, global = true)]
+    gas_limit: Option<u64>,
+
+    /// Simulate the transaction instead of broadcasting it [default: false]
+    #[arg(long, global = true, default_value_t = false)]
+    simulate: bool,
+
     /// The block height at which to perform queries [default: last finalized height]
     #[arg(long, global = true)]
     height: Option<u64>,
@@ -90,7 +98,17 @@ async fn main() -> anyhow::Result<()> {
         Command::Query(cmd) => cmd.run(&cli.node, cli.height, cli.prove).await,
         Command::Tendermint(cmd) => cmd.run(&cli.node).await,
         Command::Tx(cmd) => {
-            cmd.run(&cli.node, key_dir, cli.key_name, cli.sender, cli.chain_id, cli.sequence).await
+            cmd.run(
+                &cli.node,
+                key_dir,
+                cli.key_name,
+                cli.sender,
+                cli.chain_id,
+                cli.sequence,
+                cli.gas_limit,
+                cli.simulate,
+            )
+            .await
         },
     }
 }
@@ -84,6 +84,24 @@ pub enum QueryCmd {
         /// Key in hex encoding
         key: String,
     },
+    /// Query a transaction and its outcome by hash, from the node's tx index
+    Tx {
+        /// Transaction hash
+        hash: Hash,
+    },
+    /// Search for transactions by an event attribute, from the node's tx index
+    SearchTxs {
+        /// Event type
+        event_type: String,
+        /// Attribute key
+        key: String,
+        /// Attribute value
+        value: String,
+        /// Start after this transaction hash
+        start_after: Option<Hash>,
+        /// Maximum number of items to display
+        limit: Option<u32>,
+    },
 }
 
 impl QueryCmd {
@@ -132,6 +150,16 @@ impl QueryCmd {
             QueryCmd::Store {
                 key,
             } => query_store(&client, key, height, prove).await,
+            QueryCmd::Tx {
+                hash,
+            } => print_json_pretty(client.query_tx(hash).await?),
+            QueryCmd::SearchTxs {
+                event_type,
+                key,
+                value,
+                start_after,
+                limit,
+            } => print_json_pretty(client.search_txs(event_type, key, value, start_after, limit).await?),
         }
     }
 }
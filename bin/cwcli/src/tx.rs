@@ -81,6 +81,7 @@ pub enum TxCmd {
 }
 
 impl TxCmd {
+    #[allow(clippy::too_many_arguments)]
     pub async fn run(
         self,
         rpc_addr: &str,
@@ -89,6 +90,8 @@ impl TxCmd {
         sender: Option<Addr>,
         chain_id: Option<String>,
         sequence: Option<u32>,
+        gas_limit: Option<u64>,
+        simulate: bool,
     ) -> anyhow::Result<()> {
         let sender = sender.ok_or(anyhow!("sender not specified"))?;
         let key_name = key_name.ok_or(anyhow!("key name not specified"))?;
@@ -168,10 +171,18 @@ impl TxCmd {
             sender,
             chain_id,
             sequence,
+            gas_limit,
         };
 
-        // broadcast transaction
         let client = Client::connect(rpc_addr)?;
+
+        // simulate the transaction without broadcasting it
+        if simulate {
+            let res = client.simulate_tx(msgs, &sign_opts).await?;
+            return print_json_pretty(res);
+        }
+
+        // broadcast transaction
         let maybe_res = client.send_tx_with_confirmation(msgs, &sign_opts, |tx| {
             print_json_pretty(tx)?;
             Ok(confirm("🤔 Broadcast transaction?".bold())?)
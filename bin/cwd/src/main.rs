@@ -1,11 +1,20 @@
 use {
-    anyhow::anyhow, clap::Parser, cw_app::App, cw_db::BaseStore, home::home_dir,
-    std::path::PathBuf, tracing::metadata::LevelFilter,
+    anyhow::anyhow,
+    clap::Parser,
+    cw_app::App,
+    cw_db::{BaseStore, BaseStoreOptions, PruningPolicy},
+    cw_vm::ModuleCache,
+    home::home_dir,
+    std::{num::NonZeroUsize, path::PathBuf},
+    tracing::metadata::LevelFilter,
 };
 
 // relative to user home directory (~)
 const DEFAULT_DATA_DIR: &str = ".cwd";
 
+// relative to the data directory
+const MODULE_CACHE_DIR: &str = "modules";
+
 #[derive(Parser)]
 #[command(author, version, about, next_display_order = None)]
 struct Cli {
@@ -21,6 +30,29 @@ struct Cli {
     #[arg(long, default_value = "1048576")]
     pub read_buf_size: usize,
 
+    /// Only keep the most recent given number of versions of the state
+    /// commitment. If neither pruning option is set, all versions are kept.
+    #[arg(long, conflicts_with = "pruning_keep_every")]
+    pub pruning_keep_recent: Option<u64>,
+
+    /// Keep one version of the state commitment out of every given number of
+    /// versions. If neither pruning option is set, all versions are kept.
+    #[arg(long)]
+    pub pruning_keep_every: Option<u64>,
+
+    /// Take a snapshot of the state once every this many blocks, which are
+    /// served to other nodes for state sync. If unset, no snapshot is taken.
+    #[arg(long)]
+    pub snapshot_interval: Option<u64>,
+
+    /// Number of most recent snapshots to keep. Zero means keeping all.
+    #[arg(long, default_value = "2")]
+    pub snapshot_keep_recent: u32,
+
+    /// Maximum number of compiled Wasm modules to keep in memory
+    #[arg(long, default_value = "100")]
+    pub module_cache_capacity: NonZeroUsize,
+
     /// Logging verbosity: error|warn|info|debug|trace
     #[arg(long, default_value = "info")]
     pub tracing_level: LevelFilter,
@@ -41,9 +73,25 @@ fn main() -> anyhow::Result<()> {
         home_dir.join(DEFAULT_DATA_DIR)
     };
 
+    // determine pruning policy
+    let pruning = match (cli.pruning_keep_recent, cli.pruning_keep_every) {
+        (Some(num), _) => PruningPolicy::KeepRecent(num),
+        (_, Some(num)) => PruningPolicy::KeepEvery(num),
+        (None, None) => PruningPolicy::KeepAll,
+    };
+
+    // create the Wasm module cache. compiled modules are also saved to disk, so
+    // they don't need to be compiled again after a restart
+    let cache =
+        ModuleCache::new(cli.module_cache_capacity, Some(data_dir.join(MODULE_CACHE_DIR)))?;
+
     // create DB backend
-    let store = BaseStore::open(data_dir)?;
+    let store = BaseStore::open_with_options(data_dir, BaseStoreOptions {
+        pruning,
+        snapshot_interval:    cli.snapshot_interval,
+        snapshot_keep_recent: cli.snapshot_keep_recent,
+    })?;
 
     // start the ABCI server
-    App::new(store).start_abci_server(cli.read_buf_size, cli.addr).map_err(Into::into)
+    App::new(store, cache).start_abci_server(cli.read_buf_size, cli.addr).map_err(Into::into)
 }
@@ -0,0 +1,67 @@
+use {
+    cw_std::{Addr, CodedError, StdError},
+    thiserror::Error,
+};
+
+#[derive(Debug, Error)]
+pub enum AccountError {
+    #[error(transparent)]
+    Std(#[from] StdError),
+
+    #[error("Signature verification failed")]
+    Unauthorized,
+
+    #[error("Only the account itself can update key! sender: {sender}")]
+    NotSelf {
+        sender: Addr,
+    },
+}
+
+impl AccountError {
+    pub fn not_self(sender: Addr) -> Self {
+        Self::NotSelf { sender }
+    }
+}
+
+impl CodedError for AccountError {
+    fn codespace(&self) -> &str {
+        match self {
+            Self::Std(err) => err.codespace(),
+            _ => AccountErrorCode::CODESPACE,
+        }
+    }
+
+    fn code(&self) -> u32 {
+        let code = match self {
+            Self::Std(err) => return err.code(),
+            Self::Unauthorized => AccountErrorCode::Unauthorized,
+            Self::NotSelf { .. } => AccountErrorCode::NotSelf,
+        };
+        code as u32
+    }
+}
+
+/// The codes of `AccountError` variants, in the `account` codespace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum AccountErrorCode {
+    Unauthorized = 1,
+    NotSelf      = 2,
+}
+
+impl AccountErrorCode {
+    pub const CODESPACE: &'static str = "account";
+
+    /// Find the variant corresponding to the code. Return `None` if the code
+    /// isn't defined.
+    pub fn from_code(code: u32) -> Option<Self> {
+        let code = match code {
+            1 => Self::Unauthorized,
+            2 => Self::NotSelf,
+            _ => return None,
+        };
+        Some(code)
+    }
+}
+
+pub type AccountResult<T> = std::result::Result<T, AccountError>;
@@ -1,12 +1,13 @@
+mod error;
+
+pub use crate::error::{AccountError, AccountErrorCode, AccountResult};
 #[cfg(not(feature = "library"))]
 use cw_std::entry_point;
 use {
-    anyhow::ensure,
     cw_std::{
-        cw_serde, to_json, Addr, BeforeTxCtx, Binary, ExecuteCtx, InstantiateCtx, Item, Message,
-        QueryCtx, ReceiveCtx, Response, Tx,
+        cw_serde, to_json, Addr, BeforeTxCtx, Binary, ExecuteCtx, Hash, InstantiateCtx, Item,
+        Message, QueryCtx, ReceiveCtx, Response, SignDoc, StdResult, Tx, Uint64,
     },
-    sha2::{Digest, Sha256},
 };
 
 const PUBKEY:   Item<PubKey> = Item::new("pk");
@@ -44,40 +45,22 @@ pub enum PubKey {
 }
 
 /// Given details of a transaction, produce the bytes that the sender needs to
-/// sign (hashed).
-///
-/// The bytes are defined as:
-///
-/// ```plain
-/// bytes := blake3(json(msgs) | sender_addr | chain_id | sequence)
-/// ```
-///
-/// where:
-/// - `sender_addr` is a 32 bytes address of the sender;
-/// - `chain_id` is the chain ID in UTF-8 encoding;
-/// - `sequence` is the sender account's sequence in 32-bit big endian encoding.
+/// sign (hashed), which is the SHA-256 hash of the canonical JSON encoding of
+/// the transaction's `SignDoc`.
 ///
-/// TODO: json here is ambiguous, i.e. what padding and linebreak character to
-/// use, the order of fields... elaborate it.
-///
-/// TODO: is it efficient to do hashing in the contract? maybe move this to the
-/// host??
+/// See the docs of `SignDoc` for details.
 pub fn sign_bytes(
-    msgs:     &[Message],
-    sender:   &Addr,
-    chain_id: &str,
-    sequence: u32,
-) -> anyhow::Result<[u8; 32]> {
-    let mut hasher = Sha256::new();
-    hasher.update(&to_json(&msgs)?);
-    hasher.update(sender);
-    hasher.update(chain_id.as_bytes());
-    hasher.update(sequence.to_be_bytes());
-    Ok(hasher.finalize().into())
+    msgs:      &[Message],
+    sender:    &Addr,
+    chain_id:  &str,
+    sequence:  u32,
+    gas_limit: Uint64,
+) -> StdResult<Hash> {
+    SignDoc::new(chain_id, sender.clone(), sequence, gas_limit, msgs.to_vec()).sign_bytes()
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn instantiate(ctx: InstantiateCtx, msg: InstantiateMsg) -> anyhow::Result<Response> {
+pub fn instantiate(ctx: InstantiateCtx, msg: InstantiateMsg) -> AccountResult<Response> {
     PUBKEY.save(ctx.store, &msg.pubkey)?;
     SEQUENCE.save(ctx.store, &0)?;
 
@@ -85,7 +68,7 @@ pub fn instantiate(ctx: InstantiateCtx, msg: InstantiateMsg) -> anyhow::Result<R
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn receive(ctx: ReceiveCtx) -> anyhow::Result<Response> {
+pub fn receive(ctx: ReceiveCtx) -> AccountResult<Response> {
     // do nothing, accept all transfers. log the receipt to events
     Ok(Response::new()
         .add_attribute("method", "receive")
@@ -94,24 +77,26 @@ pub fn receive(ctx: ReceiveCtx) -> anyhow::Result<Response> {
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn before_tx(ctx: BeforeTxCtx, tx: Tx) -> anyhow::Result<Response> {
+pub fn before_tx(ctx: BeforeTxCtx, tx: Tx) -> AccountResult<Response> {
     let pubkey = PUBKEY.load(ctx.store)?;
     let mut sequence = SEQUENCE.load(ctx.store)?;
 
-    // prepare the hash that is expected to have been signed
-    let msg_hash = sign_bytes(&tx.msgs, &tx.sender, &ctx.chain_id, sequence)?;
-
-    // verify the signature
-    // skip if we are in simulate mode
+    // prepare the hash that is expected to have been signed. this is the same
+    // as `sign_bytes`, except that the hashing is done by the host, which is
+    // cheaper than doing it in Wasm
+    let sign_doc = SignDoc::new(&ctx.chain_id, tx.sender, sequence, tx.gas_limit, tx.msgs);
+    let msg_hash = ctx.sha2_256(sign_doc.to_canonical_json()?);
+
+    // verify the signature.
+    // in simulate mode, the credential may not be valid, so the result is
+    // ignored. the verification is still performed though, such that its gas
+    // cost is included in the estimate.
+    let res = match &pubkey {
+        PubKey::Secp256k1(bytes) => ctx.secp256k1_verify(msg_hash, &tx.credential, bytes),
+        PubKey::Secp256r1(bytes) => ctx.secp256r1_verify(msg_hash, &tx.credential, bytes),
+    };
     if !ctx.simulate {
-        match &pubkey {
-            PubKey::Secp256k1(bytes) => {
-                ctx.secp256k1_verify(msg_hash, &tx.credential, bytes)?;
-            },
-            PubKey::Secp256r1(bytes) => {
-                ctx.secp256r1_verify(msg_hash, &tx.credential, bytes)?;
-            },
-        }
+        res.map_err(|_| AccountError::Unauthorized)?;
     }
 
     // update sequence
@@ -124,7 +109,7 @@ pub fn before_tx(ctx: BeforeTxCtx, tx: Tx) -> anyhow::Result<Response> {
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn execute(ctx: ExecuteCtx, msg: ExecuteMsg) -> anyhow::Result<Response> {
+pub fn execute(ctx: ExecuteCtx, msg: ExecuteMsg) -> AccountResult<Response> {
     match msg {
         ExecuteMsg::UpdateKey {
             new_pubkey,
@@ -133,15 +118,16 @@ pub fn execute(ctx: ExecuteCtx, msg: ExecuteMsg) -> anyhow::Result<Response> {
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(ctx: QueryCtx, msg: QueryMsg) -> anyhow::Result<Binary> {
+pub fn query(ctx: QueryCtx, msg: QueryMsg) -> AccountResult<Binary> {
     match msg {
-        QueryMsg::State {} => to_json(&query_state(ctx)?),
+        QueryMsg::State {} => to_json(&query_state(ctx)?).map_err(Into::into),
     }
-    .map_err(Into::into) // TODO: remove
 }
 
-pub fn update_key(ctx: ExecuteCtx, new_pubkey: PubKey) -> anyhow::Result<Response> {
-    ensure!(ctx.sender == ctx.contract, "only the account itself can update key");
+pub fn update_key(ctx: ExecuteCtx, new_pubkey: PubKey) -> AccountResult<Response> {
+    if ctx.sender != ctx.contract {
+        return Err(AccountError::not_self(ctx.sender));
+    }
     // TODO: ensure new pubkey is valid?
 
     PUBKEY.save(ctx.store, &new_pubkey)?;
@@ -149,7 +135,7 @@ pub fn update_key(ctx: ExecuteCtx, new_pubkey: PubKey) -> anyhow::Result<Respons
     Ok(Response::new())
 }
 
-pub fn query_state(ctx: QueryCtx) -> anyhow::Result<StateResponse> {
+pub fn query_state(ctx: QueryCtx) -> AccountResult<StateResponse> {
     Ok(StateResponse {
         pubkey:   PUBKEY.load(ctx.store)?,
         sequence: SEQUENCE.load(ctx.store)?,
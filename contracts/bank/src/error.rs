@@ -0,0 +1,104 @@
+use {
+    cw_std::{Addr, CodedError, StdError, Uint128},
+    thiserror::Error,
+};
+
+#[derive(Debug, Error)]
+pub enum BankError {
+    #[error(transparent)]
+    Std(#[from] StdError),
+
+    #[error("Duplicate address in initial balances: {address}")]
+    DuplicateAddress {
+        address: Addr,
+    },
+
+    #[error("Do not send funds to this contract")]
+    FundsNotAccepted,
+
+    #[error("Only the taxman can force transfer! sender: {sender}")]
+    NotTaxman {
+        sender: Addr,
+    },
+
+    #[error("Insufficient {denom} balance! address: {address}, balance: {balance}, need: {amount}")]
+    InsufficientBalance {
+        address: Addr,
+        denom:   String,
+        balance: Uint128,
+        amount:  Uint128,
+    },
+}
+
+impl BankError {
+    pub fn duplicate_address(address: Addr) -> Self {
+        Self::DuplicateAddress { address }
+    }
+
+    pub fn not_taxman(sender: Addr) -> Self {
+        Self::NotTaxman { sender }
+    }
+
+    pub fn insufficient_balance(
+        address: Addr,
+        denom:   impl Into<String>,
+        balance: Uint128,
+        amount:  Uint128,
+    ) -> Self {
+        Self::InsufficientBalance {
+            address,
+            denom: denom.into(),
+            balance,
+            amount,
+        }
+    }
+}
+
+impl CodedError for BankError {
+    fn codespace(&self) -> &str {
+        match self {
+            Self::Std(err) => err.codespace(),
+            _ => BankErrorCode::CODESPACE,
+        }
+    }
+
+    fn code(&self) -> u32 {
+        let code = match self {
+            Self::Std(err) => return err.code(),
+            Self::DuplicateAddress { .. } => BankErrorCode::DuplicateAddress,
+            Self::FundsNotAccepted => BankErrorCode::FundsNotAccepted,
+            Self::NotTaxman { .. } => BankErrorCode::NotTaxman,
+            Self::InsufficientBalance { .. } => BankErrorCode::InsufficientBalance,
+        };
+        code as u32
+    }
+}
+
+/// The codes of `BankError` variants, in the `bank` codespace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum BankErrorCode {
+    DuplicateAddress    = 1,
+    FundsNotAccepted    = 2,
+    NotTaxman           = 3,
+    InsufficientBalance = 4,
+}
+
+impl BankErrorCode {
+    pub const CODESPACE: &'static str = "bank";
+
+    /// Find the variant corresponding to the code. Return `None` if the code
+    /// isn't defined.
+    pub fn from_code(code: u32) -> Option<Self> {
+        let code = match code {
+            1 => Self::DuplicateAddress,
+            2 => Self::FundsNotAccepted,
+            3 => Self::NotTaxman,
+            4 => Self::InsufficientBalance,
+            _ => return None,
+        };
+        Some(code)
+    }
+}
+
+pub type BankResult<T> = std::result::Result<T, BankError>;
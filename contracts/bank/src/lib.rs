@@ -1,7 +1,9 @@
+mod error;
+
+pub use crate::error::{BankError, BankErrorCode, BankResult};
 #[cfg(not(feature = "library"))]
 use cw_std::entry_point;
 use {
-    anyhow::bail,
     cw_std::{
         cw_serde, Addr, BankQuery, BankQueryResponse, Bound, Coin, Coins, ExecuteCtx,
         InstantiateCtx, Map, Order, QueryCtx, ReceiveCtx, Response, StdResult, Storage,
@@ -42,10 +44,18 @@ pub enum ExecuteMsg {
         denom:  String,
         amount: Uint128,
     },
+    /// Move coins from one account to another, without the consent of the
+    /// account they're moved from. Only callable by the chain's taxman, for
+    /// the purpose of withholding transaction fees.
+    ForceTransfer {
+        from:  Addr,
+        to:    Addr,
+        coins: Coins,
+    },
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn instantiate(ctx: InstantiateCtx, msg: InstantiateMsg) -> anyhow::Result<Response> {
+pub fn instantiate(ctx: InstantiateCtx, msg: InstantiateMsg) -> BankResult<Response> {
     // need to make sure there are no duplicate address in initial balances.
     // we don't need to dedup denoms however. if there's duplicate denoms, the
     // deserialization setup should have already thrown an error.
@@ -54,7 +64,7 @@ pub fn instantiate(ctx: InstantiateCtx, msg: InstantiateMsg) -> anyhow::Result<R
 
     for Balance { address, coins } in msg.initial_balances {
         if seen_addrs.contains(&address) {
-            bail!("Duplicate address in initial balances");
+            return Err(BankError::duplicate_address(address));
         }
 
         for coin in coins {
@@ -78,7 +88,7 @@ fn accumulate_supply(
     supplies: &mut HashMap<String, Uint128>,
     denom:    &str,
     by:       Uint128,
-) -> anyhow::Result<()> {
+) -> StdResult<()> {
     let Some(supply) = supplies.get_mut(denom) else {
         supplies.insert(denom.into(), by);
         return Ok(());
@@ -90,7 +100,7 @@ fn accumulate_supply(
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn transfer(ctx: TransferCtx, msg: TransferMsg) -> StdResult<Response> {
+pub fn transfer(ctx: TransferCtx, msg: TransferMsg) -> BankResult<Response> {
     for coin in &msg.coins {
         decrease_balance(ctx.store, &msg.from, coin.denom, *coin.amount)?;
         increase_balance(ctx.store, &msg.to, coin.denom, *coin.amount)?;
@@ -104,14 +114,14 @@ pub fn transfer(ctx: TransferCtx, msg: TransferMsg) -> StdResult<Response> {
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn receive(_ctx: ReceiveCtx) -> anyhow::Result<Response> {
+pub fn receive(_ctx: ReceiveCtx) -> BankResult<Response> {
     // we do not expect anyone to send any fund to this contract.
     // throw an error to revert the transfer.
-    bail!("do not send funds to this contract");
+    Err(BankError::FundsNotAccepted)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn execute(ctx: ExecuteCtx, msg: ExecuteMsg) -> anyhow::Result<Response> {
+pub fn execute(ctx: ExecuteCtx, msg: ExecuteMsg) -> BankResult<Response> {
     match msg {
         ExecuteMsg::Mint {
             to,
@@ -123,6 +133,11 @@ pub fn execute(ctx: ExecuteCtx, msg: ExecuteMsg) -> anyhow::Result<Response> {
             denom,
             amount,
         } => burn(ctx, from, denom, amount),
+        ExecuteMsg::ForceTransfer {
+            from,
+            to,
+            coins,
+        } => force_transfer(ctx, from, to, coins),
     }
 }
 
@@ -133,7 +148,7 @@ pub fn mint(
     to:     Addr,
     denom:  String,
     amount: Uint128,
-) -> anyhow::Result<Response> {
+) -> BankResult<Response> {
     increase_supply(ctx.store, &denom, amount)?;
     increase_balance(ctx.store, &to, &denom, amount)?;
 
@@ -151,7 +166,7 @@ pub fn burn(
     from:   Addr,
     denom:  String,
     amount: Uint128,
-) -> anyhow::Result<Response> {
+) -> BankResult<Response> {
     decrease_supply(ctx.store, &denom, amount)?;
     decrease_balance(ctx.store, &from, &denom, amount)?;
 
@@ -162,6 +177,29 @@ pub fn burn(
         .add_attribute("amount", amount))
 }
 
+pub fn force_transfer(
+    ctx:   ExecuteCtx,
+    from:  Addr,
+    to:    Addr,
+    coins: Coins,
+) -> BankResult<Response> {
+    let cfg = ctx.query_info()?.config;
+    if cfg.taxman.as_ref() != Some(&ctx.sender) {
+        return Err(BankError::not_taxman(ctx.sender));
+    }
+
+    for coin in &coins {
+        decrease_balance(ctx.store, &from, coin.denom, *coin.amount)?;
+        increase_balance(ctx.store, &to, coin.denom, *coin.amount)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "force_transfer")
+        .add_attribute("from", from)
+        .add_attribute("to", to)
+        .add_attribute("coins", coins.to_string()))
+}
+
 /// Increase the total supply of a token by the given amount.
 /// Return the total supply value after the increase.
 fn increase_supply(
@@ -214,9 +252,12 @@ fn decrease_balance(
     address: &Addr,
     denom:   &str,
     amount:  Uint128,
-) -> StdResult<Option<Uint128>> {
+) -> BankResult<Option<Uint128>> {
     BALANCES.update(store, (address, denom), |balance| {
-        let balance = balance.unwrap_or_default().checked_sub(amount)?;
+        let balance = balance.unwrap_or_default();
+        let Ok(balance) = balance.checked_sub(amount) else {
+            return Err(BankError::insufficient_balance(address.clone(), denom, balance, amount));
+        };
         // if balance is reduced to zero, delete it, to save disk space
         if balance.is_zero() {
             Ok(None)
@@ -136,7 +136,7 @@ fn new_refund_msg(to: Addr, coins: Coins) -> StdResult<SubMessage> {
 mod tests {
     use {
         super::*,
-        cw_std::{hash, Coin, MockStorage, Timestamp, Uint64},
+        cw_std::{hash, Coin, MockApi, MockQuerier, MockStorage, Timestamp, Uint64},
     };
 
     #[test]
@@ -152,6 +152,8 @@ mod tests {
         // TODO: this should be a helper function, something like ReceiveCtx::mock
         let ctx = ReceiveCtx {
             store:           &mut store,
+            api:             &MockApi,
+            querier:         &MockQuerier::new(),
             chain_id:        "dev-1".into(),
             block_height:    Uint64::new(0),
             block_timestamp: Timestamp::from_seconds(0),
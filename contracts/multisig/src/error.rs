@@ -0,0 +1,153 @@
+use {
+    cw_std::{Addr, CodedError, StdError},
+    thiserror::Error,
+};
+
+#[derive(Debug, Error)]
+pub enum MultisigError {
+    #[error(transparent)]
+    Std(#[from] StdError),
+
+    #[error("Signer is not a member of the multisig! name: {name}")]
+    NotMember {
+        name: String,
+    },
+
+    #[error("Signature verification failed! member: {name}")]
+    Unauthorized {
+        name: String,
+    },
+
+    #[error("Not enough signatures! total weight: {total_weight}, threshold: {threshold}")]
+    BelowThreshold {
+        total_weight: u32,
+        threshold:    u32,
+    },
+
+    #[error("Only the multisig itself can update members! sender: {sender}")]
+    NotSelf {
+        sender: Addr,
+    },
+
+    #[error("Member is both added and removed! name: {name}")]
+    AddedAndRemoved {
+        name: String,
+    },
+
+    #[error("Member doesn't exist! name: {name}")]
+    MemberNotFound {
+        name: String,
+    },
+
+    #[error("Threshold can't be zero")]
+    ZeroThreshold,
+
+    #[error("Member has zero weight! name: {name}")]
+    ZeroWeight {
+        name: String,
+    },
+
+    #[error("Threshold exceeds total weight! threshold: {threshold}, total weight: {total_weight}")]
+    ThresholdTooHigh {
+        threshold:    u32,
+        total_weight: u32,
+    },
+}
+
+impl MultisigError {
+    pub fn not_member(name: impl Into<String>) -> Self {
+        Self::NotMember { name: name.into() }
+    }
+
+    pub fn unauthorized(name: impl Into<String>) -> Self {
+        Self::Unauthorized { name: name.into() }
+    }
+
+    pub fn below_threshold(total_weight: u32, threshold: u32) -> Self {
+        Self::BelowThreshold { total_weight, threshold }
+    }
+
+    pub fn not_self(sender: Addr) -> Self {
+        Self::NotSelf { sender }
+    }
+
+    pub fn added_and_removed(name: impl Into<String>) -> Self {
+        Self::AddedAndRemoved { name: name.into() }
+    }
+
+    pub fn member_not_found(name: impl Into<String>) -> Self {
+        Self::MemberNotFound { name: name.into() }
+    }
+
+    pub fn zero_weight(name: impl Into<String>) -> Self {
+        Self::ZeroWeight { name: name.into() }
+    }
+
+    pub fn threshold_too_high(threshold: u32, total_weight: u32) -> Self {
+        Self::ThresholdTooHigh { threshold, total_weight }
+    }
+}
+
+impl CodedError for MultisigError {
+    fn codespace(&self) -> &str {
+        match self {
+            Self::Std(err) => err.codespace(),
+            _ => MultisigErrorCode::CODESPACE,
+        }
+    }
+
+    fn code(&self) -> u32 {
+        let code = match self {
+            Self::Std(err) => return err.code(),
+            Self::NotMember { .. } => MultisigErrorCode::NotMember,
+            Self::Unauthorized { .. } => MultisigErrorCode::Unauthorized,
+            Self::BelowThreshold { .. } => MultisigErrorCode::BelowThreshold,
+            Self::NotSelf { .. } => MultisigErrorCode::NotSelf,
+            Self::AddedAndRemoved { .. } => MultisigErrorCode::AddedAndRemoved,
+            Self::MemberNotFound { .. } => MultisigErrorCode::MemberNotFound,
+            Self::ZeroThreshold => MultisigErrorCode::ZeroThreshold,
+            Self::ZeroWeight { .. } => MultisigErrorCode::ZeroWeight,
+            Self::ThresholdTooHigh { .. } => MultisigErrorCode::ThresholdTooHigh,
+        };
+        code as u32
+    }
+}
+
+/// The codes of `MultisigError` variants, in the `multisig` codespace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum MultisigErrorCode {
+    NotMember        = 1,
+    Unauthorized     = 2,
+    BelowThreshold   = 3,
+    NotSelf          = 4,
+    AddedAndRemoved  = 5,
+    MemberNotFound   = 6,
+    ZeroThreshold    = 7,
+    ZeroWeight       = 8,
+    ThresholdTooHigh = 9,
+}
+
+impl MultisigErrorCode {
+    pub const CODESPACE: &'static str = "multisig";
+
+    /// Find the variant corresponding to the code. Return `None` if the code
+    /// isn't defined.
+    pub fn from_code(code: u32) -> Option<Self> {
+        let code = match code {
+            1 => Self::NotMember,
+            2 => Self::Unauthorized,
+            3 => Self::BelowThreshold,
+            4 => Self::NotSelf,
+            5 => Self::AddedAndRemoved,
+            6 => Self::MemberNotFound,
+            7 => Self::ZeroThreshold,
+            8 => Self::ZeroWeight,
+            9 => Self::ThresholdTooHigh,
+            _ => return None,
+        };
+        Some(code)
+    }
+}
+
+pub type MultisigResult<T> = std::result::Result<T, MultisigError>;
@@ -0,0 +1,460 @@
+mod error;
+
+pub use crate::error::{MultisigError, MultisigErrorCode, MultisigResult};
+#[cfg(not(feature = "library"))]
+use cw_std::entry_point;
+use {
+    cw_account::{sign_bytes, PubKey},
+    cw_std::{
+        cw_serde, from_json, to_json, BeforeTxCtx, Binary, ExecuteCtx, InstantiateCtx, Item, Map,
+        Order, QueryCtx, ReceiveCtx, Response, StdResult, Storage, Tx,
+    },
+    std::collections::{BTreeMap, BTreeSet},
+};
+
+// member name => member
+const MEMBERS:   Map<&str, Member> = Map::new("m");
+const THRESHOLD: Item<u32>         = Item::new("t");
+const SEQUENCE:  Item<u32>         = Item::new("seq");
+
+#[cw_serde]
+pub struct Member {
+    pub pubkey: PubKey,
+    pub weight: u32,
+}
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub members:   BTreeMap<String, Member>,
+    /// The minimum total weight of the members who have signed for a
+    /// transaction to be authorized.
+    pub threshold: u32,
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    /// Add, update, or remove members, and set a new threshold. Only the
+    /// multisig itself can do this, i.e. it must be authorized by a threshold
+    /// of the current members.
+    ///
+    /// Members in `add` are inserted, overwriting existing ones of the same
+    /// names. Members in `remove` are deleted. The same name can't be in both.
+    UpdateMembers {
+        add:       BTreeMap<String, Member>,
+        remove:    BTreeSet<String>,
+        threshold: u32,
+    },
+}
+
+#[cw_serde]
+pub enum QueryMsg {
+    /// Query the state of the multisig, including its members, threshold, and
+    /// sequence.
+    /// Returns: StateResponse
+    State {},
+}
+
+#[cw_serde]
+pub struct StateResponse {
+    pub members:   BTreeMap<String, Member>,
+    pub threshold: u32,
+    pub sequence:  u32,
+}
+
+/// The credential of a transaction sent by the multisig, consisting of the
+/// signatures of the members who have signed it, indexed by member names.
+///
+/// Each member signs the same bytes as a regular account would, as given by
+/// `cw_account::sign_bytes`.
+#[cw_serde]
+pub struct Credential {
+    pub signatures: BTreeMap<String, Binary>,
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(ctx: InstantiateCtx, msg: InstantiateMsg) -> MultisigResult<Response> {
+    for (name, member) in &msg.members {
+        MEMBERS.save(ctx.store, name, member)?;
+    }
+
+    ensure_threshold(ctx.store, msg.threshold)?;
+
+    THRESHOLD.save(ctx.store, &msg.threshold)?;
+    SEQUENCE.save(ctx.store, &0)?;
+
+    Ok(Response::new())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn receive(ctx: ReceiveCtx) -> MultisigResult<Response> {
+    // do nothing, accept all transfers. log the receipt to events
+    Ok(Response::new()
+        .add_attribute("method", "receive")
+        .add_attribute("sender", ctx.sender)
+        .add_attribute("funds", ctx.funds.to_string()))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn before_tx(ctx: BeforeTxCtx, tx: Tx) -> MultisigResult<Response> {
+    let threshold = THRESHOLD.load(ctx.store)?;
+    let mut sequence = SEQUENCE.load(ctx.store)?;
+
+    // prepare the hash that is expected to have been signed by the members
+    let msg_hash = sign_bytes(&tx.msgs, &tx.sender, &ctx.chain_id, sequence, tx.gas_limit)?;
+
+    // verify the signatures, and sum up the weights of the members who signed.
+    // the signatures are indexed by member names, so each member can only be
+    // counted once.
+    //
+    // in simulate mode, the credential may be missing or incomplete, so this is
+    // skipped. instead, a verification is performed for every member (with the
+    // result ignored), such that the gas estimate covers the tx no matter which
+    // members end up signing it.
+    if ctx.simulate {
+        for item in MEMBERS.range(ctx.store, None, None, Order::Ascending) {
+            let (_, member) = item?;
+            let _ = match &member.pubkey {
+                PubKey::Secp256k1(bytes) => ctx.secp256k1_verify(&msg_hash, b"", bytes),
+                PubKey::Secp256r1(bytes) => ctx.secp256r1_verify(&msg_hash, b"", bytes),
+            };
+        }
+    } else {
+        let credential: Credential = from_json(&tx.credential)?;
+        let mut total_weight = 0u32;
+
+        for (name, signature) in &credential.signatures {
+            let Some(member) = MEMBERS.may_load(ctx.store, name)? else {
+                return Err(MultisigError::not_member(name));
+            };
+
+            match &member.pubkey {
+                PubKey::Secp256k1(bytes) => ctx.secp256k1_verify(&msg_hash, signature, bytes),
+                PubKey::Secp256r1(bytes) => ctx.secp256r1_verify(&msg_hash, signature, bytes),
+            }
+            .map_err(|_| MultisigError::unauthorized(name))?;
+
+            total_weight = total_weight.saturating_add(member.weight);
+        }
+
+        if total_weight < threshold {
+            return Err(MultisigError::below_threshold(total_weight, threshold));
+        }
+    }
+
+    // update sequence
+    sequence += 1;
+    SEQUENCE.save(ctx.store, &sequence)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "before_tx")
+        .add_attribute("next_sequence", sequence.to_string()))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(ctx: ExecuteCtx, msg: ExecuteMsg) -> MultisigResult<Response> {
+    match msg {
+        ExecuteMsg::UpdateMembers {
+            add,
+            remove,
+            threshold,
+        } => update_members(ctx, add, remove, threshold),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(ctx: QueryCtx, msg: QueryMsg) -> MultisigResult<Binary> {
+    match msg {
+        QueryMsg::State {} => to_json(&query_state(ctx)?),
+    }
+    .map_err(Into::into)
+}
+
+pub fn update_members(
+    ctx:       ExecuteCtx,
+    add:       BTreeMap<String, Member>,
+    remove:    BTreeSet<String>,
+    threshold: u32,
+) -> MultisigResult<Response> {
+    if ctx.sender != ctx.contract {
+        return Err(MultisigError::not_self(ctx.sender));
+    }
+
+    for name in &remove {
+        if add.contains_key(name) {
+            return Err(MultisigError::added_and_removed(name));
+        }
+        if !MEMBERS.has(ctx.store, name) {
+            return Err(MultisigError::member_not_found(name));
+        }
+        MEMBERS.remove(ctx.store, name);
+    }
+
+    for (name, member) in &add {
+        MEMBERS.save(ctx.store, name, member)?;
+    }
+
+    ensure_threshold(ctx.store, threshold)?;
+
+    THRESHOLD.save(ctx.store, &threshold)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_members")
+        .add_attribute("threshold", threshold.to_string()))
+}
+
+pub fn query_state(ctx: QueryCtx) -> MultisigResult<StateResponse> {
+    let members = MEMBERS
+        .range(ctx.store, None, None, Order::Ascending)
+        .collect::<StdResult<_>>()?;
+
+    Ok(StateResponse {
+        members,
+        threshold: THRESHOLD.load(ctx.store)?,
+        sequence:  SEQUENCE.load(ctx.store)?,
+    })
+}
+
+// make sure that the threshold is reachable by the current members, and that
+// it isn't zero, which would allow anyone to send transactions on behalf of
+// the multisig
+fn ensure_threshold(store: &dyn Storage, threshold: u32) -> MultisigResult<()> {
+    if threshold == 0 {
+        return Err(MultisigError::ZeroThreshold);
+    }
+
+    let mut total_weight = 0u32;
+    for item in MEMBERS.range(store, None, None, Order::Ascending) {
+        let (name, member) = item?;
+        if member.weight == 0 {
+            return Err(MultisigError::zero_weight(name));
+        }
+        total_weight = total_weight.saturating_add(member.weight);
+    }
+
+    if total_weight < threshold {
+        return Err(MultisigError::threshold_too_high(threshold, total_weight));
+    }
+
+    Ok(())
+}
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        cw_std::{hash, Addr, Coins, MockApi, MockQuerier, MockStorage, Timestamp, Uint64},
+        k256::ecdsa::{signature::hazmat::PrehashSigner, Signature, SigningKey},
+    };
+
+    const CHAIN_ID: &str = "dev-1";
+
+    fn mock_member(weight: u32) -> Member {
+        Member {
+            pubkey: PubKey::Secp256k1(vec![0; 33].into()),
+            weight,
+        }
+    }
+
+    fn mock_signing_key(seed: u8) -> SigningKey {
+        SigningKey::from_bytes(&[seed; 32].into()).unwrap()
+    }
+
+    fn mock_signed_member(seed: u8, weight: u32) -> Member {
+        let vk = mock_signing_key(seed).verifying_key().to_encoded_point(true);
+        Member {
+            pubkey: PubKey::Secp256k1(vk.as_bytes().to_vec().into()),
+            weight,
+        }
+    }
+
+    fn mock_tx(signers: &[(&str, u8)]) -> anyhow::Result<Tx> {
+        let msg_hash = sign_bytes(&[], &Addr::mock(1), CHAIN_ID, 0, Uint64::new(1_000_000))?;
+        let mut signatures = BTreeMap::new();
+        for (name, seed) in signers {
+            let signature: Signature = mock_signing_key(*seed).sign_prehash(&msg_hash)?;
+            signatures.insert(name.to_string(), signature.to_vec().into());
+        }
+
+        Ok(Tx {
+            sender:     Addr::mock(1),
+            gas_limit:  Uint64::new(1_000_000),
+            msgs:       vec![],
+            credential: to_json(&Credential { signatures })?,
+        })
+    }
+
+    // alice, bob, and charlie with weights 2, 1, and 1, and a threshold of 3
+    fn setup_members(store: &mut dyn Storage) -> anyhow::Result<()> {
+        MEMBERS.save(store, "alice", &mock_signed_member(1, 2))?;
+        MEMBERS.save(store, "bob", &mock_signed_member(2, 1))?;
+        MEMBERS.save(store, "charlie", &mock_signed_member(3, 1))?;
+        THRESHOLD.save(store, &3)?;
+        SEQUENCE.save(store, &0)?;
+        Ok(())
+    }
+
+    fn mock_before_tx(store: &mut dyn Storage, tx: Tx) -> MultisigResult<Response> {
+        before_tx(
+            BeforeTxCtx {
+                store,
+                api:             &MockApi,
+                querier:         &MockQuerier::new(),
+                chain_id:        CHAIN_ID.into(),
+                block_height:    Uint64::new(0),
+                block_timestamp: Timestamp::from_seconds(0),
+                block_hash:      hash(""),
+                contract:        Addr::mock(1),
+                simulate:        false,
+            },
+            tx,
+        )
+    }
+
+    fn mock_update_members(
+        store:     &mut dyn Storage,
+        sender:    Addr,
+        add:       BTreeMap<String, Member>,
+        remove:    BTreeSet<String>,
+        threshold: u32,
+    ) -> MultisigResult<Response> {
+        update_members(
+            ExecuteCtx {
+                store,
+                api:             &MockApi,
+                querier:         &MockQuerier::new(),
+                chain_id:        CHAIN_ID.into(),
+                block_height:    Uint64::new(0),
+                block_timestamp: Timestamp::from_seconds(0),
+                block_hash:      hash(""),
+                contract:        Addr::mock(1),
+                sender,
+                funds:           Coins::new_empty(),
+            },
+            add,
+            remove,
+            threshold,
+        )
+    }
+
+    #[test]
+    fn ensuring_threshold_works() -> anyhow::Result<()> {
+        let mut store = MockStorage::new();
+
+        MEMBERS.save(&mut store, "alice", &mock_member(2))?;
+        MEMBERS.save(&mut store, "bob", &mock_member(1))?;
+
+        assert!(matches!(ensure_threshold(&store, 0), Err(MultisigError::ZeroThreshold)));
+        assert!(ensure_threshold(&store, 3).is_ok());
+        assert!(matches!(
+            ensure_threshold(&store, 4),
+            Err(MultisigError::ThresholdTooHigh { threshold: 4, total_weight: 3 })
+        ));
+
+        // members of zero weight are not allowed
+        MEMBERS.save(&mut store, "charlie", &mock_member(0))?;
+        assert!(matches!(ensure_threshold(&store, 1), Err(MultisigError::ZeroWeight { .. })));
+
+        Ok(())
+    }
+
+    #[test]
+    fn authenticating_works() -> anyhow::Result<()> {
+        let mut store = MockStorage::new();
+        setup_members(&mut store)?;
+
+        // alice and bob have a total weight of 3, which meets the threshold
+        mock_before_tx(&mut store, mock_tx(&[("alice", 1), ("bob", 2)])?)?;
+        assert_eq!(SEQUENCE.load(&store)?, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn authenticating_below_threshold_fails() -> anyhow::Result<()> {
+        let mut store = MockStorage::new();
+        setup_members(&mut store)?;
+
+        // bob and charlie have a total weight of 2, which is below the threshold
+        let err = mock_before_tx(&mut store, mock_tx(&[("bob", 2), ("charlie", 3)])?).unwrap_err();
+        assert!(matches!(err, MultisigError::BelowThreshold { total_weight: 2, threshold: 3 }));
+        assert_eq!(SEQUENCE.load(&store)?, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn authenticating_with_non_member_fails() -> anyhow::Result<()> {
+        let mut store = MockStorage::new();
+        setup_members(&mut store)?;
+
+        // a valid signature from someone who isn't a member
+        let err = mock_before_tx(&mut store, mock_tx(&[("alice", 1), ("dave", 4)])?).unwrap_err();
+        assert!(matches!(err, MultisigError::NotMember { name } if name == "dave"));
+
+        // a member's name, but signed by someone else's key
+        let err = mock_before_tx(&mut store, mock_tx(&[("alice", 1), ("bob", 4)])?).unwrap_err();
+        assert!(matches!(err, MultisigError::Unauthorized { name } if name == "bob"));
+        assert_eq!(SEQUENCE.load(&store)?, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn updating_members_works() -> anyhow::Result<()> {
+        let mut store = MockStorage::new();
+        setup_members(&mut store)?;
+
+        let add = BTreeMap::from([("dave".to_string(), mock_signed_member(4, 3))]);
+        let remove = BTreeSet::from(["charlie".to_string()]);
+
+        // only the multisig itself can update the members
+        let err = mock_update_members(&mut store, Addr::mock(2), add.clone(), remove.clone(), 4)
+            .unwrap_err();
+        assert!(matches!(err, MultisigError::NotSelf { .. }));
+
+        mock_update_members(&mut store, Addr::mock(1), add, remove, 4)?;
+        let members = MEMBERS
+            .range(&store, None, None, Order::Ascending)
+            .map(|item| item.map(|(name, member)| (name, member.weight)))
+            .collect::<StdResult<Vec<_>>>()?;
+        assert_eq!(members, [
+            ("alice".to_string(), 2),
+            ("bob".to_string(), 1),
+            ("dave".to_string(), 3),
+        ]);
+        assert_eq!(THRESHOLD.load(&store)?, 4);
+
+        Ok(())
+    }
+
+    #[test]
+    fn updating_members_with_invalid_changes_fails() -> anyhow::Result<()> {
+        // the same member can't be both added and removed
+        let mut store = MockStorage::new();
+        setup_members(&mut store)?;
+        let add = BTreeMap::from([("bob".to_string(), mock_signed_member(2, 2))]);
+        let remove = BTreeSet::from(["bob".to_string()]);
+        let err = mock_update_members(&mut store, Addr::mock(1), add, remove, 3).unwrap_err();
+        assert!(matches!(err, MultisigError::AddedAndRemoved { name } if name == "bob"));
+
+        // a member that doesn't exist can't be removed
+        let mut store = MockStorage::new();
+        setup_members(&mut store)?;
+        let remove = BTreeSet::from(["dave".to_string()]);
+        let err =
+            mock_update_members(&mut store, Addr::mock(1), BTreeMap::new(), remove, 3).unwrap_err();
+        assert!(matches!(err, MultisigError::MemberNotFound { name } if name == "dave"));
+
+        // after removing alice, the remaining weight of 2 can't reach the threshold
+        let mut store = MockStorage::new();
+        setup_members(&mut store)?;
+        let remove = BTreeSet::from(["alice".to_string()]);
+        let err =
+            mock_update_members(&mut store, Addr::mock(1), BTreeMap::new(), remove, 3).unwrap_err();
+        assert!(matches!(err, MultisigError::ThresholdTooHigh { threshold: 3, total_weight: 2 }));
+
+        Ok(())
+    }
+}
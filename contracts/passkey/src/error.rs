@@ -0,0 +1,120 @@
+use {
+    cw_std::{Addr, CodedError, StdError},
+    thiserror::Error,
+};
+
+#[derive(Debug, Error)]
+pub enum PasskeyError {
+    #[error(transparent)]
+    Std(#[from] StdError),
+
+    #[error("Incorrect client data type! expecting: {expect}, found: {found}")]
+    IncorrectClientDataType {
+        expect: String,
+        found:  String,
+    },
+
+    #[error("Challenge in client data doesn't match the sign bytes")]
+    ChallengeMismatch,
+
+    #[error("Authenticator data is too short! expecting at least {min} bytes, found: {len}")]
+    AuthenticatorDataTooShort {
+        min: usize,
+        len: usize,
+    },
+
+    #[error("RP ID hash in authenticator data doesn't match! rp_id: {rp_id}")]
+    RpIdMismatch {
+        rp_id: String,
+    },
+
+    #[error("User presence flag is not set in authenticator data")]
+    UserNotPresent,
+
+    #[error("Signature verification failed")]
+    Unauthorized,
+
+    #[error("Only the account itself can update key! sender: {sender}")]
+    NotSelf {
+        sender: Addr,
+    },
+}
+
+impl PasskeyError {
+    pub fn incorrect_client_data_type(expect: impl Into<String>, found: impl Into<String>) -> Self {
+        Self::IncorrectClientDataType {
+            expect: expect.into(),
+            found:  found.into(),
+        }
+    }
+
+    pub fn authenticator_data_too_short(min: usize, len: usize) -> Self {
+        Self::AuthenticatorDataTooShort { min, len }
+    }
+
+    pub fn rp_id_mismatch(rp_id: impl Into<String>) -> Self {
+        Self::RpIdMismatch { rp_id: rp_id.into() }
+    }
+
+    pub fn not_self(sender: Addr) -> Self {
+        Self::NotSelf { sender }
+    }
+}
+
+impl CodedError for PasskeyError {
+    fn codespace(&self) -> &str {
+        match self {
+            Self::Std(err) => err.codespace(),
+            _ => PasskeyErrorCode::CODESPACE,
+        }
+    }
+
+    fn code(&self) -> u32 {
+        let code = match self {
+            Self::Std(err) => return err.code(),
+            Self::IncorrectClientDataType { .. } => PasskeyErrorCode::IncorrectClientDataType,
+            Self::ChallengeMismatch => PasskeyErrorCode::ChallengeMismatch,
+            Self::AuthenticatorDataTooShort { .. } => PasskeyErrorCode::AuthenticatorDataTooShort,
+            Self::RpIdMismatch { .. } => PasskeyErrorCode::RpIdMismatch,
+            Self::UserNotPresent => PasskeyErrorCode::UserNotPresent,
+            Self::Unauthorized => PasskeyErrorCode::Unauthorized,
+            Self::NotSelf { .. } => PasskeyErrorCode::NotSelf,
+        };
+        code as u32
+    }
+}
+
+/// The codes of `PasskeyError` variants, in the `passkey` codespace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum PasskeyErrorCode {
+    IncorrectClientDataType   = 1,
+    ChallengeMismatch         = 2,
+    AuthenticatorDataTooShort = 3,
+    RpIdMismatch              = 4,
+    UserNotPresent            = 5,
+    Unauthorized              = 6,
+    NotSelf                   = 7,
+}
+
+impl PasskeyErrorCode {
+    pub const CODESPACE: &'static str = "passkey";
+
+    /// Find the variant corresponding to the code. Return `None` if the code
+    /// isn't defined.
+    pub fn from_code(code: u32) -> Option<Self> {
+        let code = match code {
+            1 => Self::IncorrectClientDataType,
+            2 => Self::ChallengeMismatch,
+            3 => Self::AuthenticatorDataTooShort,
+            4 => Self::RpIdMismatch,
+            5 => Self::UserNotPresent,
+            6 => Self::Unauthorized,
+            7 => Self::NotSelf,
+            _ => return None,
+        };
+        Some(code)
+    }
+}
+
+pub type PasskeyResult<T> = std::result::Result<T, PasskeyError>;
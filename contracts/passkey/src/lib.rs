@@ -0,0 +1,297 @@
+mod error;
+
+pub use crate::error::{PasskeyError, PasskeyErrorCode, PasskeyResult};
+#[cfg(not(feature = "library"))]
+use cw_std::entry_point;
+use {
+    cw_account::sign_bytes,
+    cw_std::{
+        cw_serde, from_json, hash, to_json, BeforeTxCtx, Binary, ExecuteCtx, Hash, InstantiateCtx,
+        Item, QueryCtx, ReceiveCtx, Response, Tx,
+    },
+    data_encoding::BASE64URL_NOPAD,
+    serde::Deserialize,
+};
+
+const PUBKEY:   Item<Binary> = Item::new("pk");
+const RP_ID:    Item<String> = Item::new("rp");
+const SEQUENCE: Item<u32>    = Item::new("seq");
+
+/// The WebAuthn ceremony type of assertions, i.e. signing with an existing
+/// credential, as opposed to registering one ("webauthn.create").
+const WEBAUTHN_TYPE_GET: &str = "webauthn.get";
+
+/// Length of the fixed part of the authenticator data: 32 bytes of RP ID hash,
+/// 1 byte of flags, and 4 bytes of signature counter.
+const AUTHENTICATOR_DATA_MIN_LEN: usize = 37;
+
+/// The "user present" bit in the authenticator data flags.
+const FLAG_USER_PRESENT: u8 = 0x01;
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    /// The passkey's secp256r1 public key, in SEC1 encoding.
+    pub pubkey: Binary,
+    /// The WebAuthn relying party ID, typically the domain of the web app that
+    /// created the passkey, e.g. "example.com".
+    pub rp_id:  String,
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    UpdateKey {
+        new_pubkey: Binary,
+    },
+}
+
+#[cw_serde]
+pub enum QueryMsg {
+    /// Query the state of the account, including its public key, relying party
+    /// ID, and sequence.
+    /// Returns: StateResponse
+    State {},
+}
+
+#[cw_serde]
+pub struct StateResponse {
+    pub pubkey:   Binary,
+    pub rp_id:    String,
+    pub sequence: u32,
+}
+
+/// The credential of a transaction, which is a WebAuthn assertion.
+///
+/// The authenticator signs `authenticator_data | sha256(client_data_json)`,
+/// with the challenge in the client data being the transaction's sign bytes
+/// (as given by `cw_account::sign_bytes`), base64url-encoded without padding.
+#[cw_serde]
+pub struct Credential {
+    pub authenticator_data: Binary,
+    pub client_data_json:   Binary,
+    /// The secp256r1 signature in the fixed 64-byte `r | s` encoding.
+    /// Authenticators output DER-encoded signatures, which the client needs to
+    /// convert.
+    pub signature:          Binary,
+}
+
+/// The fields we care about in the client data. Authenticators may include
+/// other fields, which are ignored.
+#[derive(Deserialize)]
+struct ClientData {
+    #[serde(rename = "type")]
+    ty:        String,
+    challenge: String,
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(ctx: InstantiateCtx, msg: InstantiateMsg) -> PasskeyResult<Response> {
+    PUBKEY.save(ctx.store, &msg.pubkey)?;
+    RP_ID.save(ctx.store, &msg.rp_id)?;
+    SEQUENCE.save(ctx.store, &0)?;
+
+    Ok(Response::new())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn receive(ctx: ReceiveCtx) -> PasskeyResult<Response> {
+    // do nothing, accept all transfers. log the receipt to events
+    Ok(Response::new()
+        .add_attribute("method", "receive")
+        .add_attribute("sender", ctx.sender)
+        .add_attribute("funds", ctx.funds.to_string()))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn before_tx(ctx: BeforeTxCtx, tx: Tx) -> PasskeyResult<Response> {
+    let pubkey = PUBKEY.load(ctx.store)?;
+    let rp_id = RP_ID.load(ctx.store)?;
+    let mut sequence = SEQUENCE.load(ctx.store)?;
+
+    // prepare the hash that is expected to be the challenge
+    let sign_bytes = sign_bytes(&tx.msgs, &tx.sender, &ctx.chain_id, sequence, tx.gas_limit)?;
+
+    // verify the WebAuthn assertion.
+    // in simulate mode, the credential may be missing or invalid, so this is
+    // skipped. a signature verification is still performed though (with the
+    // result ignored), such that its gas cost is included in the estimate.
+    if ctx.simulate {
+        let _ = ctx.secp256r1_verify(&sign_bytes, b"", &pubkey);
+    } else {
+        let credential: Credential = from_json(&tx.credential)?;
+        let msg_hash = webauthn_msg_hash(&credential, &sign_bytes, &rp_id)?;
+        ctx.secp256r1_verify(msg_hash, &credential.signature, &pubkey)
+            .map_err(|_| PasskeyError::Unauthorized)?;
+    }
+
+    // update sequence
+    sequence += 1;
+    SEQUENCE.save(ctx.store, &sequence)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "before_tx")
+        .add_attribute("next_sequence", sequence.to_string()))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(ctx: ExecuteCtx, msg: ExecuteMsg) -> PasskeyResult<Response> {
+    match msg {
+        ExecuteMsg::UpdateKey {
+            new_pubkey,
+        } => update_key(ctx, new_pubkey),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(ctx: QueryCtx, msg: QueryMsg) -> PasskeyResult<Binary> {
+    match msg {
+        QueryMsg::State {} => to_json(&query_state(ctx)?),
+    }
+    .map_err(Into::into)
+}
+
+pub fn update_key(ctx: ExecuteCtx, new_pubkey: Binary) -> PasskeyResult<Response> {
+    if ctx.sender != ctx.contract {
+        return Err(PasskeyError::not_self(ctx.sender));
+    }
+
+    PUBKEY.save(ctx.store, &new_pubkey)?;
+
+    Ok(Response::new())
+}
+
+pub fn query_state(ctx: QueryCtx) -> PasskeyResult<StateResponse> {
+    Ok(StateResponse {
+        pubkey:   PUBKEY.load(ctx.store)?,
+        rp_id:    RP_ID.load(ctx.store)?,
+        sequence: SEQUENCE.load(ctx.store)?,
+    })
+}
+
+/// Check the WebAuthn assertion's authenticator data and client data, and
+/// return the hash that the authenticator is expected to have signed:
+///
+/// ```plain
+/// sha256(authenticator_data | sha256(client_data_json))
+/// ```
+///
+/// We check that:
+/// - the client data is of an assertion, with the sign bytes as challenge;
+/// - the authenticator data is for our relying party ID;
+/// - the user was present when signing.
+///
+/// We don't check the origin in the client data. The relying party ID is what
+/// the authenticator scopes the passkey to, which is sufficient here.
+pub fn webauthn_msg_hash(
+    credential: &Credential,
+    sign_bytes: &Hash,
+    rp_id:      &str,
+) -> PasskeyResult<Hash> {
+    let client_data: ClientData = from_json(&credential.client_data_json)?;
+    if client_data.ty != WEBAUTHN_TYPE_GET {
+        return Err(PasskeyError::incorrect_client_data_type(WEBAUTHN_TYPE_GET, client_data.ty));
+    }
+    if client_data.challenge != BASE64URL_NOPAD.encode(sign_bytes) {
+        return Err(PasskeyError::ChallengeMismatch);
+    }
+
+    let authenticator_data = &credential.authenticator_data;
+    if authenticator_data.len() < AUTHENTICATOR_DATA_MIN_LEN {
+        return Err(PasskeyError::authenticator_data_too_short(
+            AUTHENTICATOR_DATA_MIN_LEN,
+            authenticator_data.len(),
+        ));
+    }
+    if authenticator_data[..32] != *hash(rp_id) {
+        return Err(PasskeyError::rp_id_mismatch(rp_id));
+    }
+    if authenticator_data[32] & FLAG_USER_PRESENT == 0 {
+        return Err(PasskeyError::UserNotPresent);
+    }
+
+    let client_data_hash = hash(&credential.client_data_json);
+
+    Ok(hash([authenticator_data.as_ref(), client_data_hash.as_ref()].concat()))
+}
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        cw_crypto::secp256r1_verify,
+        cw_std::{Addr, Uint64},
+        hex_literal::hex,
+    };
+
+    // an assertion by a P-256 passkey for the relying party "example.com",
+    // signing a transaction from `Addr::mock(1)` with no message, sequence 0,
+    // gas limit 1,000,000, and chain ID "dev-1"
+    const RP_ID:              &str = "example.com";
+    const PUBKEY:             [u8; 33] = hex!("027b243f128f2dce03fc0cd87cce16e1db78fdb7dff042c85c34d43f2eb5f520cc");
+    const AUTHENTICATOR_DATA: [u8; 37] = hex!("a379a6f6eeafb9a55e378c118034e2751e682fab9f2d30ab13d2125586ce19470500000001");
+    const CLIENT_DATA_JSON:   &str = r#"{"type":"webauthn.get","challenge":"J9QGxs9fRTc-C5K0j6Q4W6EwZyb8YO1X6Dyji0EmyNo","origin":"https://example.com","crossOrigin":false}"#;
+    const SIGNATURE:          [u8; 64] = hex!("42ce62c8feceab67da6fd8987c930e652a514e314b7e3fecc6b242186fa59b0105ff086da9017e7cad31fced672624489563af4a59181dffd3bcc7bfff98b211");
+
+    fn mock_sign_bytes() -> Hash {
+        sign_bytes(&[], &Addr::mock(1), "dev-1", 0, Uint64::new(1_000_000)).unwrap()
+    }
+
+    fn mock_credential() -> Credential {
+        Credential {
+            authenticator_data: AUTHENTICATOR_DATA.to_vec().into(),
+            client_data_json:   CLIENT_DATA_JSON.as_bytes().to_vec().into(),
+            signature:          SIGNATURE.to_vec().into(),
+        }
+    }
+
+    #[test]
+    fn verifying_webauthn_assertion() {
+        let credential = mock_credential();
+        let msg_hash = webauthn_msg_hash(&credential, &mock_sign_bytes(), RP_ID).unwrap();
+        assert!(secp256r1_verify(&msg_hash, &credential.signature, &PUBKEY).is_ok());
+
+        // incorrect relying party ID
+        let err = webauthn_msg_hash(&credential, &mock_sign_bytes(), "evil.com").unwrap_err();
+        assert!(matches!(err, PasskeyError::RpIdMismatch { rp_id } if rp_id == "evil.com"));
+
+        // incorrect challenge, i.e. signing a different transaction
+        let false_sign_bytes = sign_bytes(&[], &Addr::mock(1), "dev-1", 1, Uint64::new(1_000_000));
+        let err = webauthn_msg_hash(&credential, &false_sign_bytes.unwrap(), RP_ID).unwrap_err();
+        assert!(matches!(err, PasskeyError::ChallengeMismatch));
+
+        // incorrect client data type, i.e. a registration instead of an assertion
+        let mut false_credential = mock_credential();
+        false_credential.client_data_json =
+            CLIENT_DATA_JSON.replace("webauthn.get", "webauthn.create").into_bytes().into();
+        let err = webauthn_msg_hash(&false_credential, &mock_sign_bytes(), RP_ID).unwrap_err();
+        assert!(matches!(err, PasskeyError::IncorrectClientDataType { .. }));
+
+        // truncated authenticator data
+        let mut false_credential = mock_credential();
+        false_credential.authenticator_data = AUTHENTICATOR_DATA[..36].to_vec().into();
+        let err = webauthn_msg_hash(&false_credential, &mock_sign_bytes(), RP_ID).unwrap_err();
+        assert!(matches!(err, PasskeyError::AuthenticatorDataTooShort { min: 37, len: 36 }));
+
+        // tampered authenticator data, e.g. with a different signature counter,
+        // passes our checks but fails signature verification
+        let mut false_credential = mock_credential();
+        false_credential.authenticator_data = {
+            let mut authenticator_data = AUTHENTICATOR_DATA;
+            authenticator_data[36] = 2;
+            authenticator_data.to_vec().into()
+        };
+        let msg_hash = webauthn_msg_hash(&false_credential, &mock_sign_bytes(), RP_ID).unwrap();
+        assert!(secp256r1_verify(&msg_hash, &false_credential.signature, &PUBKEY).is_err());
+
+        // user not present
+        let mut false_credential = mock_credential();
+        false_credential.authenticator_data = {
+            let mut authenticator_data = AUTHENTICATOR_DATA;
+            authenticator_data[32] = 0x04;
+            authenticator_data.to_vec().into()
+        };
+        let err = webauthn_msg_hash(&false_credential, &mock_sign_bytes(), RP_ID).unwrap_err();
+        assert!(matches!(err, PasskeyError::UserNotPresent));
+    }
+}
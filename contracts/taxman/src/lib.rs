@@ -0,0 +1,177 @@
+//! A reference implementation of the taxman contract.
+//!
+//! Charges a flat fee of `fee_rate` units of `fee_denom` per unit of gas
+//! consumed. Before a tx is processed, the maximum fee (based on the tx's gas
+//! limit) is withheld from the sender. After the tx is processed, the unused
+//! portion (based on the amount of gas actually used) is refunded.
+
+#[cfg(not(feature = "library"))]
+use cw_std::entry_point;
+use cw_std::{
+    cw_serde, to_json, Binary, Coins, InstantiateCtx, Item, Message, QueryCtx, ReceiveCtx, Response,
+    StdResult, TaxmanCtx, Tx, TxOutcome, Uint128, Uint64,
+};
+
+pub const FEE_CONFIG: Item<FeeConfig> = Item::new("cfg");
+
+#[cw_serde]
+pub struct FeeConfig {
+    /// The denomination of the coin that fees are paid in.
+    pub fee_denom: String,
+    /// Amount of `fee_denom` to be charged per unit of gas consumed.
+    pub fee_rate:  Uint128,
+}
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub config: FeeConfig,
+}
+
+#[cw_serde]
+pub enum QueryMsg {
+    /// Returns: FeeConfig
+    Config {},
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(ctx: InstantiateCtx, msg: InstantiateMsg) -> StdResult<Response> {
+    FEE_CONFIG.save(ctx.store, &msg.config)?;
+
+    Ok(Response::new())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn receive(_ctx: ReceiveCtx) -> StdResult<Response> {
+    // do nothing, accept all transfers
+    Ok(Response::new())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn withhold_fee(ctx: TaxmanCtx, tx: Tx) -> anyhow::Result<Response> {
+    // in simulation mode, the sender may not have enough funds to cover the
+    // fee (the gas limit is typically set to a large value), so we don't
+    // withhold anything
+    if ctx.simulate {
+        return Ok(Response::new());
+    }
+
+    let cfg = FEE_CONFIG.load(ctx.store)?;
+    let withhold_amount = compute_fee(&cfg, tx.gas_limit)?;
+
+    if withhold_amount.is_zero() {
+        return Ok(Response::new());
+    }
+
+    // move the coins from the sender to the taxman. the bank contract only
+    // allows this if the request comes from the taxman.
+    let bank = ctx.query_info()?.config.bank;
+
+    Ok(Response::new()
+        .add_attribute("method", "withhold_fee")
+        .add_attribute("sender", &tx.sender)
+        .add_attribute("withhold_amount", withhold_amount)
+        .add_message(Message::Execute {
+            contract: bank,
+            msg: to_json(&cw_bank::ExecuteMsg::ForceTransfer {
+                from:  tx.sender,
+                to:    ctx.contract,
+                coins: Coins::new_one(cfg.fee_denom, withhold_amount),
+            })?,
+            funds: Coins::new_empty(),
+        }))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn finalize_fee(ctx: TaxmanCtx, tx: Tx, outcome: TxOutcome) -> anyhow::Result<Response> {
+    // nothing was withheld in simulation mode, so there's nothing to refund
+    if ctx.simulate {
+        return Ok(Response::new());
+    }
+
+    let cfg = FEE_CONFIG.load(ctx.store)?;
+    let withhold_amount = compute_fee(&cfg, tx.gas_limit)?;
+    let charge_amount = compute_fee(&cfg, outcome.gas_used)?;
+    let refund_amount = withhold_amount.checked_sub(charge_amount)?;
+
+    let mut resp = Response::new()
+        .add_attribute("method", "finalize_fee")
+        .add_attribute("sender", &tx.sender)
+        .add_attribute("charge_amount", charge_amount)
+        .add_attribute("refund_amount", refund_amount);
+
+    if !refund_amount.is_zero() {
+        resp = resp.add_message(Message::Transfer {
+            to:    tx.sender,
+            coins: Coins::new_one(cfg.fee_denom, refund_amount),
+        });
+    }
+
+    Ok(resp)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(ctx: QueryCtx, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_json(&FEE_CONFIG.load(ctx.store)?),
+    }
+}
+
+fn compute_fee(cfg: &FeeConfig, gas: Uint64) -> StdResult<Uint128> {
+    Uint128::new(gas.u64() as u128).checked_mul(cfg.fee_rate)
+}
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        cw_std::{hash, Addr, GenericResult, MockApi, MockQuerier, MockStorage, Timestamp},
+    };
+
+    #[test]
+    fn refunding_unused_fee_works() -> anyhow::Result<()> {
+        let mut store = MockStorage::new();
+
+        let mock_sender = Addr::mock(1);
+        let mock_taxman = Addr::mock(2);
+
+        FEE_CONFIG.save(&mut store, &FeeConfig {
+            fee_denom: "uatom".into(),
+            fee_rate:  Uint128::new(2),
+        })?;
+
+        let ctx = TaxmanCtx {
+            store:           &mut store,
+            api:             &MockApi,
+            querier:         &MockQuerier::new(),
+            chain_id:        "dev-1".into(),
+            block_height:    Uint64::new(0),
+            block_timestamp: Timestamp::from_seconds(0),
+            block_hash:      hash(""),
+            contract:        mock_taxman,
+            simulate:        false,
+        };
+        let tx = Tx {
+            sender:     mock_sender.clone(),
+            gas_limit:  Uint64::new(1000),
+            msgs:       vec![],
+            credential: Binary::empty(),
+        };
+        let outcome = TxOutcome {
+            gas_limit: Uint64::new(1000),
+            gas_used:  Uint64::new(300),
+            result:    GenericResult::Ok(vec![]),
+        };
+
+        // withheld 2000 uatom, charged 600 uatom, so 1400 uatom is refunded
+        let res = finalize_fee(ctx, tx, outcome)?;
+        assert_eq!(res.submsgs.len(), 1);
+        assert_eq!(res.submsgs[0].msg, Message::Transfer {
+            to:    mock_sender,
+            coins: Coins::new_one("uatom", Uint128::new(1400)),
+        });
+
+        Ok(())
+    }
+}
@@ -13,13 +13,15 @@ use {
         from_json, hash, to_json, Addr, BlockInfo, Coins, Config, GenesisState, Message,
         QueryRequest, QueryResponse, Timestamp, Tx, Uint64, GENESIS_SENDER,
     },
+    cw_vm::ModuleCache,
     k256::ecdsa::{signature::DigestSigner, Signature, SigningKey, VerifyingKey},
     rand::{rngs::StdRng, SeedableRng},
     serde::{de::DeserializeOwned, ser::Serialize},
-    std::{env, fs::File, io::Read, path::PathBuf},
+    std::{env, fs::File, io::Read, num::NonZeroUsize, path::PathBuf},
 };
 
 const MOCK_CHAIN_ID: &str = "dev-1";
+const MOCK_GAS_LIMIT: u64 = 10_000_000;
 
 fn main() -> anyhow::Result<()> {
     tracing_subscriber::fmt().with_max_level(tracing::Level::DEBUG).init();
@@ -27,7 +29,7 @@ fn main() -> anyhow::Result<()> {
     println!("🤖 Creating app");
     let data_dir = TempDataDir::new("_cw_app_account");
     let store = BaseStore::open(&data_dir)?;
-    let app = App::new(store);
+    let app = App::new(store, ModuleCache::new(NonZeroUsize::new(10).unwrap(), None)?);
 
     println!("🤖 Reading wasm byte code from file");
     let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR")?);
@@ -65,8 +67,12 @@ fn main() -> anyhow::Result<()> {
     app.do_init_chain(MOCK_CHAIN_ID.into(), mock_block_info(0, 0), &to_json(&GenesisState {
         config: Config {
             // we don't need an owner or a bank contract for this demo
-            owner: None,
-            bank:  Addr::mock(0),
+            owner:          None,
+            bank:           Addr::mock(0),
+            taxman:         None,
+            begin_blockers: vec![],
+            end_blockers:   vec![],
+            max_msg_depth:  None,
         },
         msgs: vec![
             Message::StoreCode {
@@ -195,21 +201,23 @@ fn new_tx(
             })?,
             0,
             false,
-        )?)?
+        )?.0)?
         .as_wasm_smart()
         .data)?
         .sequence;
 
     // create sign bytes
     // need to wrap bytes in Identity256 so that it can be used in sign_digest
-    let sign_bytes = sign_bytes(&msgs, sender, MOCK_CHAIN_ID, sequence)?;
-    let sign_bytes = Identity256::from_bytes(&sign_bytes);
+    let sign_bytes =
+        sign_bytes(&msgs, sender, MOCK_CHAIN_ID, sequence, Uint64::new(MOCK_GAS_LIMIT))?;
+    let sign_bytes = Identity256::from_bytes(&sign_bytes.into_slice());
 
     // sign the sign bytes
     let signature: Signature = sk.sign_digest(sign_bytes);
 
     let tx = Tx {
         sender:     sender.clone(),
+        gas_limit:  Uint64::new(MOCK_GAS_LIMIT),
         credential: signature.to_vec().into(),
         msgs,
     };
@@ -220,7 +228,7 @@ fn new_tx(
 }
 
 fn query(app: &App, req: QueryRequest) -> anyhow::Result<()> {
-    let resp = app.do_query_app(&to_json(&req)?, 0, false)?;
+    let (resp, ..) = app.do_query_app(&to_json(&req)?, 0, false)?;
     println!("{}", serde_json::to_string_pretty(&resp)?);
     Ok(())
 }
@@ -239,7 +247,7 @@ where
             })?,
             0,
             false,
-        )?)?
+        )?.0)?
         .as_wasm_smart()
         .data,
     )?;
@@ -14,16 +14,20 @@ use {
         from_json, hash, to_json, Addr, Binary, BlockInfo, Coin, Coins, Config, GenesisState, Hash,
         Message, QueryRequest, QueryResponse, Timestamp, Tx, Uint128, Uint64, GENESIS_SENDER,
     },
+    cw_vm::ModuleCache,
     k256::ecdsa::{signature::DigestSigner, Signature, SigningKey, VerifyingKey},
     lazy_static::lazy_static,
     rand::{rngs::StdRng, SeedableRng},
-    std::{collections::BTreeMap, env, fs::File, io::Read, path::PathBuf},
+    std::{collections::BTreeMap, env, fs::File, io::Read, num::NonZeroUsize, path::PathBuf},
 };
 
 lazy_static! {
     // chain ID for the purpose of this example
     static ref CHAIN_ID: &'static str = "dev-1";
 
+    // gas limit for each transaction
+    static ref GAS_LIMIT: Uint64 = Uint64::new(10_000_000);
+
     // salt for instantiating the bank contract
     static ref BANK_SALT: Binary = b"bank".to_vec().into();
 
@@ -37,7 +41,7 @@ fn main() -> anyhow::Result<()> {
     println!("🤖 Creating app");
     let data_dir = TempDataDir::new("_cw_app_bank");
     let store = BaseStore::open(&data_dir)?;
-    let app = App::new(store);
+    let app = App::new(store, ModuleCache::new(NonZeroUsize::new(10).unwrap(), None)?);
 
     println!("🤖 Reading wasm byte codes from files");
     let account_wasm = read_wasm_byte_code("cw_account")?;
@@ -197,8 +201,12 @@ fn make_genesis_state(
     // upload codes and instantiate bank contract
     let mut gen_state = GenesisState {
         config: Config {
-            owner: None,
-            bank:  bank_addr,
+            owner:          None,
+            bank:           bank_addr,
+            taxman:         None,
+            begin_blockers: vec![],
+            end_blockers:   vec![],
+            max_msg_depth:  None,
         },
         msgs: vec![
             Message::StoreCode {
@@ -252,16 +260,14 @@ fn make_transfer_tx<const N: usize>(
     }
 
     // for the purpose of this example, we assume sequence number is zero
-    let sign_bytes = Identity256::from_bytes(&sign_bytes(
-        &msgs,
-        &accounts[from].addr,
-        &CHAIN_ID,
-        0,
-    )?);
+    let sign_bytes = Identity256::from_bytes(
+        &sign_bytes(&msgs, &accounts[from].addr, &CHAIN_ID, 0, *GAS_LIMIT)?.into_slice(),
+    );
     let signature: Signature = accounts[from].sk.sign_digest(sign_bytes);
 
     to_json(&Tx {
         sender:     accounts[from].addr.clone(),
+        gas_limit:  *GAS_LIMIT,
         credential: signature.to_vec().into(),
         msgs,
     })
@@ -287,7 +293,7 @@ fn query_all_balances(app: &App, accounts: &[TestAccount]) -> anyhow::Result<()>
             })?,
             0,
             false,
-        )?)?
+        )?.0)?
         .as_balances();
 
         if !balances.is_empty() {
@@ -308,7 +314,7 @@ fn query_supplies(app: &App) -> anyhow::Result<()> {
         })?,
         0,
         false,
-    )?)?
+    )?.0)?
     .as_supplies();
 
     println!("{}", serde_json::to_string_pretty(&supplies)?);
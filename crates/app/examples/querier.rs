@@ -7,8 +7,9 @@ use {
         from_json, hash, to_json, Addr, BlockInfo, Coins, Config, Empty, GenesisState, Message,
         QueryRequest, QueryResponse, Timestamp, Uint64, GENESIS_SENDER,
     },
+    cw_vm::ModuleCache,
     serde::ser::Serialize,
-    std::{env, fs::File, io::Read, path::PathBuf},
+    std::{env, fs::File, io::Read, num::NonZeroUsize, path::PathBuf},
 };
 
 const MOCK_CHAIN_ID: &str = "dev-1";
@@ -19,7 +20,7 @@ fn main() -> anyhow::Result<()> {
     println!("🤖 Creating app");
     let data_dir = TempDataDir::new("_cw_app_querier");
     let store = BaseStore::open(&data_dir)?;
-    let app = App::new(store);
+    let app = App::new(store, ModuleCache::new(NonZeroUsize::new(10).unwrap(), None)?);
 
     println!("🤖 Reading wasm byte code from file");
     let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR")?);
@@ -45,8 +46,12 @@ fn main() -> anyhow::Result<()> {
     app.do_init_chain(MOCK_CHAIN_ID.into(), mock_block_info(0, 0), &to_json(&GenesisState {
         config: Config {
             // we don't need an owner or a bank contract for this demo
-            owner: None,
-            bank:  Addr::mock(0),
+            owner:          None,
+            bank:           Addr::mock(0),
+            taxman:         None,
+            begin_blockers: vec![],
+            end_blockers:   vec![],
+            max_msg_depth:  None,
         },
         msgs: vec![
             Message::StoreCode {
@@ -105,7 +110,7 @@ where
         })?,
         0,
         false,
-    )?)?
+    )?.0)?
     .as_wasm_smart()
     .data;
 
@@ -1,11 +1,16 @@
 use {
-    crate::{App, AppResult}, cw_jmt::Proof, cw_std::{Attribute, BlockInfo, Event, Hash, Timestamp, Uint64, GENESIS_BLOCK_HASH}, prost::bytes::Bytes, std::{any::type_name, net::ToSocketAddrs}, tendermint_abci::{Application, Error as ABCIError, ServerBuilder}, tendermint_proto::{
+    crate::{App, AppError, AppResult, TxOutcome}, cw_db::{DbError, Snapshot}, cw_jmt::Proof, cw_std::{Attribute, BlockInfo, CodedError, Event, Hash, Timestamp, Uint64, GENESIS_BLOCK_HASH}, prost::bytes::Bytes, std::{any::type_name, net::ToSocketAddrs}, tendermint_abci::{Application, Error as ABCIError, ServerBuilder}, tendermint_proto::{
         abci::{
-            Event as TmEvent, EventAttribute as TmAttribute, ExecTxResult, RequestCheckTx,
-            RequestFinalizeBlock, RequestInfo, RequestInitChain, RequestQuery, ResponseCheckTx,
-            ResponseCommit, ResponseFinalizeBlock, ResponseInfo, ResponseInitChain, ResponseQuery,
+            response_apply_snapshot_chunk::Result as ApplySnapshotChunkResult,
+            response_offer_snapshot::Result as OfferSnapshotResult, Event as TmEvent,
+            EventAttribute as TmAttribute, ExecTxResult, RequestApplySnapshotChunk,
+            RequestCheckTx, RequestFinalizeBlock, RequestInfo, RequestInitChain,
+            RequestLoadSnapshotChunk, RequestOfferSnapshot, RequestQuery,
+            ResponseApplySnapshotChunk, ResponseCheckTx, ResponseCommit, ResponseFinalizeBlock,
+            ResponseInfo, ResponseInitChain, ResponseListSnapshots, ResponseLoadSnapshotChunk,
+            ResponseOfferSnapshot, ResponseQuery, Snapshot as TmSnapshot,
         }, crypto::{ProofOp, ProofOps}, google::protobuf::Timestamp as TmTimestamp,
-    }, tracing::Value
+    }, tracing::{warn, Value}
 };
 
 impl App {
@@ -58,9 +63,9 @@ impl Application for App {
         let block = from_tm_block(req.height, req.time, Some(req.hash));
 
         match self.do_finalize_block(block, req.txs) {
-            Ok((app_hash, tx_results)) => {
+            Ok((app_hash, events, tx_results)) => {
                 ResponseFinalizeBlock {
-                    events:                  vec![], // this should be begin/endblocker events, which we don't have yet
+                    events:                  events.into_iter().map(to_tm_event).collect(),
                     tx_results:              tx_results.into_iter().map(to_tm_tx_result).collect(),
                     validator_updates:       vec![],
                     consensus_param_updates: None,
@@ -82,25 +87,83 @@ impl Application for App {
         }
     }
 
-    fn check_tx(&self, _req: RequestCheckTx) -> ResponseCheckTx {
-        // TODO
-        ResponseCheckTx {
-            ..Default::default()
+    fn check_tx(&self, req: RequestCheckTx) -> ResponseCheckTx {
+        match self.do_check_tx(req.tx) {
+            Ok(outcome) => match outcome.result {
+                Ok(events) => {
+                    ResponseCheckTx {
+                        code:       0,
+                        gas_wanted: outcome.gas_limit as i64,
+                        gas_used:   outcome.gas_used as i64,
+                        events:     events.into_iter().map(to_tm_event).collect(),
+                        ..Default::default()
+                    }
+                },
+                Err(err) => {
+                    ResponseCheckTx {
+                        code:       err.code(),
+                        codespace:  err.codespace().into(),
+                        log:        err.to_string(),
+                        gas_wanted: outcome.gas_limit as i64,
+                        gas_used:   outcome.gas_used as i64,
+                        ..Default::default()
+                    }
+                },
+            },
+            // failed to deserialize the tx, or to load data from the check state
+            Err(err) => {
+                ResponseCheckTx {
+                    code:      err.code(),
+                    codespace: err.codespace().into(),
+                    log:       err.to_string(),
+                    ..Default::default()
+                }
+            },
         }
     }
 
-    // TODO: From ABCI docs (https://github.com/cometbft/cometbft/blob/main/spec/abci/abci++_methods.md):
+    // From ABCI docs (https://github.com/cometbft/cometbft/blob/main/spec/abci/abci++_methods.md):
     //
     // > Applications MUST interpret "/store" or any path starting with "/store/"
     // > as a query by key on the underlying store, in which case a key SHOULD
     // > be specified in data. Applications SHOULD allow queries over specific
     // > types like /accounts/... or /votes/....
     //
-    // Currently we're going neither of these. We ignore `path`, `height`, and
-    // `prove` fields, and interpret `data` as a JSON-encoded QueryRequest.
+    // Besides "/store", we support "/app", which interprets `data` as a JSON-
+    // encoded QueryRequest, and "/simulate". Any other path is interpreted as
+    // a REST-like typed query, such as "/account/{address}"; see
+    // `parse_query_path` for the full list.
     fn query(&self, req: RequestQuery) -> ResponseQuery {
         match req.path.as_str() {
             "/app" => match self.do_query_app(&req.data, req.height as u64, req.prove) {
+                Ok((res, proof, height)) => {
+                    let proof_ops = proof.map(|proof| {
+                        ProofOps {
+                            ops: vec![ProofOp {
+                                r#type: type_name::<Proof>().into(),
+                                key:    req.data.into(),
+                                data:   proof.into(),
+                            }],
+                        }
+                    });
+                    ResponseQuery {
+                        code:      0,
+                        value:     res.to_vec().into(),
+                        height:    height as i64,
+                        proof_ops,
+                        ..Default::default()
+                    }
+                },
+                Err(err) => {
+                    ResponseQuery {
+                        code:      err.code(),
+                        codespace: err.codespace().into(),
+                        log:       err.to_string(),
+                        ..Default::default()
+                    }
+                },
+            },
+            "/simulate" => match self.do_simulate(&req.data) {
                 Ok(res) => {
                     ResponseQuery {
                         code:  0,
@@ -110,51 +173,164 @@ impl Application for App {
                 },
                 Err(err) => {
                     ResponseQuery {
-                        code:      1,
-                        codespace: "app".into(),
+                        code:      err.code(),
+                        codespace: err.codespace().into(),
                         log:       err.to_string(),
                         ..Default::default()
                     }
                 },
             },
-            "/store" => match self.do_query_store(&req.data, req.height as u64, req.prove) {
-                Ok((value, proof)) => {
-                    let proof_ops = proof.map(|proof| {
+            path if path == "/store" || path.starts_with("/store/") => {
+                match self.do_query_store(&req.data, req.height as u64, req.prove) {
+                    Ok((value, proof, height)) => {
+                        let proof_ops = proof.map(|proof| {
+                            ProofOps {
+                                ops: vec![ProofOp {
+                                    r#type: type_name::<Proof>().into(),
+                                    key:    req.data.into(),
+                                    data:   proof.into(),
+                                }],
+                            }
+                        });
+                        ResponseQuery {
+                            code:      0,
+                            value:     value.unwrap_or_default().into(),
+                            height:    height as i64,
+                            proof_ops,
+                            ..Default::default()
+                        }
+                    },
+                    Err(err) => {
+                        ResponseQuery {
+                            code:      err.code(),
+                            codespace: err.codespace().into(),
+                            log:       err.to_string(),
+                            ..Default::default()
+                        }
+                    },
+                }
+            },
+            path => match self.do_query_path(path, req.height as u64, req.prove) {
+                Ok((res, proof, height)) => {
+                    // the proof is for the storage key that the path looks up,
+                    // same as that of a "/store" query for that key
+                    let proof_ops = proof.map(|(key, proof)| {
                         ProofOps {
                             ops: vec![ProofOp {
                                 r#type: type_name::<Proof>().into(),
-                                key:    req.data.into(),
+                                key,
                                 data:   proof.into(),
                             }],
                         }
                     });
                     ResponseQuery {
                         code:      0,
-                        value:     value.unwrap_or_default().into(),
-                        height:    req.height,
+                        value:     res.to_vec().into(),
+                        height:    height as i64,
                         proof_ops,
                         ..Default::default()
                     }
                 },
                 Err(err) => {
                     ResponseQuery {
-                        code:      1,
-                        codespace: "store".into(),
+                        code:      err.code(),
+                        codespace: err.codespace().into(),
                         log:       err.to_string(),
                         ..Default::default()
                     }
                 },
-            }
-            unknown => {
-                ResponseQuery {
-                    code:      1,
-                    codespace: "app".into(),
-                    log:       format!("unknown path `{unknown}`; must be `/app` or `/store`"),
-                    ..Default::default()
+            },
+        }
+
+    }
+
+    fn list_snapshots(&self) -> ResponseListSnapshots {
+        match self.do_list_snapshots() {
+            Ok(snapshots) => {
+                ResponseListSnapshots {
+                    snapshots: snapshots.into_iter().map(to_tm_snapshot).collect(),
                 }
-            }
+            },
+            // snapshots are only served to peers doing state sync. failing to
+            // list them shouldn't take down the node; just offer none.
+            Err(err) => {
+                warn!(err = err.to_string(), "Failed to list snapshots");
+                ResponseListSnapshots::default()
+            },
+        }
+    }
+
+    fn load_snapshot_chunk(&self, req: RequestLoadSnapshotChunk) -> ResponseLoadSnapshotChunk {
+        match self.do_load_snapshot_chunk(req.height, req.format, req.chunk) {
+            Ok(chunk) => {
+                ResponseLoadSnapshotChunk {
+                    chunk: chunk.unwrap_or_default().into(),
+                }
+            },
+            Err(err) => {
+                warn!(err = err.to_string(), "Failed to load snapshot chunk");
+                ResponseLoadSnapshotChunk::default()
+            },
+        }
+    }
+
+    fn offer_snapshot(&self, req: RequestOfferSnapshot) -> ResponseOfferSnapshot {
+        let result = match from_tm_snapshot(req.snapshot, req.app_hash) {
+            Ok((snapshot, app_hash)) => match self.do_offer_snapshot(snapshot, app_hash) {
+                Ok(()) => OfferSnapshotResult::Accept,
+                Err(AppError::Db(DbError::UnsupportedSnapshotFormat { .. })) => {
+                    OfferSnapshotResult::RejectFormat
+                },
+                Err(err) => {
+                    warn!(err = err.to_string(), "Rejected snapshot");
+                    OfferSnapshotResult::Reject
+                },
+            },
+            Err(err) => {
+                warn!(err = err.to_string(), "Rejected snapshot");
+                OfferSnapshotResult::Reject
+            },
+        };
+
+        ResponseOfferSnapshot {
+            result: result as i32,
         }
+    }
 
+    fn apply_snapshot_chunk(&self, req: RequestApplySnapshotChunk) -> ResponseApplySnapshotChunk {
+        match self.do_apply_snapshot_chunk(req.index, &req.chunk) {
+            Ok(_) => {
+                ResponseApplySnapshotChunk {
+                    result: ApplySnapshotChunkResult::Accept as i32,
+                    ..Default::default()
+                }
+            },
+            // the chunk is corrupted. fetch it again, from a different peer
+            Err(AppError::Db(DbError::SnapshotChunkHashMismatch { index })) => {
+                warn!(index, sender = req.sender.as_str(), "Snapshot chunk hash mismatch");
+                ResponseApplySnapshotChunk {
+                    result:         ApplySnapshotChunkResult::Retry as i32,
+                    refetch_chunks: vec![index],
+                    reject_senders: vec![req.sender],
+                }
+            },
+            // the restored state doesn't match the app hash. the snapshot
+            // itself must be bad, so try another one
+            Err(err @ AppError::Db(DbError::SnapshotRootHashMismatch { .. })) => {
+                warn!(err = err.to_string(), "Rejected snapshot");
+                ResponseApplySnapshotChunk {
+                    result: ApplySnapshotChunkResult::RejectSnapshot as i32,
+                    ..Default::default()
+                }
+            },
+            Err(err) => {
+                warn!(err = err.to_string(), "Failed to apply snapshot chunk");
+                ResponseApplySnapshotChunk {
+                    result: ApplySnapshotChunkResult::Abort as i32,
+                    ..Default::default()
+                }
+            },
+        }
     }
 }
 
@@ -174,17 +350,54 @@ fn from_tm_hash(bytes: Bytes) -> Hash {
     bytes.to_vec().try_into().expect("incorrect block hash length")
 }
 
-fn to_tm_tx_result(tx_result: AppResult<Vec<Event>>) -> ExecTxResult {
-    match tx_result {
+fn from_tm_snapshot(
+    tm_snapshot: Option<TmSnapshot>,
+    app_hash:    Bytes,
+) -> AppResult<(Snapshot, Hash)> {
+    // the snapshot is supplied by a peer, so don't trust it to be present
+    let tm_snapshot = tm_snapshot.ok_or(DbError::InvalidSnapshotMetadata)?;
+    let snapshot = Snapshot::from_metadata(
+        tm_snapshot.height,
+        tm_snapshot.format,
+        &tm_snapshot.metadata,
+    )?;
+
+    // the metadata must be consistent with the snapshot's hash and number of
+    // chunks, otherwise the snapshot is malformed
+    if snapshot.num_chunks() != tm_snapshot.chunks
+        || snapshot.hash().as_ref() != tm_snapshot.hash.as_ref()
+    {
+        return Err(DbError::InvalidSnapshotMetadata.into());
+    }
+
+    Ok((snapshot, app_hash.to_vec().try_into()?))
+}
+
+fn to_tm_snapshot(snapshot: Snapshot) -> TmSnapshot {
+    TmSnapshot {
+        height:   snapshot.version,
+        format:   snapshot.format,
+        chunks:   snapshot.num_chunks(),
+        hash:     snapshot.hash().into_vec().into(),
+        metadata: snapshot.metadata().into(),
+    }
+}
+
+fn to_tm_tx_result(outcome: TxOutcome) -> ExecTxResult {
+    match outcome.result {
         Ok(events) => ExecTxResult {
-            code:   0,
-            events: events.into_iter().map(to_tm_event).collect(),
+            code:       0,
+            gas_wanted: outcome.gas_limit as i64,
+            gas_used:   outcome.gas_used as i64,
+            events:     events.into_iter().map(to_tm_event).collect(),
             ..Default::default()
         },
         Err(err) => ExecTxResult {
-            code:      1,                // TODO: custom error code
-            codespace: "tx".to_string(), // TODO: custom error codespace
-            log:       err.to_string(),
+            code:       err.code(),
+            codespace:  err.codespace().into(),
+            log:        err.to_string(),
+            gas_wanted: outcome.gas_limit as i64,
+            gas_used:   outcome.gas_used as i64,
             ..Default::default()
         },
     }
@@ -1,13 +1,16 @@
 use {
     crate::{
-        authenticate_tx, process_msg, process_query, AppError, AppResult, CHAIN_ID, CONFIG,
-        LAST_FINALIZED_BLOCK,
+        authenticate_tx, call_begin_blockers, call_end_blockers, finalize_fee, index_txs,
+        parse_query_path, process_msg, process_query, prune_txs, query_storage_key, query_tx,
+        search_txs, withhold_fee, AppError, AppResult, Vm, CHAIN_ID, CONFIG,
+        LAST_FINALIZED_BLOCK, MAX_QUERY_GAS, MAX_SIMULATE_GAS, TAXMAN_GAS_LIMIT,
     },
-    cw_db::{BaseStore, CacheStore, SharedStore},
+    cw_db::{BaseStore, CacheStore, SharedStore, Snapshot, StateStorage},
     cw_std::{
-        from_json, hash, to_json, Binary, BlockInfo, Event, GenesisState, Hash, QueryRequest,
-        Storage, Tx, GENESIS_SENDER,
+        from_json, hash, to_json, Binary, BlockInfo, Event, GenericResult, GenesisState,
+        Hash, QueryRequest, QueryResponse, Storage, Tx, Uint64, GENESIS_SENDER,
     },
+    cw_vm::{GasTracker, ModuleCache},
     tracing::{debug, info},
 };
 
@@ -17,12 +20,51 @@ use {
 /// https://github.com/informalsystems/tendermint-rs/blob/v0.34.0/abci/src/application.rs#L22-L25
 #[derive(Clone)]
 pub struct App {
-    store: BaseStore,
+    store:       BaseStore,
+    /// The state used by CheckTx. Changes made during CheckTx (most notably,
+    /// the increment of account sequence numbers) are accumulated here, such
+    /// that multiple txs from the same sender can be accepted into the mempool
+    /// within the same block. It's reset to the latest committed state after
+    /// each `Commit`.
+    check_state: SharedStore<CacheStore<StateStorage>>,
+    /// Compiled Wasm modules, such that contracts don't need to be compiled
+    /// every time they're called.
+    cache:       ModuleCache,
 }
 
 impl App {
-    pub fn new(store: BaseStore) -> Self {
-        Self { store }
+    pub fn new(store: BaseStore, cache: ModuleCache) -> Self {
+        let check_state = SharedStore::new(CacheStore::new(store.state_storage(None), None));
+        Self { store, check_state, cache }
+    }
+
+    /// Discard the changes made during CheckTx, and start over from the latest
+    /// committed state.
+    fn reset_check_state(&self) {
+        *self.check_state.write_access() = CacheStore::new(self.store.state_storage(None), None);
+    }
+}
+
+/// The outcome of executing a transaction.
+pub struct TxOutcome {
+    pub gas_limit: u64,
+    pub gas_used:  u64,
+    pub result:    AppResult<Vec<Event>>,
+}
+
+impl TxOutcome {
+    /// Convert to the `cw_std` type, where the error is reduced to its
+    /// codespace, code, and message, such that it can be serialized, e.g. to be
+    /// saved in the tx index.
+    pub fn to_std(&self) -> cw_std::TxOutcome {
+        cw_std::TxOutcome {
+            gas_limit: Uint64::new(self.gas_limit),
+            gas_used:  Uint64::new(self.gas_used),
+            result:    match &self.result {
+                Ok(events) => GenericResult::Ok(events.clone()),
+                Err(err) => GenericResult::Err(err.into()),
+            },
+        }
     }
 }
 
@@ -55,13 +97,25 @@ impl App {
         // the developer should examine the error, fix it, and retry.
         for (idx, msg) in genesis_state.msgs.into_iter().enumerate() {
             info!(idx, "Processing genesis message");
-            process_msg(cached.clone(), &block, &GENESIS_SENDER, msg)?;
+            // genesis messages aren't subject to gas limit
+            process_msg(
+                cached.clone(),
+                self.cache.clone(),
+                &block,
+                GasTracker::new_limitless(),
+                0,
+                &GENESIS_SENDER,
+                msg,
+            )?;
         }
 
         // persist the state changes to disk
         let (_, pending) = cached.disassemble().disassemble();
         let (version, root_hash) = self.store.flush_and_commit(pending)?;
 
+        // the check state needs to be rebuilt on top of the genesis state
+        self.reset_check_state();
+
         // BaseStore version should be 0
         debug_assert_eq!(version, 0);
         // the root hash should not be None. it's only None when the merkle tree
@@ -85,9 +139,11 @@ impl App {
         &self,
         block:   BlockInfo,
         raw_txs: Vec<impl AsRef<[u8]>>,
-    ) -> AppResult<(Hash, Vec<AppResult<Vec<Event>>>)> {
+    ) -> AppResult<(Hash, Vec<Event>, Vec<TxOutcome>)> {
         let mut cached = SharedStore::new(CacheStore::new(self.store.state_storage(None), None));
+        let mut events = vec![];
         let mut tx_results = vec![];
+        let mut indexed_txs = vec![];
 
         // make sure the new block height is exactly the last finalized height
         // plus one. this ensures that block height always matches the BaseStore
@@ -100,11 +156,23 @@ impl App {
             ));
         }
 
+        // call the begin blockers that are due at this block
+        events.extend(call_begin_blockers(cached.share(), self.cache.clone(), &block)?);
+
         for (idx, raw_tx) in raw_txs.into_iter().enumerate() {
-            debug!(idx, tx_hash = hash(raw_tx.as_ref()).to_string(), "Processing transaction");
-            tx_results.push(run_tx(cached.share(), &block, from_json(raw_tx)?));
+            // this is the same as the tx hash used by Tendermint
+            let tx_hash = hash(raw_tx.as_ref());
+            debug!(idx, tx_hash = tx_hash.to_string(), "Processing transaction");
+            let tx: Tx = from_json(raw_tx)?;
+            let outcome = run_tx(cached.share(), self.cache.clone(), &block, tx.clone(), false);
+            indexed_txs.push((tx_hash, tx, outcome.to_std()));
+            tx_results.push(outcome);
         }
 
+        // call the end blockers that are due at this block. the config is
+        // loaded again, as it may have been updated by the txs.
+        events.extend(call_end_blockers(cached.share(), self.cache.clone(), &block)?);
+
         // save the last committed block
         //
         // note that we do this *after* the transactions have been executed, so
@@ -117,6 +185,16 @@ impl App {
         let (_, batch) = cached.disassemble().disassemble();
         let (version, root_hash) = self.store.flush_but_not_commit(batch)?;
 
+        // write the txs and their outcomes to the tx index, and delete those in
+        // the blocks whose states are being pruned. like the state changes,
+        // these are persisted to disk in the ABCI `Commit` call.
+        let mut index = CacheStore::new(self.store.tx_index(), None);
+        index_txs(&mut index, version, indexed_txs)?;
+        if let Some((after, up_to)) = self.store.pruning_policy().versions_to_prune(version) {
+            prune_txs(&mut index, after, up_to)?;
+        }
+        self.store.flush_tx_index(index.disassemble().1)?;
+
         // block height should match the DB version
         debug_assert_eq!(block.height.u64(), version);
         // the merkle tree should never be empty because at least we always have
@@ -130,18 +208,42 @@ impl App {
             "Finalized block"
         );
 
-        Ok((root_hash.unwrap(), tx_results))
+        Ok((root_hash.unwrap(), events, tx_results))
     }
 
     // TODO: we need to think about what to do if the flush fails here?
     pub fn do_commit(&self) -> AppResult<()> {
         self.store.commit()?;
+        self.reset_check_state();
 
         info!(version = self.store.latest_version(), "Committed state");
 
         Ok(())
     }
 
+    pub fn do_check_tx(&self, raw_tx: impl AsRef<[u8]>) -> AppResult<TxOutcome> {
+        let tx: Tx = from_json(raw_tx)?;
+        let block = LAST_FINALIZED_BLOCK.load(&self.check_state)?;
+
+        // the check state is shared by all CheckTx calls, so we make a cached
+        // store on top of it, and only commit the changes if the tx passes
+        // the checks.
+        let cached = SharedStore::new(CacheStore::new(self.check_state.share(), None));
+        let gas_limit = tx.gas_limit.u64();
+        let gas_tracker = GasTracker::new_limited(gas_limit);
+
+        let result = check_tx(cached.share(), self.cache.clone(), &block, gas_tracker.clone(), &tx);
+        if result.is_ok() {
+            cached.disassemble().consume();
+        }
+
+        Ok(TxOutcome {
+            gas_limit,
+            gas_used: gas_tracker.used(),
+            result,
+        })
+    }
+
     // returns (last_block_height, last_block_app_hash)
     pub fn do_info(&self) -> AppResult<(u64, Hash)> {
         match LAST_FINALIZED_BLOCK.may_load(&self.store.state_storage(None))? {
@@ -157,82 +259,357 @@ impl App {
         }
     }
 
-    pub fn do_query_app(&self, raw_query: &[u8], height: u64, prove: bool) -> AppResult<Binary> {
-        if prove {
-            // we can't do merkle proof for smart queries. only raw store query
-            // can be merkle proved.
-            return Err(AppError::ProofNotSupported);
+    /// Find the version of the state to be queried at the given height.
+    ///
+    /// Height being zero means unspecified (protobuf doesn't have a null type)
+    /// in which case the latest version is used. Heights that have been pruned
+    /// or that haven't been reached yet can't be queried.
+    fn query_version(&self, height: u64) -> AppResult<u64> {
+        let latest = self.store.latest_version().unwrap_or(0);
+
+        if height == 0 {
+            return Ok(latest);
         }
 
-        let version = if height == 0 {
-            // height being zero means unspecified (protobuf doesn't have a null
-            // type) in which case we use the latest version.
-            None
+        if height > latest {
+            return Err(AppError::height_not_reached(height, latest));
+        }
+
+        if self.store.pruning_policy().is_pruned(height, latest) {
+            return Err(AppError::height_pruned(height));
+        }
+
+        Ok(height)
+    }
+
+    // returns (query_response, proof, height), where height is the one at
+    // which the query was actually served
+    pub fn do_query_app(
+        &self,
+        raw_query: &[u8],
+        height:    u64,
+        prove:     bool,
+    ) -> AppResult<(Binary, Option<Binary>, u64)> {
+        let req: QueryRequest = from_json(raw_query)?;
+
+        // txs are looked up in the tx index, which isn't versioned, so the
+        // height is ignored, and the latest one is reported instead. this means
+        // they can still be queried with a height that has been pruned.
+        let version = match &req {
+            QueryRequest::Tx { .. } | QueryRequest::SearchTxs { .. } => self.query_version(0)?,
+            _ => self.query_version(height)?,
+        };
+
+        // only queries that look up a single key in the state storage can be
+        // merkle proved. we can't do merkle proof for e.g. smart queries.
+        let proof = if prove {
+            let key = query_storage_key(&req).ok_or(AppError::ProofNotSupported)?;
+            Some(to_json(&self.store.prove(&key, Some(version))?)?)
         } else {
-            Some(height)
+            None
+        };
+
+        let res = match req {
+            QueryRequest::Tx {
+                hash,
+            } => QueryResponse::Tx(query_tx(&self.store.tx_index(), hash)?),
+            QueryRequest::SearchTxs {
+                event_type,
+                key,
+                value,
+                start_after,
+                limit,
+            } => {
+                let store = self.store.tx_index();
+                let txs = search_txs(&store, &event_type, &key, &value, start_after, limit)?;
+                QueryResponse::SearchTxs(txs)
+            },
+            req => {
+                // use the state storage at the given version to perform the
+                // query. the block info is loaded from the same version, such
+                // that contracts see the block at that height, not the latest.
+                let store = self.store.state_storage(Some(version));
+                let block = LAST_FINALIZED_BLOCK.load(&store)?;
+                debug_assert_eq!(block.height.u64(), version);
+                // the query is subject to the same gas limit as one made by a
+                // contract, such that a query that never returns (e.g. a smart
+                // query to a contract that loops forever) can't stall the node.
+                // simulations have their own, larger limit.
+                let gas_limit = match &req {
+                    QueryRequest::Simulate { .. } => MAX_SIMULATE_GAS,
+                    _ => MAX_QUERY_GAS,
+                };
+                let gas_tracker = GasTracker::new_limited(gas_limit);
+                process_query(store, self.cache.clone(), &block, gas_tracker, req)?
+            },
         };
 
-        // use the state storage at the given version to perform the query
-        let store = self.store.state_storage(version);
+        Ok((to_json(&res)?, proof, version))
+    }
+
+    // same as `do_query_app`, but with the query given as a REST-like path, e.g.
+    // `/account/{address}`. see `parse_query_path` for the supported paths.
+    // the proof is returned along with the storage key that it's for.
+    pub fn do_query_path(
+        &self,
+        path:   &str,
+        height: u64,
+        prove:  bool,
+    ) -> AppResult<(Binary, Option<(Vec<u8>, Binary)>, u64)> {
+        let req = parse_query_path(path)?;
+        let key = query_storage_key(&req);
+        let (res, proof, height) = self.do_query_app(&to_json(&req)?, height, prove)?;
+
+        // `do_query_app` only returns a proof for queries that have a storage
+        // key, so the key is always there if the proof is
+        Ok((res, key.zip(proof), height))
+    }
+
+    pub fn do_simulate(&self, raw_tx: &[u8]) -> AppResult<Binary> {
+        // always simulate against the latest state
+        let store = self.store.state_storage(None);
         let block = LAST_FINALIZED_BLOCK.load(&store)?;
-        let req: QueryRequest = from_json(raw_query)?;
-        let res = process_query(store, &block, req)?;
+        let req = QueryRequest::Simulate {
+            tx: from_json(raw_tx)?,
+        };
+        // the tx's gas limit is capped at `MAX_SIMULATE_GAS`
+        let gas_tracker = GasTracker::new_limited(MAX_SIMULATE_GAS);
+        let res = process_query(store, self.cache.clone(), &block, gas_tracker, req)?;
+
+        Ok(to_json(&res.as_simulate())?)
+    }
+
+    pub fn do_list_snapshots(&self) -> AppResult<Vec<Snapshot>> {
+        Ok(self.store.list_snapshots()?)
+    }
+
+    pub fn do_load_snapshot_chunk(
+        &self,
+        height: u64,
+        format: u32,
+        index:  u32,
+    ) -> AppResult<Option<Vec<u8>>> {
+        Ok(self.store.load_snapshot_chunk(height, format, index)?)
+    }
+
+    pub fn do_offer_snapshot(&self, snapshot: Snapshot, app_hash: Hash) -> AppResult<()> {
+        info!(
+            height     = snapshot.version,
+            num_chunks = snapshot.num_chunks(),
+            app_hash   = app_hash.to_string(),
+            "Restoring snapshot"
+        );
+
+        Ok(self.store.offer_snapshot(snapshot, app_hash)?)
+    }
+
+    // returns whether the snapshot has been completely restored
+    pub fn do_apply_snapshot_chunk(&self, index: u32, chunk: &[u8]) -> AppResult<bool> {
+        let done = self.store.apply_snapshot_chunk(index, chunk)?;
+
+        if done {
+            // the check state needs to be rebuilt on top of the restored state
+            self.reset_check_state();
+
+            info!(version = self.store.latest_version(), "Restored snapshot");
+        }
 
-        Ok(to_json(&res)?)
+        Ok(done)
     }
 
+    // returns (value, proof, height), where height is the one at which the
+    // query was actually served
     pub fn do_query_store(
         &self,
         key:    &[u8],
         height: u64,
         prove:  bool,
-    ) -> AppResult<(Option<Vec<u8>>, Option<Binary>)> {
-        let version = if height == 0 {
-            // height being zero means unspecified (protobuf doesn't have a null
-            // type) in which case we use the latest version.
-            None
-        } else {
-            Some(height)
-        };
+    ) -> AppResult<(Option<Vec<u8>>, Option<Binary>, u64)> {
+        let version = self.query_version(height)?;
 
         let proof = if prove {
-            Some(to_json(&self.store.prove(key, version)?)?)
+            Some(to_json(&self.store.prove(key, Some(version))?)?)
         } else {
             None
         };
 
-        let value = self.store.state_storage(version).read(key);
+        let value = self.store.state_storage(Some(version)).read(key);
 
-        Ok((value, proof))
+        Ok((value, proof, version))
     }
 }
 
-fn run_tx<S>(store: S, block: &BlockInfo, tx: Tx) -> AppResult<Vec<Event>>
+/// Execute a transaction: withhold the fee by calling the taxman's
+/// `withhold_fee` method, authenticate it by calling the sender account's
+/// `before_tx` method, process its messages one by one, then finalize the fee
+/// by calling the taxman's `finalize_fee` method.
+///
+/// If authentication fails, no state change is made, i.e. the sender doesn't
+/// pay the fee.
+///
+/// If `simulate` is true, the account may skip verifying the tx's credential.
+/// In this case the caller is responsible for discarding the state changes.
+pub fn run_tx<S, VM>(
+    store:    S,
+    vm:       VM,
+    block:    &BlockInfo,
+    tx:       Tx,
+    simulate: bool,
+) -> TxOutcome
 where
     S: Storage + Clone + 'static,
+    VM: Vm,
 {
-    let mut events = vec![];
+    // all contract calls and queries made during this tx share the same gas
+    // tracker, so the gas limit applies to the tx as a whole
+    let gas_limit = tx.gas_limit.u64();
+    let gas_tracker = GasTracker::new_limited(gas_limit);
+
+    let result = _run_tx(store, vm, block, gas_tracker.clone(), tx, simulate);
 
+    TxOutcome {
+        gas_limit,
+        gas_used: gas_tracker.used(),
+        result,
+    }
+}
+
+/// Check a transaction for CheckTx: call the taxman's `withhold_fee` and the
+/// sender account's `before_tx` methods. We don't execute the messages here.
+/// This is not a simulation, so the account should verify the tx's credential.
+///
+/// Calling `withhold_fee` ensures that txs whose sender can't afford the fee
+/// are rejected from entering the mempool.
+fn check_tx<S, VM>(
+    store:       S,
+    vm:          VM,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    tx:          &Tx,
+) -> AppResult<Vec<Event>>
+where
+    S: Storage + Clone + 'static,
+    VM: Vm,
+{
+    let taxman_gas_tracker = GasTracker::new_limited(TAXMAN_GAS_LIMIT);
+    let mut events = withhold_fee(store.clone(), vm.clone(), block, taxman_gas_tracker, tx, false)?;
+    events.extend(authenticate_tx(store, vm, block, gas_tracker, tx, false)?);
+
+    Ok(events)
+}
+
+fn _run_tx<S, VM>(
+    store:       S,
+    vm:          VM,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    tx:          Tx,
+    simulate:    bool,
+) -> AppResult<Vec<Event>>
+where
+    S: Storage + Clone + 'static,
+    VM: Vm,
+{
     // create cached store for this tx
     let cached = SharedStore::new(CacheStore::new(store, None));
 
-    // first, authenticate tx by calling the sender account's before_tx method.
-    // if authentication fails, abort, discard uncommitted.
-    events.extend(authenticate_tx(cached.share(), block, &tx)?);
-
-    // update the account state. as long as authentication succeeds, regardless
-    // of whether the message are successful, we update account state. if auth
-    // fails, we don't update account state.
+    // first, call the taxman to withhold the tx fee. if this fails, abort,
+    // discard uncommitted.
+    //
+    // calls to the taxman are initiated by the chain, not the user, so they are
+    // not subject to the tx's gas limit, but to `TAXMAN_GAS_LIMIT` instead.
+    let mut events = withhold_fee(
+        cached.share(),
+        vm.clone(),
+        block,
+        GasTracker::new_limited(TAXMAN_GAS_LIMIT),
+        &tx,
+        simulate,
+    )?;
+
+    // authenticate the tx by calling the sender account's `before_tx` method.
+    // if this fails, abort, discard the fee withheld above as well, such that
+    // only authenticated txs pay fees. otherwise, a block proposer could include
+    // txs with forged credentials or stale sequences to drain the sender's
+    // balance through fees.
+    //
+    // as long as authentication succeeds, its state changes (most notably, the
+    // account's sequence increment) are kept, regardless of whether the
+    // messages succeed.
+    events.extend(authenticate_tx(
+        cached.share(),
+        vm.clone(),
+        block,
+        gas_tracker.clone(),
+        &tx,
+        simulate,
+    )?);
+
+    // process the messages
+    let result = process_msgs(cached.share(), vm.clone(), block, gas_tracker.clone(), &tx);
+
+    // call the taxman to finalize the tx fee, based on the amount of gas used.
+    // this is done regardless of whether the tx succeeded. if this fails, the
+    // taxman is faulty; abort, discard all state changes made by this tx.
+    let outcome = cw_std::TxOutcome {
+        gas_limit: tx.gas_limit,
+        gas_used:  Uint64::new(gas_tracker.used()),
+        result:    match &result {
+            Ok(events) => GenericResult::Ok(events.clone()),
+            Err(err) => GenericResult::Err(err.into()),
+        },
+    };
+    let fee_events = finalize_fee(
+        cached.share(),
+        vm,
+        block,
+        GasTracker::new_limited(TAXMAN_GAS_LIMIT),
+        &tx,
+        &outcome,
+        simulate,
+    )?;
+
+    // commit the state changes made by the taxman and the account, as well as
+    // those by the messages (if any)
     cached.write_access().commit();
 
-    // now that the tx is authenticated, we loop through the messages and
-    // execute them one by one.
+    events.extend(result?);
+    events.extend(fee_events);
+
+    Ok(events)
+}
+
+fn process_msgs<S, VM>(
+    store:       S,
+    vm:          VM,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    tx:          &Tx,
+) -> AppResult<Vec<Event>>
+where
+    S: Storage + Clone + 'static,
+    VM: Vm,
+{
+    let mut events = vec![];
+
+    // create cached store for the messages
+    let cached = SharedStore::new(CacheStore::new(store, None));
+
+    // loop through the messages and execute them one by one.
     // if any one of the msgs fails, the entire tx fails; abort, discard
     // uncommitted changes (the changes from the before_tx call earlier are
     // persisted)
-    for (idx, msg) in tx.msgs.into_iter().enumerate() {
+    for (idx, msg) in tx.msgs.iter().enumerate() {
         debug!(idx, "Processing message");
-        events.extend(process_msg(cached.share(), block, &tx.sender, msg)?);
+        events.extend(process_msg(
+            cached.share(),
+            vm.clone(),
+            block,
+            gas_tracker.clone(),
+            0,
+            &tx.sender,
+            msg.clone(),
+        )?);
     }
 
     // all messages succeeded. commit the state changes
@@ -240,3 +617,194 @@ where
 
     Ok(events)
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        cw_db::{BaseStoreOptions, PruningPolicy, TempDataDir},
+        cw_std::{Addr, Coins, Config, Empty, InfoResponse, Message, SimulateResponse, Timestamp},
+        cw_vm::VmError,
+        std::num::NonZeroUsize,
+        wasmer::wat2wasm,
+    };
+
+    // a contract whose `query` entry point loops forever. `instantiate` returns
+    // an empty response, which is preset in memory: a region at 0, pointing to
+    // the data at 16. `allocate` is a bump allocator, and `deallocate` does
+    // nothing.
+    const LOOPING_CONTRACT: &str = r#"
+        (module
+            (memory (export "memory") 1)
+            (data (i32.const 0) "\10\00\00\00\25\00\00\00\25\00\00\00")
+            (data (i32.const 16) "{\"ok\":{\"submsgs\":[],\"attributes\":[]}}")
+            (global $next (mut i32) (i32.const 1024))
+            (func (export "allocate") (param $capacity i32) (result i32)
+                (local $ptr i32)
+                (local.set $ptr (global.get $next))
+                (i32.store (local.get $ptr) (i32.add (local.get $ptr) (i32.const 12)))
+                (i32.store offset=4 (local.get $ptr) (local.get $capacity))
+                (i32.store offset=8 (local.get $ptr) (i32.const 0))
+                (global.set $next
+                    (i32.add (local.get $ptr) (i32.add (local.get $capacity) (i32.const 12))))
+                (local.get $ptr))
+            (func (export "deallocate") (param i32))
+            (func (export "instantiate") (param i32 i32) (result i32) (i32.const 0))
+            (func (export "query") (param i32 i32) (result i32)
+                (loop $forever (br $forever))
+                (i32.const 0)))
+    "#;
+
+    fn mock_block(height: u64) -> BlockInfo {
+        BlockInfo {
+            height:    Uint64::new(height),
+            timestamp: Timestamp::from_seconds(height * 5),
+            hash:      hash(height.to_be_bytes()),
+        }
+    }
+
+    // an app that keeps the two most recent versions, at the given height
+    fn setup(path: &TempDataDir, height: u64, msgs: Vec<Message>) -> App {
+        let store = BaseStore::open_with_options(path, BaseStoreOptions {
+            pruning: PruningPolicy::KeepRecent(2),
+            ..Default::default()
+        })
+        .unwrap();
+        let cache = ModuleCache::new(NonZeroUsize::new(10).unwrap(), None).unwrap();
+        let app = App::new(store, cache);
+
+        let genesis_state = GenesisState {
+            config: Config {
+                owner:          None,
+                bank:           Addr::mock(1),
+                taxman:         None,
+                begin_blockers: vec![],
+                end_blockers:   vec![],
+                max_msg_depth:  None,
+            },
+            msgs,
+        };
+        app.do_init_chain("dev-1".into(), mock_block(0), &to_json(&genesis_state).unwrap())
+            .unwrap();
+
+        for height in 1..=height {
+            app.do_finalize_block(mock_block(height), Vec::<Vec<u8>>::new()).unwrap();
+            app.do_commit().unwrap();
+        }
+
+        app
+    }
+
+    fn query(app: &App, req: &QueryRequest, height: u64) -> AppResult<(QueryResponse, u64)> {
+        let (res, _, height) = app.do_query_app(&to_json(req)?, height, false)?;
+        Ok((from_json(res)?, height))
+    }
+
+    #[test]
+    fn query_version_works() {
+        let path = TempDataDir::new("_cw_app_query_version_works");
+        let app = setup(&path, 3, vec![]);
+
+        // zero means the latest height
+        assert_eq!(app.query_version(0).unwrap(), 3);
+
+        // heights that are kept can be queried
+        assert_eq!(app.query_version(2).unwrap(), 2);
+        assert_eq!(app.query_version(3).unwrap(), 3);
+
+        // heights that haven't been reached can't be queried
+        assert!(matches!(
+            app.query_version(4),
+            Err(AppError::HeightNotReached { height: 4, latest: 3 })
+        ));
+
+        // neither can heights that have been pruned
+        assert!(matches!(app.query_version(1), Err(AppError::HeightPruned { height: 1 })));
+    }
+
+    #[test]
+    fn querying_at_height_works() {
+        let path = TempDataDir::new("_cw_app_querying_at_height_works");
+        let app = setup(&path, 3, vec![]);
+
+        // the query is served at the given height, and sees the state and the
+        // block at that height
+        let (res, height) = query(&app, &QueryRequest::Info {}, 2).unwrap();
+        let InfoResponse { last_finalized_block, .. } = res.as_info();
+        assert_eq!(height, 2);
+        assert_eq!(last_finalized_block, mock_block(2));
+
+        // or at the latest height, if unspecified
+        let (res, height) = query(&app, &QueryRequest::Info {}, 0).unwrap();
+        assert_eq!(height, 3);
+        assert_eq!(res.as_info().last_finalized_block, mock_block(3));
+
+        // pruned heights are rejected
+        let err = query(&app, &QueryRequest::Info {}, 1).unwrap_err();
+        assert!(matches!(err, AppError::HeightPruned { height: 1 }));
+
+        // the tx index isn't versioned, so tx searches ignore the height, even
+        // if it's pruned, and are served at the latest height
+        let req = QueryRequest::SearchTxs {
+            event_type:  "transfer".into(),
+            key:         "to".into(),
+            value:       "alice".into(),
+            start_after: None,
+            limit:       None,
+        };
+        let (res, height) = query(&app, &req, 1).unwrap();
+        assert_eq!(height, 3);
+        assert!(res.as_search_txs().is_empty());
+
+        // so are tx lookups. the tx doesn't exist, but the height is accepted
+        let err = query(&app, &QueryRequest::Tx { hash: Hash::ZERO }, 1).unwrap_err();
+        assert!(!matches!(err, AppError::HeightPruned { .. }));
+    }
+
+    #[test]
+    fn simulating_caps_gas_limit() {
+        let path = TempDataDir::new("_cw_app_simulating_caps_gas_limit");
+        let app = setup(&path, 1, vec![]);
+
+        // a client can't have the node run a tx with an unlimited amount of gas
+        let tx = Tx {
+            sender:     Addr::mock(2),
+            gas_limit:  Uint64::new(u64::MAX),
+            msgs:       vec![],
+            credential: Binary::empty(),
+        };
+        let res = app.do_simulate(&to_json(&tx).unwrap()).unwrap();
+        let res: SimulateResponse = from_json(res).unwrap();
+        assert_eq!(res.gas_limit.u64(), MAX_SIMULATE_GAS);
+    }
+
+    #[test]
+    fn query_gas_is_limited() {
+        let path = TempDataDir::new("_cw_app_query_gas_is_limited");
+
+        let wasm = wat2wasm(LOOPING_CONTRACT.as_bytes()).unwrap().into_owned();
+        let code_hash = hash(&wasm);
+        let salt = Binary::from(b"looper".to_vec());
+        let contract = Addr::compute(&GENESIS_SENDER, &code_hash, &salt);
+        let app = setup(&path, 1, vec![
+            Message::StoreCode {
+                wasm_byte_code: wasm.into(),
+            },
+            Message::Instantiate {
+                code_hash,
+                msg: to_json(&Empty {}).unwrap(),
+                salt,
+                funds: Coins::new_empty(),
+                admin: None,
+            },
+        ]);
+
+        // the query runs out of gas, instead of keeping the node busy forever
+        let req = QueryRequest::WasmSmart {
+            contract,
+            msg: to_json(&Empty {}).unwrap(),
+        };
+        let err = query(&app, &req, 0).unwrap_err();
+        assert!(matches!(err, AppError::Vm(VmError::OutOfGas { limit: MAX_QUERY_GAS, .. })));
+    }
+}
@@ -1,6 +1,6 @@
 use {
     cw_db::DbError,
-    cw_std::{Addr, Hash, StdError},
+    cw_std::{Addr, AppErrorCode, CodedError, Hash, StdError},
     cw_vm::VmError,
     thiserror::Error,
 };
@@ -16,9 +16,20 @@ pub enum AppError {
     #[error(transparent)]
     Db(#[from] DbError),
 
-    #[error("Merkle proof is not support for `/app` query; use `/store` instead")]
+    #[error("Merkle proof is only supported for `Code`, `Account`, and `WasmRaw` queries")]
     ProofNotSupported,
 
+    #[error("Transactions can only be queried via ABCI Query, not by contracts")]
+    TxQueryNotSupported,
+
+    #[error(
+        "Unknown query path `{path}`; must be `/app`, `/simulate`, `/store`, `/account/...`, \
+         `/balance/...`, `/code/...`, or `/wasm/.../raw/...`"
+    )]
+    UnknownQueryPath {
+        path: String,
+    },
+
     #[error("Incorrect block height! expecting: {expect}, actual: {actual}")]
     IncorrectBlockHeight {
         expect: u64,
@@ -52,6 +63,32 @@ pub enum AppError {
     AccountExists {
         address: Addr,
     },
+
+    #[error("Query depth exceeded! max: {max}")]
+    QueryDepthExceeded {
+        max: usize,
+    },
+
+    #[error("Query gas limit exceeded! limit: {limit}")]
+    QueryGasExceeded {
+        limit: u64,
+    },
+
+    #[error("Maximum message depth exceeded! max: {max}")]
+    MaxDepthExceeded {
+        max: u32,
+    },
+
+    #[error("State at height {height} has been pruned")]
+    HeightPruned {
+        height: u64,
+    },
+
+    #[error("Height {height} hasn't been reached yet! latest height: {latest}")]
+    HeightNotReached {
+        height: u64,
+        latest: u64,
+    },
 }
 
 impl AppError {
@@ -59,6 +96,12 @@ impl AppError {
         Self::IncorrectBlockHeight { expect, actual }
     }
 
+    pub fn unknown_query_path(path: impl Into<String>) -> Self {
+        Self::UnknownQueryPath {
+            path: path.into(),
+        }
+    }
+
     pub fn not_owner(sender: Addr, owner: Addr) -> Self {
         Self::NotOwner { sender, owner }
     }
@@ -74,6 +117,69 @@ impl AppError {
     pub fn account_exists(address: Addr) -> Self {
         Self::AccountExists { address }
     }
+
+    pub fn query_depth_exceeded(max: usize) -> Self {
+        Self::QueryDepthExceeded { max }
+    }
+
+    pub fn query_gas_exceeded(limit: u64) -> Self {
+        Self::QueryGasExceeded { limit }
+    }
+
+    pub fn max_depth_exceeded(max: u32) -> Self {
+        Self::MaxDepthExceeded { max }
+    }
+
+    pub fn height_pruned(height: u64) -> Self {
+        Self::HeightPruned { height }
+    }
+
+    pub fn height_not_reached(height: u64, latest: u64) -> Self {
+        Self::HeightNotReached { height, latest }
+    }
+}
+
+impl AppError {
+    pub const CODESPACE: &'static str = AppErrorCode::CODESPACE;
+}
+
+/// Codes in the `app` codespace. Once assigned, a code must not be changed, so
+/// new variants must be given new codes.
+///
+/// Errors from the VM and from `cw_std` keep their own codespaces and codes,
+/// so an error returned by a contract keeps the contract's.
+impl CodedError for AppError {
+    fn codespace(&self) -> &str {
+        match self {
+            Self::Std(err) => err.codespace(),
+            Self::Vm(err) => err.codespace(),
+            _ => Self::CODESPACE,
+        }
+    }
+
+    fn code(&self) -> u32 {
+        let code = match self {
+            Self::Std(err) => return err.code(),
+            Self::Vm(err) => return err.code(),
+            Self::Db(_) => AppErrorCode::Db,
+            Self::ProofNotSupported => AppErrorCode::ProofNotSupported,
+            Self::TxQueryNotSupported => AppErrorCode::TxQueryNotSupported,
+            Self::IncorrectBlockHeight { .. } => AppErrorCode::IncorrectBlockHeight,
+            Self::OwnerNotSet => AppErrorCode::OwnerNotSet,
+            Self::NotOwner { .. } => AppErrorCode::NotOwner,
+            Self::AdminNotSet => AppErrorCode::AdminNotSet,
+            Self::NotAdmin { .. } => AppErrorCode::NotAdmin,
+            Self::CodeExists { .. } => AppErrorCode::CodeExists,
+            Self::AccountExists { .. } => AppErrorCode::AccountExists,
+            Self::UnknownQueryPath { .. } => AppErrorCode::UnknownQueryPath,
+            Self::QueryDepthExceeded { .. } => AppErrorCode::QueryDepthExceeded,
+            Self::QueryGasExceeded { .. } => AppErrorCode::QueryGasExceeded,
+            Self::MaxDepthExceeded { .. } => AppErrorCode::MaxDepthExceeded,
+            Self::HeightPruned { .. } => AppErrorCode::HeightPruned,
+            Self::HeightNotReached { .. } => AppErrorCode::HeightNotReached,
+        };
+        code as u32
+    }
 }
 
 pub type AppResult<T> = std::result::Result<T, AppError>;
@@ -1,18 +1,20 @@
 use {
     super::{handle_submessages, new_before_tx_event},
-    crate::{AppResult, Querier, ACCOUNTS, CHAIN_ID, CODES, CONTRACT_NAMESPACE},
-    cw_db::PrefixStore,
+    crate::{AppResult, Vm, ACCOUNTS, CHAIN_ID},
     cw_std::{BlockInfo, Context, Event, Storage, Tx},
-    cw_vm::Instance,
+    cw_vm::GasTracker,
     tracing::{debug, warn},
 };
 
-pub fn authenticate_tx<S: Storage + Clone + 'static>(
-    store: S,
-    block: &BlockInfo,
-    tx:    &Tx,
+pub fn authenticate_tx<S: Storage + Clone + 'static, VM: Vm>(
+    store:       S,
+    vm:          VM,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    tx:          &Tx,
+    simulate:    bool,
 ) -> AppResult<Vec<Event>> {
-    match _authenticate_tx(store, block, tx) {
+    match _authenticate_tx(store, vm, block, gas_tracker, tx, simulate) {
         Ok(events) => {
             // TODO: add txhash here?
             debug!(sender = tx.sender.to_string(), "Transaction authenticated");
@@ -25,20 +27,25 @@ pub fn authenticate_tx<S: Storage + Clone + 'static>(
     }
 }
 
-fn _authenticate_tx<S: Storage + Clone + 'static>(
-    store: S,
-    block: &BlockInfo,
-    tx:    &Tx,
+fn _authenticate_tx<S: Storage + Clone + 'static, VM: Vm>(
+    store:       S,
+    vm:          VM,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    tx:          &Tx,
+    simulate:    bool,
 ) -> AppResult<Vec<Event>> {
-    // load wasm code
     let chain_id = CHAIN_ID.load(&store)?;
     let account = ACCOUNTS.load(&store, &tx.sender)?;
-    let wasm_byte_code = CODES.load(&store, &account.code_hash)?;
 
-    // create wasm host
-    let substore = PrefixStore::new(store.clone(), &[CONTRACT_NAMESPACE, &tx.sender]);
-    let querier = Querier::new(store.clone(), block.clone());
-    let mut instance = Instance::build_from_code(substore, querier, &wasm_byte_code)?;
+    // create contract instance
+    let mut instance = vm.build_instance(
+        store.clone(),
+        block,
+        gas_tracker.clone(),
+        &tx.sender,
+        &account.code_hash,
+    )?;
 
     // call `before_tx` entry point
     let ctx = Context {
@@ -49,14 +56,22 @@ fn _authenticate_tx<S: Storage + Clone + 'static>(
         contract:        tx.sender.clone(),
         sender:          None,
         funds:           None,
-        simulate:        Some(false),
+        simulate:        Some(simulate),
         submsg_result:   None,
     };
     let resp = instance.call_before_tx(&ctx, tx)?.into_std_result()?;
 
     // handle submessages
     let mut events = vec![new_before_tx_event(&ctx.contract, resp.attributes)];
-    events.extend(handle_submessages(Box::new(store), block, &ctx.contract, resp.submsgs)?);
+    events.extend(handle_submessages(
+        Box::new(store),
+        vm,
+        block,
+        gas_tracker,
+        0,
+        &ctx.contract,
+        resp.submsgs,
+    )?);
 
     Ok(events)
 }
@@ -0,0 +1,232 @@
+use {
+    super::{handle_submessages, new_after_block_event, new_before_block_event},
+    crate::{AppResult, Instance, Vm, ACCOUNTS, CHAIN_ID, CONFIG},
+    cw_db::{CacheStore, SharedStore},
+    cw_std::{Addr, BlockInfo, Context, Event, Storage},
+    cw_vm::GasTracker,
+    tracing::{info, warn},
+};
+
+/// The maximum amount of gas a cronjob may consume.
+///
+/// Cronjobs are initiated by the chain, not by users, so there isn't a gas limit
+/// specified by a user. Without a bound, a cronjob that never returns (e.g. one
+/// that loops forever) would stall `FinalizeBlock`, halting the chain. With it,
+/// such a cronjob runs out of gas and fails instead.
+pub const CRONJOB_GAS_LIMIT: u64 = 10_000_000;
+
+// --------------------------------- cronjobs ----------------------------------
+
+/// Call the begin blockers, as specified in the chain's config, that are due at
+/// the given block, and return the events they emitted.
+///
+/// This is used both by the app during `FinalizeBlock`, and by the test harness
+/// when advancing blocks, such that they call cronjobs the same way.
+pub fn call_begin_blockers<S: Storage + Clone + 'static, VM: Vm>(
+    store: S,
+    vm:    VM,
+    block: &BlockInfo,
+) -> AppResult<Vec<Event>> {
+    let cfg = CONFIG.load(&store)?;
+    let mut events = vec![];
+
+    for cronjob in cfg.begin_blockers.iter().filter(|job| job.is_due(block.height)) {
+        events.extend(run_cronjob(
+            store.clone(),
+            vm.clone(),
+            block,
+            &cronjob.contract,
+            before_block,
+        ));
+    }
+
+    Ok(events)
+}
+
+/// Call the end blockers, as specified in the chain's config, that are due at
+/// the given block, and return the events they emitted.
+///
+/// This is used both by the app during `FinalizeBlock`, and by the test harness
+/// when advancing blocks, such that they call cronjobs the same way.
+pub fn call_end_blockers<S: Storage + Clone + 'static, VM: Vm>(
+    store: S,
+    vm:    VM,
+    block: &BlockInfo,
+) -> AppResult<Vec<Event>> {
+    let cfg = CONFIG.load(&store)?;
+    let mut events = vec![];
+
+    for cronjob in cfg.end_blockers.iter().filter(|job| job.is_due(block.height)) {
+        events.extend(run_cronjob(
+            store.clone(),
+            vm.clone(),
+            block,
+            &cronjob.contract,
+            after_block,
+        ));
+    }
+
+    Ok(events)
+}
+
+/// Call a cronjob's `before_block` or `after_block` entry point, as specified by
+/// `hook`, and return the events it emitted.
+///
+/// The cronjob is run in its own cached store, with a gas limit of
+/// `CRONJOB_GAS_LIMIT`. If it fails (including by running out of gas), its
+/// state changes are discarded, and no event is returned. The error has already
+/// been logged by the hook; we don't want a faulty cronjob to halt the chain.
+fn run_cronjob<S, VM, F>(
+    store:    S,
+    vm:       VM,
+    block:    &BlockInfo,
+    contract: &Addr,
+    hook:     F,
+) -> Vec<Event>
+where
+    S: Storage + Clone + 'static,
+    VM: Vm,
+    F: Fn(
+        SharedStore<CacheStore<S>>,
+        VM,
+        &BlockInfo,
+        GasTracker,
+        &Addr,
+    ) -> AppResult<Vec<Event>>,
+{
+    let cached = SharedStore::new(CacheStore::new(store, None));
+
+    let gas_tracker = GasTracker::new_limited(CRONJOB_GAS_LIMIT);
+    match hook(cached.share(), vm, block, gas_tracker, contract) {
+        Ok(events) => {
+            cached.write_access().commit();
+            events
+        },
+        Err(_) => vec![],
+    }
+}
+
+// ------------------------------- before block --------------------------------
+
+pub fn before_block<S: Storage + Clone + 'static, VM: Vm>(
+    store:       S,
+    vm:          VM,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    contract:    &Addr,
+) -> AppResult<Vec<Event>> {
+    match _before_block(store, vm, block, gas_tracker, contract) {
+        Ok(events) => {
+            info!(contract = contract.to_string(), "Called before block hook");
+            Ok(events)
+        },
+        Err(err) => {
+            warn!(err = err.to_string(), "Failed to call before block hook");
+            Err(err)
+        },
+    }
+}
+
+fn _before_block<S: Storage + Clone + 'static, VM: Vm>(
+    store:       S,
+    vm:          VM,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    contract:    &Addr,
+) -> AppResult<Vec<Event>> {
+    let (mut instance, ctx) =
+        create_instance(store.clone(), vm.clone(), block, gas_tracker.clone(), contract)?;
+    let resp = instance.call_before_block(&ctx)?.into_std_result()?;
+
+    // handle submessages
+    let mut events = vec![new_before_block_event(&ctx.contract, resp.attributes)];
+    events.extend(handle_submessages(
+        Box::new(store),
+        vm,
+        block,
+        gas_tracker,
+        0,
+        &ctx.contract,
+        resp.submsgs,
+    )?);
+
+    Ok(events)
+}
+
+// -------------------------------- after block --------------------------------
+
+pub fn after_block<S: Storage + Clone + 'static, VM: Vm>(
+    store:       S,
+    vm:          VM,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    contract:    &Addr,
+) -> AppResult<Vec<Event>> {
+    match _after_block(store, vm, block, gas_tracker, contract) {
+        Ok(events) => {
+            info!(contract = contract.to_string(), "Called after block hook");
+            Ok(events)
+        },
+        Err(err) => {
+            warn!(err = err.to_string(), "Failed to call after block hook");
+            Err(err)
+        },
+    }
+}
+
+fn _after_block<S: Storage + Clone + 'static, VM: Vm>(
+    store:       S,
+    vm:          VM,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    contract:    &Addr,
+) -> AppResult<Vec<Event>> {
+    let (mut instance, ctx) =
+        create_instance(store.clone(), vm.clone(), block, gas_tracker.clone(), contract)?;
+    let resp = instance.call_after_block(&ctx)?.into_std_result()?;
+
+    // handle submessages
+    let mut events = vec![new_after_block_event(&ctx.contract, resp.attributes)];
+    events.extend(handle_submessages(
+        Box::new(store),
+        vm,
+        block,
+        gas_tracker,
+        0,
+        &ctx.contract,
+        resp.submsgs,
+    )?);
+
+    Ok(events)
+}
+
+// ---------------------------------- helpers ----------------------------------
+
+fn create_instance<S: Storage + Clone + 'static, VM: Vm>(
+    store:       S,
+    vm:          VM,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    contract:    &Addr,
+) -> AppResult<(Box<dyn Instance>, Context)> {
+    let chain_id = CHAIN_ID.load(&store)?;
+
+    // create contract instance
+    let account = ACCOUNTS.load(&store, contract)?;
+    let instance = vm.build_instance(store, block, gas_tracker, contract, &account.code_hash)?;
+
+    // cronjobs aren't part of a transaction, so there's no sender or funds
+    let ctx = Context {
+        chain_id,
+        block_height:    block.height,
+        block_timestamp: block.timestamp,
+        block_hash:      block.hash.clone(),
+        contract:        contract.clone(),
+        sender:          None,
+        funds:           None,
+        simulate:        None,
+        submsg_result:   None,
+    };
+
+    Ok((instance, ctx))
+}
@@ -19,6 +19,30 @@ pub fn new_before_tx_event(sender: &Addr, attrs: Vec<Attribute>) -> Event {
         .add_attributes(attrs)
 }
 
+pub fn new_before_block_event(contract: &Addr, attrs: Vec<Attribute>) -> Event {
+    Event::new("before_block")
+        .add_attribute(CONTRACT_ADDRESS_KEY, contract)
+        .add_attributes(attrs)
+}
+
+pub fn new_after_block_event(contract: &Addr, attrs: Vec<Attribute>) -> Event {
+    Event::new("after_block")
+        .add_attribute(CONTRACT_ADDRESS_KEY, contract)
+        .add_attributes(attrs)
+}
+
+pub fn new_withhold_fee_event(taxman: &Addr, attrs: Vec<Attribute>) -> Event {
+    Event::new("withhold_fee")
+        .add_attribute(CONTRACT_ADDRESS_KEY, taxman)
+        .add_attributes(attrs)
+}
+
+pub fn new_finalize_fee_event(taxman: &Addr, attrs: Vec<Attribute>) -> Event {
+    Event::new("finalize_fee")
+        .add_attribute(CONTRACT_ADDRESS_KEY, taxman)
+        .add_attributes(attrs)
+}
+
 pub fn new_transfer_event(bank: &Addr, attrs: Vec<Attribute>) -> Event {
     Event::new("transfer")
         .add_attribute(CONTRACT_ADDRESS_KEY, bank)
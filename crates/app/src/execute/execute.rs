@@ -1,21 +1,24 @@
 use {
     super::{handle_submessages, new_execute_event, transfer},
-    crate::{AppResult, Querier, ACCOUNTS, CHAIN_ID, CODES, CONTRACT_NAMESPACE},
-    cw_db::PrefixStore,
+    crate::{AppResult, Vm, ACCOUNTS, CHAIN_ID},
     cw_std::{Addr, Binary, BlockInfo, Coins, Context, Event, Storage},
-    cw_vm::Instance,
+    cw_vm::GasTracker,
     tracing::{info, warn},
 };
 
-pub fn execute<S: Storage + Clone + 'static>(
-    store:    S,
-    block:    &BlockInfo,
-    contract: &Addr,
-    sender:   &Addr,
-    msg:      Binary,
-    funds:    Coins,
+#[allow(clippy::too_many_arguments)]
+pub fn execute<S: Storage + Clone + 'static, VM: Vm>(
+    store:       S,
+    vm:          VM,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    depth:       u32,
+    contract:    &Addr,
+    sender:      &Addr,
+    msg:         Binary,
+    funds:       Coins,
 ) -> AppResult<Vec<Event>> {
-    match _execute(store, block, contract, sender, msg, funds) {
+    match _execute(store, vm, block, gas_tracker, depth, contract, sender, msg, funds) {
         Ok(events) => {
             info!(contract = contract.to_string(), "Executed contract");
             Ok(events)
@@ -27,29 +30,38 @@ pub fn execute<S: Storage + Clone + 'static>(
     }
 }
 
-fn _execute<S: Storage + Clone + 'static>(
-    store:    S,
-    block:    &BlockInfo,
-    contract: &Addr,
-    sender:   &Addr,
-    msg:      Binary,
-    funds:    Coins,
+#[allow(clippy::too_many_arguments)]
+fn _execute<S: Storage + Clone + 'static, VM: Vm>(
+    store:       S,
+    vm:          VM,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    depth:       u32,
+    contract:    &Addr,
+    sender:      &Addr,
+    msg:         Binary,
+    funds:       Coins,
 ) -> AppResult<Vec<Event>> {
     let chain_id = CHAIN_ID.load(&store)?;
 
     // make the coin transfers
     if !funds.is_empty() {
-        transfer(store.clone(), block, sender.clone(), contract.clone(), funds.clone())?;
+        transfer(
+            store.clone(),
+            vm.clone(),
+            block,
+            gas_tracker.clone(),
+            depth,
+            sender.clone(),
+            contract.clone(),
+            funds.clone(),
+        )?;
     }
 
-    // load wasm code
+    // create contract instance
     let account = ACCOUNTS.load(&store, contract)?;
-    let wasm_byte_code = CODES.load(&store, &account.code_hash)?;
-
-    // create wasm host
-    let substore = PrefixStore::new(store.clone(), &[CONTRACT_NAMESPACE, &contract]);
-    let querier = Querier::new(store.clone(), block.clone());
-    let mut instance = Instance::build_from_code(substore, querier, &wasm_byte_code)?;
+    let mut instance =
+        vm.build_instance(store.clone(), block, gas_tracker.clone(), contract, &account.code_hash)?;
 
     // call execute
     let ctx = Context {
@@ -63,11 +75,19 @@ fn _execute<S: Storage + Clone + 'static>(
         simulate:        None,
         submsg_result:   None,
     };
-    let resp = instance.call_execute(&ctx, msg)?.into_std_result()?;
+    let resp = instance.call_execute(&ctx, &msg)?.into_std_result()?;
 
     // handle submessages
     let mut events = vec![new_execute_event(&ctx.contract, resp.attributes)];
-    events.extend(handle_submessages(Box::new(store), block, &ctx.contract, resp.submsgs)?);
+    events.extend(handle_submessages(
+        Box::new(store),
+        vm,
+        block,
+        gas_tracker,
+        depth,
+        &ctx.contract,
+        resp.submsgs,
+    )?);
 
     Ok(events)
 }
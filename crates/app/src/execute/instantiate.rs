@@ -1,24 +1,38 @@
 use {
     super::{handle_submessages, new_instantiate_event, transfer},
-    crate::{AppError, AppResult, Querier, ACCOUNTS, CHAIN_ID, CODES, CONTRACT_NAMESPACE},
-    cw_db::PrefixStore,
+    crate::{AppError, AppResult, Vm, ACCOUNTS, CHAIN_ID},
     cw_std::{Account, Addr, Binary, BlockInfo, Coins, Context, Event, Hash, Storage},
-    cw_vm::Instance,
+    cw_vm::GasTracker,
     tracing::{info, warn},
 };
 
 #[allow(clippy::too_many_arguments)]
-pub fn instantiate<S: Storage + Clone + 'static>(
-    store:     S,
-    block:     &BlockInfo,
-    sender:    &Addr,
-    code_hash: Hash,
-    msg:       Binary,
-    salt:      Binary,
-    funds:     Coins,
-    admin:     Option<Addr>,
+pub fn instantiate<S: Storage + Clone + 'static, VM: Vm>(
+    store:       S,
+    vm:          VM,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    depth:       u32,
+    sender:      &Addr,
+    code_hash:   Hash,
+    msg:         Binary,
+    salt:        Binary,
+    funds:       Coins,
+    admin:       Option<Addr>,
 ) -> AppResult<Vec<Event>> {
-    match _instantiate(store, block, sender, code_hash, msg, salt, funds, admin) {
+    match _instantiate(
+        store,
+        vm,
+        block,
+        gas_tracker,
+        depth,
+        sender,
+        code_hash,
+        msg,
+        salt,
+        funds,
+        admin,
+    ) {
         Ok((events, address)) => {
             info!(address = address.to_string(), "Instantiated contract");
             Ok(events)
@@ -32,21 +46,21 @@ pub fn instantiate<S: Storage + Clone + 'static>(
 
 // return the address of the contract that is instantiated.
 #[allow(clippy::too_many_arguments)]
-fn _instantiate<S: Storage + Clone + 'static>(
-    mut store: S,
-    block:     &BlockInfo,
-    sender:    &Addr,
-    code_hash: Hash,
-    msg:       Binary,
-    salt:      Binary,
-    funds:     Coins,
-    admin:     Option<Addr>,
+fn _instantiate<S: Storage + Clone + 'static, VM: Vm>(
+    mut store:   S,
+    vm:          VM,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    depth:       u32,
+    sender:      &Addr,
+    code_hash:   Hash,
+    msg:         Binary,
+    salt:        Binary,
+    funds:       Coins,
+    admin:       Option<Addr>,
 ) -> AppResult<(Vec<Event>, Addr)> {
     let chain_id = CHAIN_ID.load(&store)?;
 
-    // load wasm code
-    let wasm_byte_code = CODES.load(&store, &code_hash)?;
-
     // compute contract address and save account info
     let address = Addr::compute(sender, &code_hash, &salt);
 
@@ -60,13 +74,21 @@ fn _instantiate<S: Storage + Clone + 'static>(
 
     // make the coin transfers
     if !funds.is_empty() {
-        transfer(store.clone(), block, sender.clone(), address.clone(), funds.clone())?;
+        transfer(
+            store.clone(),
+            vm.clone(),
+            block,
+            gas_tracker.clone(),
+            depth,
+            sender.clone(),
+            address.clone(),
+            funds.clone(),
+        )?;
     }
 
-    // create wasm host
-    let substore = PrefixStore::new(store.clone(), &[CONTRACT_NAMESPACE, &address]);
-    let querier = Querier::new(store.clone(), block.clone());
-    let mut instance = Instance::build_from_code(substore, querier, &wasm_byte_code)?;
+    // create contract instance
+    let mut instance =
+        vm.build_instance(store.clone(), block, gas_tracker.clone(), &address, &account.code_hash)?;
 
     // call instantiate
     let ctx = Context {
@@ -80,11 +102,19 @@ fn _instantiate<S: Storage + Clone + 'static>(
         simulate:        None,
         submsg_result:   None,
     };
-    let resp = instance.call_instantiate(&ctx, msg)?.into_std_result()?;
+    let resp = instance.call_instantiate(&ctx, &msg)?.into_std_result()?;
 
     // handle submessages
     let mut events = vec![new_instantiate_event(&ctx.contract, &account.code_hash, resp.attributes)];
-    events.extend(handle_submessages(Box::new(store), block, &ctx.contract, resp.submsgs)?);
+    events.extend(handle_submessages(
+        Box::new(store),
+        vm,
+        block,
+        gas_tracker,
+        depth,
+        &ctx.contract,
+        resp.submsgs,
+    )?);
 
     Ok((events, ctx.contract))
 }
@@ -1,21 +1,24 @@
 use {
     super::{handle_submessages, new_migrate_event},
-    crate::{AppError, AppResult, Querier, ACCOUNTS, CHAIN_ID, CODES, CONTRACT_NAMESPACE},
-    cw_db::PrefixStore,
+    crate::{AppError, AppResult, Vm, ACCOUNTS, CHAIN_ID},
     cw_std::{Addr, Binary, BlockInfo, Context, Event, Hash, Storage},
-    cw_vm::Instance,
+    cw_vm::GasTracker,
     tracing::{info, warn},
 };
 
-pub fn migrate<S: Storage + Clone + 'static>(
+#[allow(clippy::too_many_arguments)]
+pub fn migrate<S: Storage + Clone + 'static, VM: Vm>(
     store:         S,
+    vm:            VM,
     block:         &BlockInfo,
+    gas_tracker:   GasTracker,
+    depth:         u32,
     contract:      &Addr,
     sender:        &Addr,
     new_code_hash: Hash,
     msg:           Binary,
 ) -> AppResult<Vec<Event>> {
-    match _migrate(store, block, contract, sender, new_code_hash, msg) {
+    match _migrate(store, vm, block, gas_tracker, depth, contract, sender, new_code_hash, msg) {
         Ok(events) => {
             info!(contract = contract.to_string(), "Migrated contract");
             Ok(events)
@@ -27,9 +30,13 @@ pub fn migrate<S: Storage + Clone + 'static>(
     }
 }
 
-fn _migrate<S: Storage + Clone + 'static>(
+#[allow(clippy::too_many_arguments)]
+fn _migrate<S: Storage + Clone + 'static, VM: Vm>(
     mut store:     S,
+    vm:            VM,
     block:         &BlockInfo,
+    gas_tracker:   GasTracker,
+    depth:         u32,
     contract:      &Addr,
     sender:        &Addr,
     new_code_hash: Hash,
@@ -51,13 +58,9 @@ fn _migrate<S: Storage + Clone + 'static>(
     account.code_hash = new_code_hash;
     ACCOUNTS.save(&mut store, contract, &account)?;
 
-    // load wasm code
-    let wasm_byte_code = CODES.load(&store, &account.code_hash)?;
-
-    // create wasm host
-    let substore = PrefixStore::new(store.clone(), &[CONTRACT_NAMESPACE, &contract]);
-    let querier = Querier::new(store.clone(), block.clone());
-    let mut instance = Instance::build_from_code(substore, querier, &wasm_byte_code)?;
+    // create contract instance
+    let mut instance =
+        vm.build_instance(store.clone(), block, gas_tracker.clone(), contract, &account.code_hash)?;
 
     // call the contract's migrate entry point
     let ctx = Context {
@@ -71,7 +74,7 @@ fn _migrate<S: Storage + Clone + 'static>(
         simulate:        None,
         submsg_result:   None,
     };
-    let resp = instance.call_migrate(&ctx, msg)?.into_std_result()?;
+    let resp = instance.call_migrate(&ctx, &msg)?.into_std_result()?;
 
     // handle submessages
     let mut events = vec![new_migrate_event(
@@ -80,7 +83,15 @@ fn _migrate<S: Storage + Clone + 'static>(
         &account.code_hash,
         resp.attributes,
     )];
-    events.extend(handle_submessages(Box::new(store), block, &ctx.contract, resp.submsgs)?);
+    events.extend(handle_submessages(
+        Box::new(store),
+        vm,
+        block,
+        gas_tracker,
+        depth,
+        &ctx.contract,
+        resp.submsgs,
+    )?);
 
     Ok(events)
 }
@@ -1,5 +1,6 @@
 mod auth;
 mod config;
+mod cron;
 mod events;
 #[allow(clippy::module_inception)]
 mod execute;
@@ -7,18 +8,25 @@ mod instantiate;
 mod migrate;
 mod store;
 mod submessage;
+mod taxman;
 mod transfer;
 
-pub use auth::authenticate_tx;
+pub use {
+    auth::authenticate_tx,
+    cron::{after_block, before_block, call_begin_blockers, call_end_blockers, CRONJOB_GAS_LIMIT},
+    taxman::{finalize_fee, withhold_fee, TAXMAN_GAS_LIMIT},
+};
 
 use {
-    crate::AppResult,
+    crate::{AppError, AppResult, Vm, CONFIG},
     config::update_config,
     cw_std::{Addr, BlockInfo, Event, Message, Storage},
+    cw_vm::GasTracker,
     events::{
-        new_before_tx_event, new_execute_event, new_instantiate_event, new_migrate_event,
-        new_receive_event, new_reply_event, new_store_code_event, new_transfer_event,
-        new_update_config_event,
+        new_after_block_event, new_before_block_event, new_before_tx_event, new_execute_event,
+        new_finalize_fee_event, new_instantiate_event, new_migrate_event, new_receive_event,
+        new_reply_event, new_store_code_event, new_transfer_event, new_update_config_event,
+        new_withhold_fee_event,
     },
     execute::execute,
     instantiate::instantiate,
@@ -28,12 +36,37 @@ use {
     transfer::transfer,
 };
 
-pub fn process_msg<S: Storage + Clone + 'static>(
-    mut store: S,
-    block: &BlockInfo,
-    sender: &Addr,
-    msg: Message,
+/// The maximum depth of messages, if not specified in the chain's config.
+pub const DEFAULT_MAX_MSG_DEPTH: u32 = 10;
+
+/// The maximum depth of messages regardless of the chain's config. A larger
+/// value in the config is capped at this, so that a governance mistake can't
+/// let a contract overflow the node's stack.
+pub const MAX_MSG_DEPTH: u32 = 32;
+
+/// Process a message. `depth` is zero for a message in a transaction (or a
+/// genesis message), and one more than that of the parent message for a
+/// submessage. See `handle_submessages`.
+pub fn process_msg<S: Storage + Clone + 'static, VM: Vm>(
+    mut store:   S,
+    vm:          VM,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    depth:       u32,
+    sender:      &Addr,
+    msg:         Message,
 ) -> AppResult<Vec<Event>> {
+    // submessages are processed recursively, so without a limit on the depth,
+    // a contract that emits itself as a submessage would overflow the stack
+    let max_depth = CONFIG
+        .load(&store)?
+        .max_msg_depth
+        .unwrap_or(DEFAULT_MAX_MSG_DEPTH)
+        .min(MAX_MSG_DEPTH);
+    if depth > max_depth {
+        return Err(AppError::max_depth_exceeded(max_depth));
+    }
+
     match msg {
         Message::UpdateConfig {
             new_cfg,
@@ -41,26 +74,38 @@ pub fn process_msg<S: Storage + Clone + 'static>(
         Message::Transfer {
             to,
             coins,
-        } => transfer(store, block, sender.clone(), to, coins),
+        } => transfer(store, vm, block, gas_tracker, depth, sender.clone(), to, coins),
         Message::StoreCode {
             wasm_byte_code,
-        } => store_code(&mut store, sender, &wasm_byte_code),
+        } => store_code(&mut store, &vm, sender, &wasm_byte_code),
         Message::Instantiate {
             code_hash,
             msg,
             salt,
             funds,
             admin,
-        } => instantiate(store, block, sender, code_hash, msg, salt, funds, admin),
+        } => instantiate(
+            store,
+            vm,
+            block,
+            gas_tracker,
+            depth,
+            sender,
+            code_hash,
+            msg,
+            salt,
+            funds,
+            admin,
+        ),
         Message::Execute {
             contract,
             msg,
             funds,
-        } => execute(store, block, &contract, sender, msg, funds),
+        } => execute(store, vm, block, gas_tracker, depth, &contract, sender, msg, funds),
         Message::Migrate {
             contract,
             new_code_hash,
             msg,
-        } => migrate(store, block, &contract, sender, new_code_hash, msg),
+        } => migrate(store, vm, block, gas_tracker, depth, &contract, sender, new_code_hash, msg),
     }
 }
@@ -1,16 +1,17 @@
 use {
     super::new_store_code_event,
-    crate::{AppError, AppResult, CODES},
+    crate::{AppError, AppResult, Vm, CODES},
     cw_std::{hash, Addr, Binary, Event, Hash, Storage},
     tracing::{info, warn},
 };
 
-pub fn store_code(
+pub fn store_code<VM: Vm>(
     store:          &mut dyn Storage,
+    vm:             &VM,
     uploader:       &Addr,
     wasm_byte_code: &Binary,
 ) -> AppResult<Vec<Event>> {
-    match _store_code(store, uploader, wasm_byte_code) {
+    match _store_code(store, vm, uploader, wasm_byte_code) {
         Ok((events, code_hash)) => {
             info!(code_hash = code_hash.to_string(), "Stored code");
             Ok(events)
@@ -23,12 +24,16 @@ pub fn store_code(
 }
 
 // return the hash of the code that is stored, for purpose of tracing/logging
-fn _store_code(
+fn _store_code<VM: Vm>(
     store:          &mut dyn Storage,
+    vm:             &VM,
     uploader:       &Addr,
     wasm_byte_code: &Binary,
 ) -> AppResult<(Vec<Event>, Hash)> {
-    // TODO: static check, ensure wasm code has necessary imports/exports
+    // make sure the code is usable as a contract, such that it doesn't fail
+    // only when someone attempts to instantiate it
+    vm.validate_code(wasm_byte_code)?;
+
     let code_hash = hash(wasm_byte_code);
 
     // make sure that the same code isn't uploaded twice
@@ -1,26 +1,33 @@
 use {
     super::new_reply_event,
-    crate::{process_msg, AppResult, Querier, ACCOUNTS, CHAIN_ID, CODES, CONTRACT_NAMESPACE},
-    cw_db::{CacheStore, PrefixStore, SharedStore},
+    crate::{process_msg, AppResult, Vm, ACCOUNTS, CHAIN_ID},
+    cw_db::{CacheStore, SharedStore},
     cw_std::{Addr, Binary, BlockInfo, Context, Event, GenericResult, ReplyOn, Storage, SubMessage},
-    cw_vm::Instance,
+    cw_vm::GasTracker,
     tracing::{info, warn},
 };
 
 /// Recursively execute submessages emitted in a contract response using a
 /// depth-first approach.
 ///
+/// `depth` is the depth of the message that the contract was responding to.
+/// The submessages are processed at one level deeper, and so are the replies
+/// to them, such that a contract can't recurse indefinitely through replies
+/// either. Contract calls made by the chain itself (e.g. `before_tx` and
+/// cronjobs) are at depth zero, same as the messages in a transaction.
+///
 /// Note: The `sender` in this function signature is the contract, i.e. the
 /// account that emitted the submessages, not the transaction's sender.
-pub fn handle_submessages(
+pub fn handle_submessages<VM: Vm>(
     // This function takes a boxed store instead of using a generic like others.
     //
     // This is because this function is recursive: every layer of recursion, it
     // wraps the store with `SharedStore<CacheStore<S>>`.
     //
     // Although the recursion is guaranteed to be bounded at run time (thanks to
-    // gas limit), the compiler can't understand this. The compiler thinks the
-    // wrapping can possibly go on infinitely. It would throw this error:
+    // the maximum message depth), the compiler can't understand this. The
+    // compiler thinks the wrapping can possibly go on infinitely. It would
+    // throw this error:
     //
     // > error: reached the recursion limit while instantiating
     // > `process_msg::<SharedStore<CacheStore<SharedStore<CacheStore<SharedStore<...>>>>>>`
@@ -39,15 +46,28 @@ pub fn handle_submessages(
     //
     // TODO: these wrapping and boxing for sure has impact on performance.
     // This is probably fine for now, but we should think about optimizing this.
-    store:   Box<dyn Storage>,
-    block:   &BlockInfo,
-    sender:  &Addr,
-    submsgs: Vec<SubMessage>,
+    store:       Box<dyn Storage>,
+    vm:          VM,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    depth:       u32,
+    sender:      &Addr,
+    submsgs:     Vec<SubMessage>,
 ) -> AppResult<Vec<Event>> {
+    let depth = depth + 1;
     let mut events = vec![];
     for submsg in submsgs {
         let cached = SharedStore::new(CacheStore::new(store.clone(), None));
-        match (submsg.reply_on, process_msg(cached.share(), block, sender, submsg.msg)) {
+        let result = process_msg(
+            cached.share(),
+            vm.clone(),
+            block,
+            gas_tracker.clone(),
+            depth,
+            sender,
+            submsg.msg,
+        );
+        match (submsg.reply_on, result) {
             // success - callback requested
             // flush state changes, log events, give callback
             (ReplyOn::Success(payload) | ReplyOn::Always(payload), Result::Ok(submsg_events)) => {
@@ -55,7 +75,10 @@ pub fn handle_submessages(
                 events.extend(submsg_events.clone());
                 events.extend(reply(
                     store.clone(),
+                    vm.clone(),
                     block,
+                    gas_tracker.clone(),
+                    depth,
                     sender,
                     payload,
                     GenericResult::Ok(submsg_events),
@@ -66,10 +89,13 @@ pub fn handle_submessages(
             (ReplyOn::Error(payload) | ReplyOn::Always(payload), Result::Err(err)) => {
                 events.extend(reply(
                     store.clone(),
+                    vm.clone(),
                     block,
+                    gas_tracker.clone(),
+                    depth,
                     sender,
                     payload,
-                    GenericResult::Err(err.to_string()),
+                    GenericResult::Err(err.into()),
                 )?);
             },
             // success - callback not requested
@@ -88,14 +114,18 @@ pub fn handle_submessages(
     Ok(events)
 }
 
-fn reply<S: Storage + Clone + 'static>(
+#[allow(clippy::too_many_arguments)]
+fn reply<S: Storage + Clone + 'static, VM: Vm>(
     store:         S,
+    vm:            VM,
     block:         &BlockInfo,
+    gas_tracker:   GasTracker,
+    depth:         u32,
     contract:      &Addr,
     payload:       Binary,
     submsg_result: GenericResult<Vec<Event>>,
 ) -> AppResult<Vec<Event>> {
-    match _reply(store, block, contract, payload, submsg_result) {
+    match _reply(store, vm, block, gas_tracker, depth, contract, payload, submsg_result) {
         Ok(events) => {
             info!(contract = contract.to_string(), "Performed callback");
             Ok(events)
@@ -107,22 +137,23 @@ fn reply<S: Storage + Clone + 'static>(
     }
 }
 
-fn _reply<S: Storage + Clone + 'static>(
+#[allow(clippy::too_many_arguments)]
+fn _reply<S: Storage + Clone + 'static, VM: Vm>(
     store:         S,
+    vm:            VM,
     block:         &BlockInfo,
+    gas_tracker:   GasTracker,
+    depth:         u32,
     contract:      &Addr,
     payload:       Binary,
     submsg_result: GenericResult<Vec<Event>>,
 ) -> AppResult<Vec<Event>> {
-    // load wasm code
     let chain_id = CHAIN_ID.load(&store)?;
     let account = ACCOUNTS.load(&store, contract)?;
-    let wasm_byte_code = CODES.load(&store, &account.code_hash)?;
 
-    // create wasm host
-    let substore = PrefixStore::new(store.clone(), &[CONTRACT_NAMESPACE, &contract]);
-    let querier = Querier::new(store.clone(), block.clone());
-    let mut instance = Instance::build_from_code(substore, querier, &wasm_byte_code)?;
+    // create contract instance
+    let mut instance =
+        vm.build_instance(store.clone(), block, gas_tracker.clone(), contract, &account.code_hash)?;
 
     // call reply
     let ctx = Context {
@@ -136,11 +167,19 @@ fn _reply<S: Storage + Clone + 'static>(
         simulate:        None,
         submsg_result:   Some(submsg_result),
     };
-    let resp = instance.call_reply(&ctx, payload)?.into_std_result()?;
+    let resp = instance.call_reply(&ctx, &payload)?.into_std_result()?;
 
     // handle submessages
     let mut events = vec![new_reply_event(contract, resp.attributes)];
-    events.extend(handle_submessages(Box::new(store), block, contract, resp.submsgs)?);
+    events.extend(handle_submessages(
+        Box::new(store),
+        vm,
+        block,
+        gas_tracker,
+        depth,
+        contract,
+        resp.submsgs,
+    )?);
 
     Ok(events)
 }
@@ -0,0 +1,163 @@
+use {
+    super::{handle_submessages, new_finalize_fee_event, new_withhold_fee_event},
+    crate::{AppResult, Vm, ACCOUNTS, CHAIN_ID, CONFIG},
+    cw_std::{BlockInfo, Context, Event, Storage, Tx, TxOutcome},
+    cw_vm::GasTracker,
+    tracing::{debug, warn},
+};
+
+/// The maximum amount of gas each call to the taxman may consume.
+///
+/// Calls to the taxman are initiated by the chain, not by the tx's sender, so
+/// they aren't metered against the tx's gas limit. Without a bound, a taxman
+/// that never returns would stall `CheckTx` and `FinalizeBlock` for every tx,
+/// halting the chain. With it, such a call runs out of gas and fails instead.
+pub const TAXMAN_GAS_LIMIT: u64 = 10_000_000;
+
+// ------------------------------- withhold fee --------------------------------
+
+pub fn withhold_fee<S: Storage + Clone + 'static, VM: Vm>(
+    store:       S,
+    vm:          VM,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    tx:          &Tx,
+    simulate:    bool,
+) -> AppResult<Vec<Event>> {
+    match _withhold_fee(store, vm, block, gas_tracker, tx, simulate) {
+        Ok(events) => {
+            debug!(sender = tx.sender.to_string(), "Withheld transaction fee");
+            Ok(events)
+        },
+        Err(err) => {
+            warn!(err = err.to_string(), "Failed to withhold transaction fee");
+            Err(err)
+        },
+    }
+}
+
+fn _withhold_fee<S: Storage + Clone + 'static, VM: Vm>(
+    store:       S,
+    vm:          VM,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    tx:          &Tx,
+    simulate:    bool,
+) -> AppResult<Vec<Event>> {
+    // if the chain doesn't have a taxman, txs are free of charge
+    let Some(taxman) = CONFIG.load(&store)?.taxman else {
+        return Ok(vec![]);
+    };
+
+    let chain_id = CHAIN_ID.load(&store)?;
+    let account = ACCOUNTS.load(&store, &taxman)?;
+
+    // create contract instance
+    let mut instance =
+        vm.build_instance(store.clone(), block, gas_tracker.clone(), &taxman, &account.code_hash)?;
+
+    // call `withhold_fee` entry point
+    let ctx = Context {
+        chain_id,
+        block_height:    block.height,
+        block_timestamp: block.timestamp,
+        block_hash:      block.hash.clone(),
+        contract:        taxman,
+        sender:          None,
+        funds:           None,
+        simulate:        Some(simulate),
+        submsg_result:   None,
+    };
+    let resp = instance.call_withhold_fee(&ctx, tx)?.into_std_result()?;
+
+    // handle submessages
+    let mut events = vec![new_withhold_fee_event(&ctx.contract, resp.attributes)];
+    events.extend(handle_submessages(
+        Box::new(store),
+        vm,
+        block,
+        gas_tracker,
+        0,
+        &ctx.contract,
+        resp.submsgs,
+    )?);
+
+    Ok(events)
+}
+
+// ------------------------------- finalize fee --------------------------------
+
+pub fn finalize_fee<S: Storage + Clone + 'static, VM: Vm>(
+    store:       S,
+    vm:          VM,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    tx:          &Tx,
+    outcome:     &TxOutcome,
+    simulate:    bool,
+) -> AppResult<Vec<Event>> {
+    match _finalize_fee(store, vm, block, gas_tracker, tx, outcome, simulate) {
+        Ok(events) => {
+            debug!(
+                sender   = tx.sender.to_string(),
+                gas_used = outcome.gas_used.u64(),
+                "Finalized transaction fee"
+            );
+            Ok(events)
+        },
+        Err(err) => {
+            warn!(err = err.to_string(), "Failed to finalize transaction fee");
+            Err(err)
+        },
+    }
+}
+
+fn _finalize_fee<S: Storage + Clone + 'static, VM: Vm>(
+    store:       S,
+    vm:          VM,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    tx:          &Tx,
+    outcome:     &TxOutcome,
+    simulate:    bool,
+) -> AppResult<Vec<Event>> {
+    // if the chain doesn't have a taxman, txs are free of charge
+    let Some(taxman) = CONFIG.load(&store)?.taxman else {
+        return Ok(vec![]);
+    };
+
+    let chain_id = CHAIN_ID.load(&store)?;
+    let account = ACCOUNTS.load(&store, &taxman)?;
+
+    // create contract instance
+    let mut instance =
+        vm.build_instance(store.clone(), block, gas_tracker.clone(), &taxman, &account.code_hash)?;
+
+    // call `finalize_fee` entry point
+    let ctx = Context {
+        chain_id,
+        block_height:    block.height,
+        block_timestamp: block.timestamp,
+        block_hash:      block.hash.clone(),
+        contract:        taxman,
+        sender:          None,
+        funds:           None,
+        simulate:        Some(simulate),
+        submsg_result:   None,
+    };
+    let resp = instance.call_finalize_fee(&ctx, tx, outcome)?.into_std_result()?;
+
+    // handle submessages
+    let mut events = vec![new_finalize_fee_event(&ctx.contract, resp.attributes)];
+    events.extend(handle_submessages(
+        Box::new(store),
+        vm,
+        block,
+        gas_tracker,
+        0,
+        &ctx.contract,
+        resp.submsgs,
+    )?);
+
+    Ok(events)
+}
@@ -1,20 +1,23 @@
 use {
     super::{handle_submessages, new_receive_event, new_transfer_event},
-    crate::{AppResult, Querier, ACCOUNTS, CHAIN_ID, CODES, CONFIG, CONTRACT_NAMESPACE},
-    cw_db::PrefixStore,
+    crate::{AppResult, Vm, ACCOUNTS, CHAIN_ID, CONFIG},
     cw_std::{Addr, BlockInfo, Coins, Context, Event, Storage, TransferMsg},
-    cw_vm::Instance,
+    cw_vm::GasTracker,
     tracing::{info, warn},
 };
 
-pub fn transfer<S: Storage + Clone + 'static>(
-    store: S,
-    block: &BlockInfo,
-    from:  Addr,
-    to:    Addr,
-    coins: Coins,
+#[allow(clippy::too_many_arguments)]
+pub fn transfer<S: Storage + Clone + 'static, VM: Vm>(
+    store:       S,
+    vm:          VM,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    depth:       u32,
+    from:        Addr,
+    to:          Addr,
+    coins:       Coins,
 ) -> AppResult<Vec<Event>> {
-    match _transfer(store, block, from, to, coins) {
+    match _transfer(store, vm, block, gas_tracker, depth, from, to, coins) {
         Ok((events, msg)) => {
             info!(
                 from  = msg.from.to_string(),
@@ -33,23 +36,29 @@ pub fn transfer<S: Storage + Clone + 'static>(
 
 // return the TransferMsg, which includes the sender, receiver, and amount, for
 // purpose of tracing/logging
-fn _transfer<S: Storage + Clone + 'static>(
-    store: S,
-    block: &BlockInfo,
-    from:  Addr,
-    to:    Addr,
-    coins: Coins,
+#[allow(clippy::too_many_arguments)]
+fn _transfer<S: Storage + Clone + 'static, VM: Vm>(
+    store:       S,
+    vm:          VM,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    depth:       u32,
+    from:        Addr,
+    to:          Addr,
+    coins:       Coins,
 ) -> AppResult<(Vec<Event>, TransferMsg)> {
-    // load wasm code
     let chain_id = CHAIN_ID.load(&store)?;
     let cfg = CONFIG.load(&store)?;
     let account = ACCOUNTS.load(&store, &cfg.bank)?;
-    let wasm_byte_code = CODES.load(&store, &account.code_hash)?;
 
-    // create wasm host
-    let substore = PrefixStore::new(store.clone(), &[CONTRACT_NAMESPACE, &cfg.bank]);
-    let querier = Querier::new(store.clone(), block.clone());
-    let mut instance = Instance::build_from_code(substore, querier, &wasm_byte_code)?;
+    // create contract instance
+    let mut instance = vm.build_instance(
+        store.clone(),
+        block,
+        gas_tracker.clone(),
+        &cfg.bank,
+        &account.code_hash,
+    )?;
 
     // call transfer
     let ctx = Context {
@@ -72,28 +81,36 @@ fn _transfer<S: Storage + Clone + 'static>(
 
     // handle submessages
     let mut events = vec![new_transfer_event(&ctx.contract, resp.attributes)];
-    events.extend(handle_submessages(Box::new(store.clone()), block, &ctx.contract, resp.submsgs)?);
+    events.extend(handle_submessages(
+        Box::new(store.clone()),
+        vm.clone(),
+        block,
+        gas_tracker.clone(),
+        depth,
+        &ctx.contract,
+        resp.submsgs,
+    )?);
 
     // call the recipient contract's `receive` entry point to inform it of this
     // transfer
-    _receive(store, block, msg, events)
+    _receive(store, vm, block, gas_tracker, depth, msg, events)
 }
 
-fn _receive<S: Storage + Clone + 'static>(
-    store:      S,
-    block:      &BlockInfo,
-    msg:        TransferMsg,
-    mut events: Vec<Event>,
+fn _receive<S: Storage + Clone + 'static, VM: Vm>(
+    store:       S,
+    vm:          VM,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    depth:       u32,
+    msg:         TransferMsg,
+    mut events:  Vec<Event>,
 ) -> AppResult<(Vec<Event>, TransferMsg)> {
-    // load wasm code
     let chain_id = CHAIN_ID.load(&store)?;
     let account = ACCOUNTS.load(&store, &msg.to)?;
-    let wasm_byte_code = CODES.load(&store, &account.code_hash)?;
 
-    // create wasm host
-    let substore = PrefixStore::new(store.clone(), &[CONTRACT_NAMESPACE, &msg.to]);
-    let querier = Querier::new(store.clone(), block.clone());
-    let mut instance = Instance::build_from_code(substore, querier, &wasm_byte_code)?;
+    // create contract instance
+    let mut instance =
+        vm.build_instance(store.clone(), block, gas_tracker.clone(), &msg.to, &account.code_hash)?;
 
     // call the recipient contract's `receive` entry point
     let ctx = Context {
@@ -111,7 +128,15 @@ fn _receive<S: Storage + Clone + 'static>(
 
     // handle submessages
     events.push(new_receive_event(&msg.to, resp.attributes));
-    events.extend(handle_submessages(Box::new(store), block, &ctx.contract, resp.submsgs)?);
+    events.extend(handle_submessages(
+        Box::new(store),
+        vm,
+        block,
+        gas_tracker,
+        depth,
+        &ctx.contract,
+        resp.submsgs,
+    )?);
 
     Ok((events, msg))
 }
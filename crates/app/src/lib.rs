@@ -4,11 +4,25 @@ mod error;
 mod execute;
 mod query;
 mod state;
+mod tx_index;
+mod vm;
 
 pub use crate::{
-    app::App,
+    app::{run_tx, App, TxOutcome},
     error::{AppError, AppResult},
-    execute::{authenticate_tx, process_msg},
-    query::{process_query, Querier},
+    execute::{
+        after_block, authenticate_tx, before_block, call_begin_blockers, call_end_blockers,
+        finalize_fee, process_msg, withhold_fee, CRONJOB_GAS_LIMIT, DEFAULT_MAX_MSG_DEPTH,
+        MAX_MSG_DEPTH, TAXMAN_GAS_LIMIT,
+    },
+    query::{
+        parse_query_path, process_query, query_storage_key, Querier, MAX_QUERY_DEPTH,
+        MAX_QUERY_GAS, MAX_SIMULATE_GAS,
+    },
     state::{ACCOUNTS, CHAIN_ID, CODES, CONFIG, CONTRACT_NAMESPACE, LAST_FINALIZED_BLOCK},
+    tx_index::{
+        index_txs, prune_txs, query_tx, search_txs, MAX_INDEXED_ATTRIBUTE_LEN, TXS, TX_EVENTS,
+        TX_HASHES,
+    },
+    vm::{Instance, Vm},
 };
@@ -1,62 +1,175 @@
 use {
     crate::{
-        AppResult, ACCOUNTS, CHAIN_ID, CODES, CONFIG, CONTRACT_NAMESPACE, LAST_FINALIZED_BLOCK,
+        run_tx, AppError, AppResult, Vm, ACCOUNTS, CHAIN_ID, CODES, CONFIG, CONTRACT_NAMESPACE,
+        LAST_FINALIZED_BLOCK,
     },
-    cw_db::PrefixStore,
+    cw_db::{CacheStore, PrefixStore, SharedStore},
     cw_std::{
         AccountResponse, Addr, BankQuery, BankQueryResponse, Binary, BlockInfo, Bound, Coin, Coins,
-        Context, GenericResult, Hash, InfoResponse, Order, QueryRequest, QueryResponse, StdResult,
-        Storage, WasmRawResponse, WasmSmartResponse,
+        Context, GenericResult, Hash, InfoResponse, Order, QueryRequest, QueryResponse,
+        SimulateResponse, StdError, StdResult, Storage, Tx, Uint64, WasmRawResponse,
+        WasmSmartResponse,
     },
-    cw_vm::{BackendQuerier, BackendStorage, Instance, VmResult},
+    cw_vm::{BackendQuerier, BackendStorage, GasTracker, VmResult},
+    std::cell::Cell,
 };
 
 const DEFAULT_PAGE_LIMIT: u32 = 30;
 
+/// The maximum depth of nested queries made by contracts. E.g. a contract
+/// querying another contract, which in turn queries a third one, is a depth of
+/// two.
+pub const MAX_QUERY_DEPTH: usize = 10;
+
+/// The maximum amount of gas a single query may consume, including the nested
+/// queries it leads to, whether it's made by a contract or by a client (except
+/// for simulations made by a client; see `MAX_SIMULATE_GAS`).
+pub const MAX_QUERY_GAS: u64 = 3_000_000;
+
+/// The maximum amount of gas a transaction simulated by the node (i.e. through
+/// the `/simulate` ABCI query) may consume. A larger gas limit in the tx is
+/// capped at this, so that a simulation can't keep the node busy indefinitely.
+pub const MAX_SIMULATE_GAS: u64 = 100_000_000;
+
+thread_local! {
+    // the depth of the queries currently being processed on this thread.
+    // queries are processed synchronously, with the nested ones on the same
+    // thread as the query (or the contract call) that made them, so this
+    // tracks nesting across all paths a query may take, including simulations.
+    static QUERY_DEPTH: Cell<usize> = Cell::new(0);
+}
+
 // ------------------------------ backend querier ------------------------------
 
-pub struct Querier<S> {
-    store: S,
-    block: BlockInfo,
+pub struct Querier<S, VM> {
+    store:       S,
+    vm:          VM,
+    block:       BlockInfo,
+    // the gas consumed by queries made by a contract is charged to the same
+    // gas tracker as the contract call itself
+    gas_tracker: GasTracker,
 }
 
-impl<S> Querier<S> {
-    pub fn new(store: S, block: BlockInfo) -> Self {
-        Self { store, block }
+impl<S, VM> Querier<S, VM> {
+    pub fn new(store: S, vm: VM, block: BlockInfo, gas_tracker: GasTracker) -> Self {
+        Self {
+            store,
+            vm,
+            block,
+            gas_tracker,
+        }
     }
 }
 
-impl<S: Storage + Clone + 'static> BackendQuerier for Querier<S> {
+impl<S: Storage + Clone + 'static, VM: Vm> Querier<S, VM> {
+    /// Process a query made by a contract, subject to the query depth and gas
+    /// limits.
+    ///
+    /// The query runs with its own gas tracker, whose limit is `MAX_QUERY_GAS`
+    /// or the caller's remaining gas, whichever is smaller. The gas it consumed
+    /// is then charged to the caller.
+    ///
+    /// The inner result is returned to the contract. The outer error means the
+    /// contract itself has run out of gas, and its call must be aborted.
+    fn query(&self, req: QueryRequest) -> VmResult<AppResult<QueryResponse>> {
+        let Some(_guard) = QueryDepthGuard::enter() else {
+            return Ok(Err(AppError::query_depth_exceeded(MAX_QUERY_DEPTH)));
+        };
+
+        let gas_limit = self.gas_tracker.remaining().min(MAX_QUERY_GAS);
+        let query_gas_tracker = GasTracker::new_limited(gas_limit);
+
+        let res = process_query(
+            self.store.clone(),
+            self.vm.clone(),
+            &self.block,
+            query_gas_tracker.clone(),
+            req,
+        );
+
+        self.gas_tracker.consume(query_gas_tracker.used())?;
+
+        // the query failed for having used up its gas budget. if the budget
+        // was the caller's remaining gas instead, the caller is now out of gas
+        // as well, and fails as soon as it resumes.
+        if res.is_err() && gas_limit == MAX_QUERY_GAS && query_gas_tracker.used() >= gas_limit {
+            return Ok(Err(AppError::query_gas_exceeded(MAX_QUERY_GAS)));
+        }
+
+        Ok(res)
+    }
+}
+
+impl<S: Storage + Clone + 'static, VM: Vm> BackendQuerier for Querier<S, VM> {
     fn query_chain(&self, req: QueryRequest) -> VmResult<GenericResult<QueryResponse>> {
-        Ok(process_query(self.store.clone(), &self.block, req).into())
+        self.query(req).map(Into::into)
+    }
+}
+
+// used by contracts that run natively instead of in the Wasm VM. the error is
+// forwarded the same way it would be across the FFI.
+impl<S: Storage + Clone + 'static, VM: Vm> cw_std::Querier for Querier<S, VM> {
+    fn query_chain(&self, req: &QueryRequest) -> StdResult<QueryResponse> {
+        let res = self.query(req.clone()).map_err(AppError::from).and_then(|res| res);
+        GenericResult::from(res).into_std_result()
+    }
+}
+
+/// Increments the query depth when created, and decrements it when dropped,
+/// such that the depth is restored however the query ends.
+struct QueryDepthGuard;
+
+impl QueryDepthGuard {
+    /// Enter a nested query. Return `None` if the maximum depth is exceeded.
+    fn enter() -> Option<Self> {
+        QUERY_DEPTH.with(|depth| {
+            if depth.get() >= MAX_QUERY_DEPTH {
+                return None;
+            }
+
+            depth.set(depth.get() + 1);
+
+            Some(Self)
+        })
+    }
+}
+
+impl Drop for QueryDepthGuard {
+    fn drop(&mut self) {
+        QUERY_DEPTH.with(|depth| depth.set(depth.get() - 1));
     }
 }
 
 // ------------------------------- process query -------------------------------
 
-pub fn process_query<S: Storage + Clone + 'static>(
-    store: S,
-    block: &BlockInfo,
-    req:   QueryRequest,
+pub fn process_query<S: Storage + Clone + 'static, VM: Vm>(
+    store:       S,
+    vm:          VM,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    req:         QueryRequest,
 ) -> AppResult<QueryResponse> {
     match req {
         QueryRequest::Info {} => query_info(&store).map(QueryResponse::Info),
         QueryRequest::Balance {
             address,
             denom,
-        } => query_balance(store, block, address, denom).map(QueryResponse::Balance),
+        } => query_balance(store, vm, block, gas_tracker, address, denom)
+            .map(QueryResponse::Balance),
         QueryRequest::Balances {
             address,
             start_after,
             limit,
-        } => query_balances(store, block, address, start_after, limit).map(QueryResponse::Balances),
+        } => query_balances(store, vm, block, gas_tracker, address, start_after, limit)
+            .map(QueryResponse::Balances),
         QueryRequest::Supply {
             denom,
-        } => query_supply(store, block, denom).map(QueryResponse::Supply),
+        } => query_supply(store, vm, block, gas_tracker, denom).map(QueryResponse::Supply),
         QueryRequest::Supplies {
             start_after,
             limit,
-        } => query_supplies(store, block, start_after, limit).map(QueryResponse::Supplies),
+        } => query_supplies(store, vm, block, gas_tracker, start_after, limit)
+            .map(QueryResponse::Supplies),
         QueryRequest::Code {
             hash,
         } => query_code(&store, hash).map(QueryResponse::Code),
@@ -78,7 +191,79 @@ pub fn process_query<S: Storage + Clone + 'static>(
         QueryRequest::WasmSmart {
             contract,
             msg
-        } => query_wasm_smart(store, block, contract, msg).map(QueryResponse::WasmSmart),
+        } => query_wasm_smart(store, vm, block, gas_tracker, contract, msg)
+            .map(QueryResponse::WasmSmart),
+        QueryRequest::Simulate {
+            tx,
+        } => query_simulate(store, vm, block, gas_tracker, tx).map(QueryResponse::Simulate),
+        // txs are looked up in the node's local tx index, not in the state, so
+        // they aren't available to contracts. see `App::do_query_app`.
+        QueryRequest::Tx { .. } | QueryRequest::SearchTxs { .. } => {
+            Err(AppError::TxQueryNotSupported)
+        },
+    }
+}
+
+/// If the query is simply a lookup of a single key in the state storage, return
+/// that key, such that a Merkle proof can be generated for it.
+///
+/// Other queries can't be proved this way. Most notably, smart queries run Wasm
+/// code, which may read any number of keys.
+pub fn query_storage_key(req: &QueryRequest) -> Option<Vec<u8>> {
+    match req {
+        QueryRequest::Code {
+            hash,
+        } => Some(CODES.storage_key(hash)),
+        QueryRequest::Account {
+            address,
+        } => Some(ACCOUNTS.storage_key(address)),
+        QueryRequest::WasmRaw {
+            contract,
+            key,
+        } => Some([CONTRACT_NAMESPACE, contract.as_ref(), key.as_ref()].concat()),
+        _ => None,
+    }
+}
+
+/// Parse a REST-like ABCI query path into the query that it stands for, such
+/// that the state can be inspected (e.g. using `curl` against the Tendermint
+/// RPC) without building a JSON-encoded `QueryRequest` by hand. Supported paths:
+///
+/// - `/account/{address}`
+/// - `/balance/{address}/{denom}`
+/// - `/code/{hash}`
+/// - `/wasm/{contract}/raw/{key}`, where the key is hex-encoded
+pub fn parse_query_path(path: &str) -> AppResult<QueryRequest> {
+    let unknown = || AppError::unknown_query_path(path);
+
+    let (route, rest) = path
+        .strip_prefix('/')
+        .and_then(|path| path.split_once('/'))
+        .ok_or_else(unknown)?;
+
+    match route {
+        "account" => Ok(QueryRequest::Account {
+            address: rest.parse()?,
+        }),
+        "balance" => {
+            // the denom is the rest of the path, as it may contain slashes
+            let (address, denom) = rest.split_once('/').ok_or_else(unknown)?;
+            Ok(QueryRequest::Balance {
+                address: address.parse()?,
+                denom:   denom.into(),
+            })
+        },
+        "code" => Ok(QueryRequest::Code {
+            hash: rest.parse()?,
+        }),
+        "wasm" => {
+            let (contract, key) = rest.split_once("/raw/").ok_or_else(unknown)?;
+            Ok(QueryRequest::WasmRaw {
+                contract: contract.parse()?,
+                key:      hex::decode(key).map_err(StdError::from)?.into(),
+            })
+        },
+        _ => Err(unknown()),
     }
 }
 
@@ -90,61 +275,67 @@ fn query_info(store: &dyn Storage) -> AppResult<InfoResponse> {
     })
 }
 
-fn query_balance<S: Storage + Clone + 'static>(
-    store:   S,
-    block:   &BlockInfo,
-    address: Addr,
-    denom:   String,
+fn query_balance<S: Storage + Clone + 'static, VM: Vm>(
+    store:       S,
+    vm:          VM,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    address:     Addr,
+    denom:       String,
 ) -> AppResult<Coin> {
-    _query_bank(store, block, &BankQuery::Balance { address, denom })
+    _query_bank(store, vm, block, gas_tracker, &BankQuery::Balance { address, denom })
         .map(|res| res.as_balance())
 }
 
-fn query_balances<S: Storage + Clone + 'static>(
+fn query_balances<S: Storage + Clone + 'static, VM: Vm>(
     store:       S,
+    vm:          VM,
     block:       &BlockInfo,
+    gas_tracker: GasTracker,
     address:     Addr,
     start_after: Option<String>,
     limit:       Option<u32>,
 ) -> AppResult<Coins> {
-    _query_bank(store, block, &BankQuery::Balances { address, start_after, limit })
-        .map(|res| res.as_balances())
+    let msg = BankQuery::Balances { address, start_after, limit };
+    _query_bank(store, vm, block, gas_tracker, &msg).map(|res| res.as_balances())
 }
 
-fn query_supply<S: Storage + Clone + 'static>(
-    store: S,
-    block: &BlockInfo,
-    denom: String,
+fn query_supply<S: Storage + Clone + 'static, VM: Vm>(
+    store:       S,
+    vm:          VM,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    denom:       String,
 ) -> AppResult<Coin> {
-    _query_bank(store, block, &BankQuery::Supply { denom })
+    _query_bank(store, vm, block, gas_tracker, &BankQuery::Supply { denom })
         .map(|res| res.as_supply())
 }
 
-fn query_supplies<S: Storage + Clone + 'static>(
+fn query_supplies<S: Storage + Clone + 'static, VM: Vm>(
     store:       S,
+    vm:          VM,
     block:       &BlockInfo,
+    gas_tracker: GasTracker,
     start_after: Option<String>,
     limit:       Option<u32>,
 ) -> AppResult<Coins> {
-    _query_bank(store, block, &BankQuery::Supplies { start_after, limit })
+    _query_bank(store, vm, block, gas_tracker, &BankQuery::Supplies { start_after, limit })
         .map(|res| res.as_supplies())
 }
 
-fn _query_bank<S: Storage + Clone + 'static>(
-    store: S,
-    block: &BlockInfo,
-    msg:   &BankQuery,
+fn _query_bank<S: Storage + Clone + 'static, VM: Vm>(
+    store:       S,
+    vm:          VM,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    msg:         &BankQuery,
 ) -> AppResult<BankQueryResponse> {
-    // load wasm code
     let chain_id = CHAIN_ID.load(&store)?;
     let cfg = CONFIG.load(&store)?;
     let account = ACCOUNTS.load(&store, &cfg.bank)?;
-    let wasm_byte_code = CODES.load(&store, &account.code_hash)?;
 
-    // create wasm host
-    let substore = PrefixStore::new(store.clone(), &[CONTRACT_NAMESPACE, &cfg.bank]);
-    let querier = Querier::new(store, block.clone());
-    let mut instance = Instance::build_from_code(substore, querier, &wasm_byte_code)?;
+    // create contract instance
+    let mut instance = vm.build_instance(store, block, gas_tracker, &cfg.bank, &account.code_hash)?;
 
     // call query
     let ctx = Context {
@@ -225,21 +416,19 @@ fn query_wasm_raw<S: Storage + 'static>(
     })
 }
 
-fn query_wasm_smart<S: Storage + Clone + 'static>(
-    store:    S,
-    block:    &BlockInfo,
-    contract: Addr,
-    msg:      Binary,
+fn query_wasm_smart<S: Storage + Clone + 'static, VM: Vm>(
+    store:       S,
+    vm:          VM,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    contract:    Addr,
+    msg:         Binary,
 ) -> AppResult<WasmSmartResponse> {
-    // load wasm code
     let chain_id = CHAIN_ID.load(&store)?;
     let account = ACCOUNTS.load(&store, &contract)?;
-    let wasm_byte_code = CODES.load(&store, &account.code_hash)?;
 
-    // create wasm host
-    let substore = PrefixStore::new(store.clone(), &[CONTRACT_NAMESPACE, &contract]);
-    let querier = Querier::new(store, block.clone());
-    let mut instance = Instance::build_from_code(substore, querier, &wasm_byte_code)?;
+    // create contract instance
+    let mut instance = vm.build_instance(store, block, gas_tracker, &contract, &account.code_hash)?;
 
     // call query
     let ctx = Context {
@@ -253,10 +442,132 @@ fn query_wasm_smart<S: Storage + Clone + 'static>(
         simulate:        None,
         submsg_result:   None,
     };
-    let data = instance.call_query(&ctx, msg)?.into_std_result()?;
+    let data = instance.call_query(&ctx, &msg)?.into_std_result()?;
 
     Ok(WasmSmartResponse {
         contract: ctx.contract,
         data,
     })
 }
+
+fn query_simulate<S: Storage + Clone + 'static, VM: Vm>(
+    store:       S,
+    vm:          VM,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    mut tx:      Tx,
+) -> AppResult<SimulateResponse> {
+    // the simulation can't use more gas than what's left for the query, which
+    // is `MAX_QUERY_GAS` if it's made by a contract, or `MAX_SIMULATE_GAS` if
+    // it's made by a client. otherwise, the tx could run with an arbitrarily
+    // high gas limit, bypassing these limits.
+    let gas_limit = tx.gas_limit.u64().min(gas_tracker.remaining());
+    tx.gas_limit = Uint64::new(gas_limit);
+
+    // the store may be read-only (e.g. a StateStorage), so we wrap it in a
+    // cached store, and discard the state changes once the simulation is done
+    let cached = SharedStore::new(CacheStore::new(store, None));
+    let outcome = run_tx(cached, vm, block, tx, true);
+
+    // the simulation runs with its own gas tracker, using the tx's gas limit.
+    // the gas it consumed also needs to be charged to the caller.
+    gas_tracker.consume(outcome.gas_used)?;
+
+    Ok(SimulateResponse {
+        gas_limit: Uint64::new(outcome.gas_limit),
+        gas_used:  Uint64::new(outcome.gas_used),
+        result:    outcome.result.into(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, cw_std::hash};
+
+    #[test]
+    fn parsing_query_paths_works() {
+        let addr = Addr::mock(1);
+        let code_hash = hash("code");
+
+        for (path, expect) in [
+            (
+                format!("/account/{addr}"),
+                QueryRequest::Account {
+                    address: addr.clone(),
+                },
+            ),
+            (
+                format!("/balance/{addr}/uatom"),
+                QueryRequest::Balance {
+                    address: addr.clone(),
+                    denom:   "uatom".into(),
+                },
+            ),
+            // the denom may contain slashes
+            (
+                format!("/balance/{addr}/ibc/transfer"),
+                QueryRequest::Balance {
+                    address: addr.clone(),
+                    denom:   "ibc/transfer".into(),
+                },
+            ),
+            (
+                format!("/code/{code_hash}"),
+                QueryRequest::Code {
+                    hash: code_hash.clone(),
+                },
+            ),
+            (
+                format!("/wasm/{addr}/raw/6f776e6572"),
+                QueryRequest::WasmRaw {
+                    contract: addr.clone(),
+                    key:      b"owner".to_vec().into(),
+                },
+            ),
+            (
+                format!("/wasm/{addr}/raw/"),
+                QueryRequest::WasmRaw {
+                    contract: addr.clone(),
+                    key:      Binary::empty(),
+                },
+            ),
+        ] {
+            assert_eq!(parse_query_path(&path).unwrap(), expect, "path: {path}");
+        }
+    }
+
+    #[test]
+    fn parsing_invalid_query_paths_fails() {
+        let addr = Addr::mock(1);
+        let code_hash = hash("code");
+
+        // paths that don't match any route
+        for path in [
+            "".to_string(),
+            "/".to_string(),
+            "/account".to_string(),
+            format!("account/{addr}"),
+            format!("/accounts/{addr}"),
+            format!("/balance/{addr}"),
+            format!("/wasm/{addr}"),
+            format!("/wasm/{addr}/smart/6f776e6572"),
+        ] {
+            let err = parse_query_path(&path).unwrap_err();
+            assert!(matches!(err, AppError::UnknownQueryPath { .. }), "path: {path}");
+        }
+
+        // paths that match a route, but whose segments can't be parsed,
+        // including those with trailing segments
+        for path in [
+            "/account/alice".to_string(),
+            format!("/account/{addr}/uatom"),
+            "/balance/alice/uatom".to_string(),
+            format!("/code/{code_hash}/wasm"),
+            format!("/wasm/{addr}/raw/zz"),
+            format!("/wasm/{addr}/raw/6f776e657"),
+        ] {
+            let err = parse_query_path(&path).unwrap_err();
+            assert!(matches!(err, AppError::Std(_)), "path: {path}");
+        }
+    }
+}
@@ -1,4 +1,9 @@
-use cw_std::{Account, Addr, Binary, BlockInfo, Config, Hash, Item, Map};
+use cw_std::{BlockInfo, Config, Item};
+
+// the layout of the code and account storage is also needed outside of the app,
+// e.g. by clients to find the keys that Merkle proofs are made against, so it's
+// defined in `cw-std`
+pub use cw_std::{ACCOUNTS, CODES, CONTRACT_NAMESPACE};
 
 /// A string that identifies the chain
 pub const CHAIN_ID: Item<String> = Item::new("chain_id");
@@ -8,13 +13,3 @@ pub const CONFIG: Item<Config> = Item::new("config");
 
 /// The most recently finalized block
 pub const LAST_FINALIZED_BLOCK: Item<BlockInfo> = Item::new("last_finalized_block");
-
-/// Wasm contract byte codes: code_hash => byte_code
-pub const CODES: Map<&Hash, Binary> = Map::new("code");
-
-/// Account metadata: address => account
-pub const ACCOUNTS: Map<&Addr, Account> = Map::new("account");
-
-/// Each contract has its own storage called, which we term the "substore".
-/// A key in a contract's substore is prefixed by this namespace + contract address.
-pub const CONTRACT_NAMESPACE: &[u8] = b"wasm";
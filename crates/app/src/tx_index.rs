@@ -0,0 +1,267 @@
+use {
+    crate::AppResult,
+    cw_std::{
+        Bound, GenericResult, Hash, Map, Order, Set, StdError, StdResult, Storage, Tx, TxOutcome,
+        TxResponse, Uint64,
+    },
+};
+
+const DEFAULT_PAGE_LIMIT: u32 = 30;
+
+/// Event attributes whose type, key, or value is longer than this many bytes
+/// are not indexed, so that they can't bloat the index (or exceed the maximum
+/// length of a storage key).
+pub const MAX_INDEXED_ATTRIBUTE_LEN: usize = 256;
+
+// The tx index is stored in its own column family, which isn't versioned or
+// part of the state commitment, so these namespaces can't clash with those of
+// the state.
+
+/// Transactions and their outcomes by position:
+/// (block_height, tx_index) => tx_response
+pub const TXS: Map<(u64, u32), TxResponse> = Map::new("tx");
+
+/// Positions of transactions by hash: (tx_hash, (block_height, tx_index))
+///
+/// The same tx may be included more than once, e.g. if it failed the first
+/// time without its sequence being incremented, so a hash may have multiple
+/// positions.
+pub const TX_HASHES: Set<(&Hash, (u64, u32))> = Set::new("tx_hash");
+
+/// Transactions by the events they emitted:
+/// ((event_type, attribute_key, attribute_value), (block_height, tx_index))
+pub const TX_EVENTS: Set<((&str, &str, &str), (u64, u32))> = Set::new("tx_event");
+
+/// Write the transactions included in a block, and their outcomes, to the tx
+/// index. The transactions are given in the order they appear in the block.
+pub fn index_txs(
+    store:  &mut dyn Storage,
+    height: u64,
+    txs:    Vec<(Hash, Tx, TxOutcome)>,
+) -> AppResult<()> {
+    for (index, (hash, tx, outcome)) in txs.into_iter().enumerate() {
+        let index = index as u32;
+
+        for (ty, key, value) in indexed_attributes(&outcome) {
+            TX_EVENTS.insert(store, ((ty, key, value), (height, index)))?;
+        }
+
+        TX_HASHES.insert(store, (&hash, (height, index)))?;
+        TXS.save(store, (height, index), &TxResponse {
+            hash,
+            height: Uint64::new(height),
+            index,
+            tx,
+            outcome,
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Delete transactions included in blocks of heights greater than `after` (if
+/// specified) and less than `up_to` from the tx index. This takes the same
+/// range as `PruningPolicy::versions_to_prune` returns, so that the index is
+/// pruned along with the state.
+pub fn prune_txs(store: &mut dyn Storage, after: Option<u64>, up_to: u64) -> AppResult<()> {
+    let min = after.map(|after| Bound::inclusive((after + 1, 0)));
+    let max = Some(Bound::exclusive((up_to, 0)));
+    let txs = TXS.range(store, min, max, Order::Ascending).collect::<StdResult<Vec<_>>>()?;
+
+    for ((height, index), res) in txs {
+        for (ty, key, value) in indexed_attributes(&res.outcome) {
+            TX_EVENTS.remove(store, ((ty, key, value), (height, index)));
+        }
+
+        TX_HASHES.remove(store, (&res.hash, (height, index)));
+        TXS.remove(store, (height, index));
+    }
+
+    Ok(())
+}
+
+/// Find a transaction by hash. If it has been included more than once, return
+/// the most recent inclusion.
+pub fn query_tx(store: &dyn Storage, hash: Hash) -> AppResult<TxResponse> {
+    let Some(position) = TX_HASHES
+        .prefix(&hash)
+        .keys(store, None, None, Order::Descending)
+        .next()
+        .transpose()?
+    else {
+        return Err(StdError::data_not_found::<TxResponse>(hash.as_ref()).into());
+    };
+
+    TXS.load(store, position).map_err(Into::into)
+}
+
+pub fn search_txs(
+    store:       &dyn Storage,
+    event_type:  &str,
+    key:         &str,
+    value:       &str,
+    start_after: Option<Hash>,
+    limit:       Option<u32>,
+) -> AppResult<Vec<TxResponse>> {
+    let start = start_after
+        .map(|hash| -> AppResult<_> {
+            // if the tx has been included more than once, start after its
+            // earliest inclusion that matches the event. this may return some
+            // txs that were already returned, but never skips any.
+            for position in TX_HASHES.prefix(&hash).keys(store, None, None, Order::Ascending) {
+                let position = position?;
+                if TX_EVENTS.has(store, ((event_type, key, value), position)) {
+                    return Ok(Bound::exclusive(position));
+                }
+            }
+            Err(StdError::data_not_found::<TxResponse>(hash.as_ref()).into())
+        })
+        .transpose()?;
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+
+    TX_EVENTS
+        .prefix((event_type, key, value))
+        .keys(store, start, None, Order::Ascending)
+        .take(limit as usize)
+        .map(|item| TXS.load(store, item?).map_err(Into::into))
+        .collect()
+}
+
+/// Return the (event type, attribute key, attribute value) triples to be
+/// indexed for a tx. A failed tx doesn't emit any event, so it can only be
+/// looked up by hash.
+fn indexed_attributes(outcome: &TxOutcome) -> Vec<(&str, &str, &str)> {
+    let GenericResult::Ok(events) = &outcome.result else {
+        return vec![];
+    };
+
+    events
+        .iter()
+        .flat_map(|event| {
+            event.attributes.iter().map(|attr| (&*event.r#type, &*attr.key, &*attr.value))
+        })
+        .filter(|(ty, key, value)| {
+            ty.len() <= MAX_INDEXED_ATTRIBUTE_LEN
+                && key.len() <= MAX_INDEXED_ATTRIBUTE_LEN
+                && value.len() <= MAX_INDEXED_ATTRIBUTE_LEN
+        })
+        .collect()
+}
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        cw_std::{hash, Addr, Binary, Event, GenericError, MockStorage},
+    };
+
+    fn mock_tx(sender: u8) -> (Hash, Tx) {
+        let tx = Tx {
+            sender:     Addr::mock(sender),
+            gas_limit:  Uint64::new(1_000_000),
+            msgs:       vec![],
+            credential: Binary::empty(),
+        };
+        (hash([sender]), tx)
+    }
+
+    fn mock_outcome(recipient: Option<&str>) -> TxOutcome {
+        let result = match recipient {
+            Some(recipient) => {
+                GenericResult::Ok(vec![Event::new("transfer").add_attribute("to", recipient)])
+            },
+            None => GenericResult::Err(GenericError::new("std", 1, "tx failed")),
+        };
+        TxOutcome {
+            gas_limit: Uint64::new(1_000_000),
+            gas_used:  Uint64::new(1_000),
+            result,
+        }
+    }
+
+    fn mock_txs(txs: &[(u8, Option<&str>)]) -> Vec<(Hash, Tx, TxOutcome)> {
+        txs.iter()
+            .map(|(sender, recipient)| {
+                let (hash, tx) = mock_tx(*sender);
+                (hash, tx, mock_outcome(*recipient))
+            })
+            .collect()
+    }
+
+    fn positions(txs: AppResult<Vec<TxResponse>>) -> Vec<(u64, u32)> {
+        txs.unwrap().into_iter().map(|res| (res.height.u64(), res.index)).collect()
+    }
+
+    #[test]
+    fn indexing_and_querying_works() {
+        let mut store = MockStorage::new();
+        index_txs(&mut store, 1, mock_txs(&[(1, Some("alice")), (2, None), (3, Some("bob"))]))
+            .unwrap();
+        index_txs(&mut store, 2, mock_txs(&[(4, Some("alice"))])).unwrap();
+
+        let res = query_tx(&store, hash([3])).unwrap();
+        assert_eq!((res.height.u64(), res.index), (1, 2));
+        assert_eq!(res.tx, mock_tx(3).1);
+
+        // a failed tx can be found by hash, but not by events
+        let res = query_tx(&store, hash([2])).unwrap();
+        assert_eq!((res.height.u64(), res.index), (1, 1));
+        assert!(query_tx(&store, hash([5])).is_err());
+
+        // search with pagination
+        let search = |start_after, limit| {
+            positions(search_txs(&store, "transfer", "to", "alice", start_after, limit))
+        };
+        assert_eq!(search(None, None), [(1, 0), (2, 0)]);
+        assert_eq!(search(None, Some(1)), [(1, 0)]);
+        assert_eq!(search(Some(hash([1])), Some(1)), [(2, 0)]);
+        assert_eq!(search(Some(hash([4])), None), []);
+        assert!(search_txs(&store, "transfer", "to", "alice", Some(hash([5])), None).is_err());
+    }
+
+    #[test]
+    fn indexing_reincluded_tx_works() {
+        let mut store = MockStorage::new();
+        index_txs(&mut store, 1, mock_txs(&[(1, Some("alice"))])).unwrap();
+        index_txs(&mut store, 2, mock_txs(&[(2, Some("bob")), (1, Some("alice"))])).unwrap();
+
+        // both inclusions are kept. looking up by hash returns the latest one
+        let res = query_tx(&store, hash([1])).unwrap();
+        assert_eq!((res.height.u64(), res.index), (2, 1));
+        assert_eq!(positions(search_txs(&store, "transfer", "to", "alice", None, None)), [
+            (1, 0),
+            (2, 1)
+        ]);
+
+        // pruning the first inclusion must not affect the second one
+        prune_txs(&mut store, None, 2).unwrap();
+        let res = query_tx(&store, hash([1])).unwrap();
+        assert_eq!((res.height.u64(), res.index), (2, 1));
+        assert_eq!(positions(search_txs(&store, "transfer", "to", "alice", None, None)), [(2, 1)]);
+        assert!(!TX_HASHES.has(&store, (&hash([1]), (1, 0))));
+        assert!(!TXS.has(&store, (1, 0)));
+    }
+
+    #[test]
+    fn pruning_works() {
+        let mut store = MockStorage::new();
+        for height in 1..=4 {
+            index_txs(&mut store, height, mock_txs(&[(height as u8, Some("alice"))])).unwrap();
+        }
+
+        // delete blocks 2 and 3
+        prune_txs(&mut store, Some(1), 4).unwrap();
+        assert!(query_tx(&store, hash([1])).is_ok());
+        assert!(query_tx(&store, hash([2])).is_err());
+        assert!(query_tx(&store, hash([3])).is_err());
+        assert!(query_tx(&store, hash([4])).is_ok());
+        assert_eq!(positions(search_txs(&store, "transfer", "to", "alice", None, None)), [
+            (1, 0),
+            (4, 0)
+        ]);
+        assert!(!TX_EVENTS.has(&store, (("transfer", "to", "alice"), (2, 0))));
+        assert!(!TX_EVENTS.has(&store, (("transfer", "to", "alice"), (3, 0))));
+    }
+}
@@ -0,0 +1,247 @@
+use {
+    crate::{AppResult, Querier, CODES, CONTRACT_NAMESPACE},
+    cw_db::PrefixStore,
+    cw_std::{
+        Addr, BankQuery, BankQueryResponse, Binary, BlockInfo, Context, GenericResult, Hash,
+        Response, Storage, TransferMsg, Tx, TxOutcome,
+    },
+    cw_vm::{
+        validate_wasm, BackendQuerier, BackendStorage, GasTracker, Instance as WasmInstance,
+        ModuleCache,
+    },
+};
+
+// ------------------------------------ vm -------------------------------------
+
+/// A virtual machine that runs contracts.
+///
+/// The app's execution and query logic is generic over the VM, such that the
+/// same logic can run Wasm contracts (`ModuleCache`), or contracts compiled
+/// natively, which is useful for testing.
+pub trait Vm: Clone + 'static {
+    /// Check whether the code is usable by this VM. Called when the code is
+    /// uploaded, such that unusable code is rejected at that point, instead of
+    /// failing when someone attempts to instantiate it.
+    fn validate_code(&self, code: &[u8]) -> AppResult<()>;
+
+    /// Create an instance of the contract at the given address, running the
+    /// code of the given hash.
+    ///
+    /// The instance is given the contract's substore, and a querier that
+    /// processes queries against the given store. Gas consumed by the instance
+    /// and by its queries is charged to the given gas tracker.
+    fn build_instance<S>(
+        &self,
+        store:       S,
+        block:       &BlockInfo,
+        gas_tracker: GasTracker,
+        contract:    &Addr,
+        code_hash:   &Hash,
+    ) -> AppResult<Box<dyn Instance>>
+    where
+        S: Storage + Clone + 'static;
+}
+
+impl Vm for ModuleCache {
+    fn validate_code(&self, code: &[u8]) -> AppResult<()> {
+        validate_wasm(code).map_err(Into::into)
+    }
+
+    fn build_instance<S>(
+        &self,
+        store:       S,
+        block:       &BlockInfo,
+        gas_tracker: GasTracker,
+        contract:    &Addr,
+        code_hash:   &Hash,
+    ) -> AppResult<Box<dyn Instance>>
+    where
+        S: Storage + Clone + 'static,
+    {
+        // load wasm code
+        let module = self.get_or_build(code_hash, || CODES.load(&store, code_hash))?;
+
+        // create wasm host
+        let substore = PrefixStore::new(store.clone(), &[CONTRACT_NAMESPACE, contract]);
+        let querier = Querier::new(store, self.clone(), block.clone(), gas_tracker.clone());
+        let instance = WasmInstance::build_from_module(substore, querier, &module, gas_tracker)?;
+
+        Ok(Box::new(instance))
+    }
+}
+
+// --------------------------------- instance ----------------------------------
+
+/// An instance of a contract, created by a `Vm`, whose entry points can be
+/// called.
+///
+/// An error here means the call couldn't be carried out (e.g. the contract
+/// doesn't have the entry point), whereas an error returned by the contract is
+/// an `Ok(GenericResult::Err)`.
+pub trait Instance {
+    fn call_instantiate(
+        &mut self,
+        ctx: &Context,
+        msg: &[u8],
+    ) -> AppResult<GenericResult<Response>>;
+
+    fn call_execute(
+        &mut self,
+        ctx: &Context,
+        msg: &[u8],
+    ) -> AppResult<GenericResult<Response>>;
+
+    fn call_query(
+        &mut self,
+        ctx: &Context,
+        msg: &[u8],
+    ) -> AppResult<GenericResult<Binary>>;
+
+    fn call_migrate(
+        &mut self,
+        ctx: &Context,
+        msg: &[u8],
+    ) -> AppResult<GenericResult<Response>>;
+
+    fn call_reply(
+        &mut self,
+        ctx: &Context,
+        msg: &[u8],
+    ) -> AppResult<GenericResult<Response>>;
+
+    fn call_receive(&mut self, ctx: &Context) -> AppResult<GenericResult<Response>>;
+
+    fn call_before_tx(
+        &mut self,
+        ctx: &Context,
+        tx:  &Tx,
+    ) -> AppResult<GenericResult<Response>>;
+
+    fn call_before_block(&mut self, ctx: &Context) -> AppResult<GenericResult<Response>>;
+
+    fn call_after_block(&mut self, ctx: &Context) -> AppResult<GenericResult<Response>>;
+
+    fn call_transfer(
+        &mut self,
+        ctx: &Context,
+        msg: &TransferMsg,
+    ) -> AppResult<GenericResult<Response>>;
+
+    fn call_query_bank(
+        &mut self,
+        ctx: &Context,
+        msg: &BankQuery,
+    ) -> AppResult<GenericResult<BankQueryResponse>>;
+
+    fn call_withhold_fee(
+        &mut self,
+        ctx: &Context,
+        tx:  &Tx,
+    ) -> AppResult<GenericResult<Response>>;
+
+    fn call_finalize_fee(
+        &mut self,
+        ctx:     &Context,
+        tx:      &Tx,
+        outcome: &TxOutcome,
+    ) -> AppResult<GenericResult<Response>>;
+}
+
+impl<S, Q> Instance for WasmInstance<S, Q>
+where
+    S: BackendStorage + 'static,
+    Q: BackendQuerier + 'static,
+{
+    fn call_instantiate(
+        &mut self,
+        ctx: &Context,
+        msg: &[u8],
+    ) -> AppResult<GenericResult<Response>> {
+        WasmInstance::call_instantiate(self, ctx, msg).map_err(Into::into)
+    }
+
+    fn call_execute(
+        &mut self,
+        ctx: &Context,
+        msg: &[u8],
+    ) -> AppResult<GenericResult<Response>> {
+        WasmInstance::call_execute(self, ctx, msg).map_err(Into::into)
+    }
+
+    fn call_query(
+        &mut self,
+        ctx: &Context,
+        msg: &[u8],
+    ) -> AppResult<GenericResult<Binary>> {
+        WasmInstance::call_query(self, ctx, msg).map_err(Into::into)
+    }
+
+    fn call_migrate(
+        &mut self,
+        ctx: &Context,
+        msg: &[u8],
+    ) -> AppResult<GenericResult<Response>> {
+        WasmInstance::call_migrate(self, ctx, msg).map_err(Into::into)
+    }
+
+    fn call_reply(
+        &mut self,
+        ctx: &Context,
+        msg: &[u8],
+    ) -> AppResult<GenericResult<Response>> {
+        WasmInstance::call_reply(self, ctx, msg).map_err(Into::into)
+    }
+
+    fn call_receive(&mut self, ctx: &Context) -> AppResult<GenericResult<Response>> {
+        WasmInstance::call_receive(self, ctx).map_err(Into::into)
+    }
+
+    fn call_before_tx(
+        &mut self,
+        ctx: &Context,
+        tx:  &Tx,
+    ) -> AppResult<GenericResult<Response>> {
+        WasmInstance::call_before_tx(self, ctx, tx).map_err(Into::into)
+    }
+
+    fn call_before_block(&mut self, ctx: &Context) -> AppResult<GenericResult<Response>> {
+        WasmInstance::call_before_block(self, ctx).map_err(Into::into)
+    }
+
+    fn call_after_block(&mut self, ctx: &Context) -> AppResult<GenericResult<Response>> {
+        WasmInstance::call_after_block(self, ctx).map_err(Into::into)
+    }
+
+    fn call_transfer(
+        &mut self,
+        ctx: &Context,
+        msg: &TransferMsg,
+    ) -> AppResult<GenericResult<Response>> {
+        WasmInstance::call_transfer(self, ctx, msg).map_err(Into::into)
+    }
+
+    fn call_query_bank(
+        &mut self,
+        ctx: &Context,
+        msg: &BankQuery,
+    ) -> AppResult<GenericResult<BankQueryResponse>> {
+        WasmInstance::call_query_bank(self, ctx, msg).map_err(Into::into)
+    }
+
+    fn call_withhold_fee(
+        &mut self,
+        ctx: &Context,
+        tx:  &Tx,
+    ) -> AppResult<GenericResult<Response>> {
+        WasmInstance::call_withhold_fee(self, ctx, tx).map_err(Into::into)
+    }
+
+    fn call_finalize_fee(
+        &mut self,
+        ctx:     &Context,
+        tx:      &Tx,
+        outcome: &TxOutcome,
+    ) -> AppResult<GenericResult<Response>> {
+        WasmInstance::call_finalize_fee(self, ctx, tx, outcome).map_err(Into::into)
+    }
+}
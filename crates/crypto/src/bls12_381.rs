@@ -0,0 +1,117 @@
+use {
+    crate::{CryptoError, CryptoResult},
+    blst::{
+        min_pk::{PublicKey, Signature},
+        BLST_ERROR,
+    },
+};
+
+/// The domain separation tag of the proof-of-possession scheme, with public
+/// keys in G1 and signatures in G2, as used by e.g. Ethereum consensus. See:
+/// https://datatracker.ietf.org/doc/html/draft-irtf-cfrg-bls-signature-05#section-4.2.3
+const DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// Verify a BLS12-381 aggregate signature, with 48-byte compressed public keys
+/// in G1 and a 96-byte compressed signature in G2.
+///
+/// If only one message is given, it is considered to be signed by all the
+/// public keys (the "fast aggregate verify" algorithm). Otherwise, the i-th
+/// message is considered to be signed by the i-th public key, so the numbers
+/// of messages and public keys must match.
+///
+/// NOTE: In the case of one message, the caller must make sure that each of
+/// the public keys comes with a proof of possession of the private key.
+/// Otherwise, the signature is vulnerable to rogue key attacks.
+pub fn bls12_381_aggregate_verify(msgs: &[&[u8]], sig: &[u8], pks: &[&[u8]]) -> CryptoResult<()> {
+    if msgs.is_empty() || pks.is_empty() {
+        return Err(CryptoError::EmptyBatch);
+    }
+
+    if msgs.len() != 1 && msgs.len() != pks.len() {
+        return Err(CryptoError::mismatched_batch_lengths(msgs.len(), 1, pks.len()));
+    }
+
+    let sig = Signature::sig_validate(sig, true)?;
+    let pks = pks.iter().map(|pk| PublicKey::key_validate(pk)).collect::<Result<Vec<_>, _>>()?;
+    let pk_refs = pks.iter().collect::<Vec<_>>();
+
+    // the signature and public keys have been validated above, so we don't
+    // need to group-check them again
+    let result = if msgs.len() == 1 {
+        sig.fast_aggregate_verify(false, msgs[0], DST, &pk_refs)
+    } else {
+        sig.aggregate_verify(false, msgs, DST, &pk_refs, false)
+    };
+
+    match result {
+        BLST_ERROR::BLST_SUCCESS => Ok(()),
+        err => Err(err.into()),
+    }
+}
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        blst::min_pk::{AggregateSignature, SecretKey},
+    };
+
+    fn mock_secret_key(seed: u8) -> SecretKey {
+        SecretKey::key_gen(&[seed; 32], &[]).unwrap()
+    }
+
+    fn aggregate(sigs: &[Signature]) -> Vec<u8> {
+        let sig_refs = sigs.iter().collect::<Vec<_>>();
+        AggregateSignature::aggregate(&sig_refs, true).unwrap().to_signature().to_bytes().to_vec()
+    }
+
+    #[test]
+    fn verifying_bls12_381_same_message() {
+        let sks = [mock_secret_key(1), mock_secret_key(2)];
+        let pks = sks.iter().map(|sk| sk.sk_to_pk().to_bytes()).collect::<Vec<_>>();
+        let pk_refs = pks.iter().map(|pk| pk.as_slice()).collect::<Vec<_>>();
+        let msg: &[u8] = b"Jake";
+        let sig = aggregate(&[sks[0].sign(msg, DST, &[]), sks[1].sign(msg, DST, &[])]);
+
+        // valid signature
+        assert!(bls12_381_aggregate_verify(&[msg], &sig, &pk_refs).is_ok());
+
+        // incorrect message
+        assert!(bls12_381_aggregate_verify(&[b"Larry"], &sig, &pk_refs).is_err());
+
+        // missing one of the signers
+        assert!(bls12_381_aggregate_verify(&[msg], &sig, &pk_refs[..1]).is_err());
+
+        // no signer at all
+        assert!(matches!(
+            bls12_381_aggregate_verify(&[msg], &sig, &[]),
+            Err(CryptoError::EmptyBatch)
+        ));
+    }
+
+    #[test]
+    fn verifying_bls12_381_distinct_messages() {
+        let sks = [mock_secret_key(1), mock_secret_key(2)];
+        let pks = sks.iter().map(|sk| sk.sk_to_pk().to_bytes()).collect::<Vec<_>>();
+        let pk_refs = pks.iter().map(|pk| pk.as_slice()).collect::<Vec<_>>();
+        let msgs: [&[u8]; 2] = [b"Jake", b"Larry"];
+        let sig = aggregate(&[sks[0].sign(msgs[0], DST, &[]), sks[1].sign(msgs[1], DST, &[])]);
+
+        // valid signature
+        assert!(bls12_381_aggregate_verify(&msgs, &sig, &pk_refs).is_ok());
+
+        // messages in the wrong order
+        assert!(bls12_381_aggregate_verify(&[msgs[1], msgs[0]], &sig, &pk_refs).is_err());
+
+        // mismatched lengths
+        assert!(matches!(
+            bls12_381_aggregate_verify(&msgs, &sig, &pk_refs[..1]),
+            Err(CryptoError::MismatchedBatchLengths { .. })
+        ));
+
+        // malformed signature
+        assert!(bls12_381_aggregate_verify(&msgs, &sig[..95], &pk_refs).is_err());
+    }
+}
@@ -0,0 +1,135 @@
+use {
+    crate::{CryptoError, CryptoResult},
+    ed25519_dalek::{Signature, VerifyingKey},
+};
+
+const ED25519_SIGNATURE_LEN: usize = 64;
+const ED25519_PUBKEY_LEN:    usize = 32;
+
+/// NOTE: Unlike the secp256k1 and secp256r1 functions, this function takes the
+/// message itself (which may be of any length), as Ed25519 hashes the message
+/// internally as part of signing.
+///
+/// A signature must be accepted by this function if and only if it's accepted
+/// by `ed25519_batch_verify`, otherwise whether a tx is valid would depend on
+/// how it was verified. Therefore this is done as a batch of one, i.e. with the
+/// cofactored verification equation, and weak public keys are rejected in both.
+pub fn ed25519_verify(msg: &[u8], sig: &[u8], pk: &[u8]) -> CryptoResult<()> {
+    let sig = to_signature(sig)?;
+    let vk = to_verifying_key(pk)?;
+    ed25519_dalek::verify_batch(&[msg], &[sig], &[vk]).map_err(Into::into)
+}
+
+/// Verify multiple Ed25519 signatures at once, which is faster than verifying
+/// them one by one. The i-th signature is verified against the i-th message
+/// and the i-th public key, so the three slices must be of the same length.
+///
+/// Succeeds if and only if all signatures are valid.
+pub fn ed25519_batch_verify(msgs: &[&[u8]], sigs: &[&[u8]], pks: &[&[u8]]) -> CryptoResult<()> {
+    if msgs.len() != sigs.len() || msgs.len() != pks.len() {
+        return Err(CryptoError::mismatched_batch_lengths(msgs.len(), sigs.len(), pks.len()));
+    }
+
+    let sigs = sigs.iter().map(|sig| to_signature(sig)).collect::<CryptoResult<Vec<_>>>()?;
+    let vks = pks.iter().map(|pk| to_verifying_key(pk)).collect::<CryptoResult<Vec<_>>>()?;
+
+    ed25519_dalek::verify_batch(msgs, &sigs, &vks).map_err(Into::into)
+}
+
+fn to_signature(sig: &[u8]) -> CryptoResult<Signature> {
+    let bytes: &[u8; ED25519_SIGNATURE_LEN] = sig
+        .try_into()
+        .map_err(|_| CryptoError::incorrect_length(ED25519_SIGNATURE_LEN, sig.len()))?;
+    Ok(Signature::from_bytes(bytes))
+}
+
+fn to_verifying_key(pk: &[u8]) -> CryptoResult<VerifyingKey> {
+    let bytes: &[u8; ED25519_PUBKEY_LEN] = pk
+        .try_into()
+        .map_err(|_| CryptoError::incorrect_length(ED25519_PUBKEY_LEN, pk.len()))?;
+    let vk = VerifyingKey::from_bytes(bytes)?;
+    // a public key of small order can "sign" a message without knowing any
+    // private key
+    if vk.is_weak() {
+        return Err(CryptoError::WeakPubkey);
+    }
+    Ok(vk)
+}
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        ed25519_dalek::{Signer, SigningKey},
+    };
+
+    #[test]
+    fn verifying_ed25519() {
+        let sk = SigningKey::from_bytes(&[1; 32]);
+        let vk = sk.verifying_key();
+        let msg = b"Jake";
+        let sig = sk.sign(msg);
+
+        // valid signature
+        assert!(ed25519_verify(msg, &sig.to_bytes(), vk.as_bytes()).is_ok());
+
+        // incorrect private key
+        let false_sig = SigningKey::from_bytes(&[2; 32]).sign(msg);
+        assert!(ed25519_verify(msg, &false_sig.to_bytes(), vk.as_bytes()).is_err());
+
+        // incorrect public key
+        let false_vk = SigningKey::from_bytes(&[2; 32]).verifying_key();
+        assert!(ed25519_verify(msg, &sig.to_bytes(), false_vk.as_bytes()).is_err());
+
+        // incorrect message
+        assert!(ed25519_verify(b"Larry", &sig.to_bytes(), vk.as_bytes()).is_err());
+
+        // signature of incorrect length
+        assert!(ed25519_verify(msg, &sig.to_bytes()[..63], vk.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn batch_verifying_ed25519() {
+        let sks = [SigningKey::from_bytes(&[1; 32]), SigningKey::from_bytes(&[2; 32])];
+        let msgs: [&[u8]; 2] = [b"Jake", b"Larry"];
+        let sigs = [sks[0].sign(msgs[0]).to_bytes(), sks[1].sign(msgs[1]).to_bytes()];
+        let pks = [sks[0].verifying_key().to_bytes(), sks[1].verifying_key().to_bytes()];
+
+        let sig_refs: Vec<&[u8]> = sigs.iter().map(|sig| sig.as_slice()).collect();
+        let pk_refs: Vec<&[u8]> = pks.iter().map(|pk| pk.as_slice()).collect();
+
+        // all signatures valid
+        assert!(ed25519_batch_verify(&msgs, &sig_refs, &pk_refs).is_ok());
+
+        // one of the messages is incorrect
+        let false_msgs: [&[u8]; 2] = [b"Jake", b"Pumpkin"];
+        assert!(ed25519_batch_verify(&false_msgs, &sig_refs, &pk_refs).is_err());
+
+        // mismatched lengths
+        assert!(matches!(
+            ed25519_batch_verify(&msgs, &sig_refs[..1], &pk_refs),
+            Err(CryptoError::MismatchedBatchLengths { .. })
+        ));
+    }
+
+    // with the identity point as the public key, R = [s]B satisfies the
+    // verification equation for any message. ed25519-dalek's `verify_batch`
+    // accepts such a signature, so both functions must reject it explicitly.
+    #[test]
+    fn verifying_with_weak_pubkey_fails() {
+        let mut pk = [0; 32];
+        pk[0] = 1;
+        let mut sig = [0x66; 64];
+        sig[0] = 0x58;
+        sig[32..].copy_from_slice(&pk);
+        let msg: &[u8] = b"Jake";
+
+        assert!(matches!(ed25519_verify(msg, &sig, &pk), Err(CryptoError::WeakPubkey)));
+        assert!(matches!(
+            ed25519_batch_verify(&[msg], &[&sig], &[&pk]),
+            Err(CryptoError::WeakPubkey)
+        ));
+    }
+}
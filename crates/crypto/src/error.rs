@@ -5,17 +5,53 @@ pub enum CryptoError {
     #[error(transparent)]
     Signature(#[from] signature::Error),
 
+    #[error("BLS error: {0:?}")]
+    Bls(blst::BLST_ERROR),
+
     #[error("data is of incorrect length: expecting {expect}, found {actual}")]
     IncorrectLength {
         expect: usize,
         actual: usize,
     },
+
+    #[error("invalid recovery id: {recovery_id}")]
+    InvalidRecoveryId {
+        recovery_id: u8,
+    },
+
+    #[error("batch items are of mismatched lengths: msgs: {msgs}, sigs: {sigs}, pks: {pks}")]
+    MismatchedBatchLengths {
+        msgs: usize,
+        sigs: usize,
+        pks:  usize,
+    },
+
+    #[error("batch is empty")]
+    EmptyBatch,
+
+    #[error("public key is of small order")]
+    WeakPubkey,
+}
+
+// BLST_ERROR doesn't implement std::error::Error, so we can't use #[from]
+impl From<blst::BLST_ERROR> for CryptoError {
+    fn from(err: blst::BLST_ERROR) -> Self {
+        Self::Bls(err)
+    }
 }
 
 impl CryptoError {
     pub fn incorrect_length(expect: usize, actual: usize) -> Self {
         Self::IncorrectLength { expect, actual }
     }
+
+    pub fn invalid_recovery_id(recovery_id: u8) -> Self {
+        Self::InvalidRecoveryId { recovery_id }
+    }
+
+    pub fn mismatched_batch_lengths(msgs: usize, sigs: usize, pks: usize) -> Self {
+        Self::MismatchedBatchLengths { msgs, sigs, pks }
+    }
 }
 
 pub type CryptoResult<T> = std::result::Result<T, CryptoError>;
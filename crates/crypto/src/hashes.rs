@@ -0,0 +1,53 @@
+use {
+    digest::Digest,
+    sha2::Sha256,
+    sha3::{Keccak256, Sha3_256},
+};
+
+pub fn sha2_256(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(data).into()
+}
+
+pub fn sha3_256(data: &[u8]) -> [u8; 32] {
+    Sha3_256::digest(data).into()
+}
+
+/// NOTE: This is the original Keccak-256 as used by Ethereum, which differs
+/// from the standardized SHA3-256 in its padding.
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
+
+pub fn blake3(data: &[u8]) -> [u8; 32] {
+    blake3::hash(data).into()
+}
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {super::*, hex_literal::hex};
+
+    // test vectors of the empty input, which are widely published, e.g.:
+    // https://en.wikipedia.org/wiki/SHA-2#Test_vectors
+    // https://en.wikipedia.org/wiki/SHA-3#Examples_of_SHA-3_variants
+    #[test]
+    fn hashing_empty_input() {
+        assert_eq!(
+            sha2_256(b""),
+            hex!("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"),
+        );
+        assert_eq!(
+            sha3_256(b""),
+            hex!("a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a"),
+        );
+        assert_eq!(
+            keccak256(b""),
+            hex!("c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"),
+        );
+        assert_eq!(
+            blake3(b""),
+            hex!("af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"),
+        );
+    }
+}
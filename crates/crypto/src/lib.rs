@@ -1,11 +1,17 @@
+mod bls12_381;
+mod ed25519;
 mod error;
+mod hashes;
 mod identity_digest;
 mod secp256k1;
 mod secp256r1;
 
 pub use crate::{
+    bls12_381::bls12_381_aggregate_verify,
+    ed25519::{ed25519_batch_verify, ed25519_verify},
     error::{CryptoError, CryptoResult},
+    hashes::{blake3, keccak256, sha2_256, sha3_256},
     identity_digest::Identity256,
-    secp256k1::secp256k1_verify,
+    secp256k1::{secp256k1_pubkey_recover, secp256k1_verify},
     secp256r1::secp256r1_verify,
 };
@@ -1,6 +1,6 @@
 use {
     crate::{CryptoError, CryptoResult, Identity256},
-    k256::ecdsa::{signature::DigestVerifier, Signature, VerifyingKey},
+    k256::ecdsa::{signature::DigestVerifier, RecoveryId, Signature, VerifyingKey},
 };
 
 /// NOTE: This function takes the hash of the message, not the prehash.
@@ -16,6 +16,32 @@ pub fn secp256k1_verify(msg_hash: &[u8], sig: &[u8], pk: &[u8]) -> CryptoResult<
     vk.verify_digest(msg, &sig).map_err(Into::into)
 }
 
+/// Recover the public key that produced the signature over the message hash,
+/// given the recovery ID (0 or 1, or 2 or 3 in the unlikely case that the x
+/// coordinate of the nonce point overflowed the curve order).
+///
+/// Returns the public key in SEC1 encoding, compressed (33 bytes) or not (65
+/// bytes) as specified. For Ethereum-style signatures, the recovery ID is the
+/// `v` value minus 27.
+///
+/// NOTE: This function takes the hash of the message, not the prehash.
+pub fn secp256k1_pubkey_recover(
+    msg_hash:    &[u8],
+    sig:         &[u8],
+    recovery_id: u8,
+    compressed:  bool,
+) -> CryptoResult<Vec<u8>> {
+    let msg = Identity256::from_slice(msg_hash)?;
+    if sig.len() != 64 {
+        return Err(CryptoError::incorrect_length(64, sig.len()));
+    }
+    let sig = Signature::from_bytes(sig.into())?;
+    let recovery_id =
+        RecoveryId::from_byte(recovery_id).ok_or(CryptoError::invalid_recovery_id(recovery_id))?;
+    let vk = VerifyingKey::recover_from_digest(msg, &sig, recovery_id)?;
+    Ok(vk.to_encoded_point(compressed).as_bytes().to_vec())
+}
+
 // ----------------------------------- tests -----------------------------------
 
 #[cfg(test)]
@@ -82,4 +108,44 @@ mod tests {
             .is_err());
         }
     }
+
+    #[test]
+    fn recovering_secp256k1_pubkey() {
+        let sk = SigningKey::random(&mut OsRng);
+        let vk = VerifyingKey::from(&sk);
+        let msg = hash(b"Jake");
+        let (sig, recovery_id) = sk.sign_digest_recoverable(msg.clone()).unwrap();
+
+        // correct recovery ID, compressed and uncompressed
+        for compressed in [true, false] {
+            let pk = secp256k1_pubkey_recover(
+                msg.as_bytes(),
+                sig.to_vec().as_slice(),
+                recovery_id.to_byte(),
+                compressed,
+            )
+            .unwrap();
+            assert_eq!(pk, vk.to_encoded_point(compressed).as_bytes());
+        }
+
+        // incorrect recovery ID
+        {
+            let false_recovery_id = recovery_id.to_byte() ^ 1;
+            let recovered = secp256k1_pubkey_recover(
+                msg.as_bytes(),
+                sig.to_vec().as_slice(),
+                false_recovery_id,
+                true,
+            );
+            assert!(recovered.map_or(true, |pk| pk != vk.to_encoded_point(true).as_bytes()));
+        }
+
+        // invalid recovery ID
+        {
+            assert!(matches!(
+                secp256k1_pubkey_recover(msg.as_bytes(), sig.to_vec().as_slice(), 4, true),
+                Err(CryptoError::InvalidRecoveryId { recovery_id: 4 })
+            ));
+        }
+    }
 }
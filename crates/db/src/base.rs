@@ -1,6 +1,9 @@
 use {
-    crate::{CacheStore, DbError, DbResult, U64Comparator, U64Timestamp},
-    cw_jmt::{MerkleTree, Proof},
+    crate::{
+        decode_chunk, encode_chunk, CacheStore, DbError, DbResult, Snapshot, U64Comparator,
+        U64Timestamp, SNAPSHOT_CHUNK_SIZE, SNAPSHOT_FORMAT,
+    },
+    cw_jmt::{MerkleTree, MultiProof, Proof},
     cw_std::{hash, Batch, Hash, Op, Order, Record, Storage},
     rocksdb::{
         BoundColumnFamily, DBWithThreadMode, IteratorMode, MultiThreaded, Options, ReadOptions,
@@ -9,11 +12,13 @@ use {
     std::{
         cell::OnceCell,
         path::Path,
-        sync::{Arc, RwLock},
+        sync::{Arc, Mutex, MutexGuard, RwLock},
+        thread::{self, JoinHandle},
     },
+    tracing::{error, info, warn},
 };
 
-/// We use three column families (CFs) for storing data.
+/// We use five column families (CFs) for storing data.
 /// The default family is used for metadata. Currently the only metadata we have
 /// is the latest version.
 const CF_NAME_DEFAULT: &str = "default";
@@ -35,12 +40,101 @@ const CF_NAME_STATE_COMMITMENT: &str = "state_commitment";
 /// https://github.com/cwsoftware123/rust-rocksdb/tree/v0.21.0-cw
 const CF_NAME_STATE_STORAGE: &str = "state_storage";
 
+/// The snapshots family stores snapshots of the state storage, which are served
+/// to other nodes for state sync. For each snapshot, the metadata and each of
+/// the chunks are stored under the following keys, respectively:
+///
+/// - `m` | version
+/// - `c` | version | chunk_index
+///
+/// where version is u64 big endian and chunk index is u32 big endian.
+const CF_NAME_SNAPSHOTS: &str = "snapshots";
+
+/// The tx index family stores the results and events of transactions, such
+/// that they can be looked up by tx hash or searched by event attributes. It is
+/// not part of the state commitment and isn't versioned; the layout of the
+/// data is decided by the app. See `BaseStore::flush_tx_index`.
+const CF_NAME_TX_INDEX: &str = "tx_index";
+
 /// Storage key for the latest version.
 const LATEST_VERSION_KEY: &[u8] = b"latest_version";
 
 /// Jellyfish Merkle tree (JMT) using default namespaces.
 const MERKLE_TREE: MerkleTree = MerkleTree::new_default();
 
+/// Determines which historical versions of the state commitment are kept.
+///
+/// Pruning deletes Merkle tree nodes that are no longer part of the tree, after
+/// which the root hash can no longer be queried and proofs can no longer be
+/// generated at the pruned versions.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PruningPolicy {
+    /// Keep all versions. Used by archive nodes.
+    #[default]
+    KeepAll,
+    /// Only keep the most recent given number of versions.
+    KeepRecent(u64),
+    /// Keep one version out of every given number of versions, as well as all
+    /// versions since the last kept one.
+    KeepEvery(u64),
+}
+
+impl PruningPolicy {
+    /// Return the range of versions to be pruned after the given version has
+    /// been written, in the format of (after_version, up_to_version), as taken
+    /// by `MerkleTree::prune_between`, i.e. versions greater than after_version
+    /// (if specified) and less than up_to_version. Return `None` if nothing is
+    /// to be pruned.
+    pub fn versions_to_prune(self, version: u64) -> Option<(Option<u64>, u64)> {
+        match self {
+            PruningPolicy::KeepAll => None,
+            PruningPolicy::KeepRecent(num) => {
+                // the oldest version to keep is `version + 1 - num`. prune the
+                // nodes that had been orphaned by then.
+                (version + 1).checked_sub(num.max(1)).map(|up_to| (None, up_to))
+            },
+            PruningPolicy::KeepEvery(num) => {
+                // once a new version to be kept is written, prune the versions
+                // between it and the last kept one.
+                let num = num.max(1);
+                if version >= num && version % num == 0 {
+                    Some((Some(version - num), version))
+                } else {
+                    None
+                }
+            },
+        }
+    }
+
+    /// Return whether the given version has been pruned, given the latest
+    /// version that has been written.
+    pub fn is_pruned(self, version: u64, latest_version: u64) -> bool {
+        match self {
+            PruningPolicy::KeepAll => false,
+            PruningPolicy::KeepRecent(num) => version + num.max(1) <= latest_version,
+            PruningPolicy::KeepEvery(num) => {
+                // versions before the last kept one are pruned, except for the
+                // ones that are themselves kept
+                let num = num.max(1);
+                version % num != 0 && version < latest_version - latest_version % num
+            },
+        }
+    }
+}
+
+/// Options for opening a `BaseStore`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BaseStoreOptions {
+    /// Which historical versions of the state commitment to keep.
+    pub pruning:              PruningPolicy,
+    /// Take a snapshot of the state storage once every this many versions.
+    /// If unspecified, no snapshot is taken.
+    pub snapshot_interval:    Option<u64>,
+    /// The number of most recent snapshots to keep. Older ones are deleted
+    /// once a new snapshot is taken. Zero means keeping all snapshots.
+    pub snapshot_keep_recent: u32,
+}
+
 /// The base storage primitive.
 ///
 /// Its main feature is the separation of state storage (SS) and state commitment
@@ -73,17 +167,23 @@ pub struct BaseStore {
 }
 
 struct BaseStoreInner {
-    db: DBWithThreadMode<MultiThreaded>,
+    db:      DBWithThreadMode<MultiThreaded>,
+    options: BaseStoreOptions,
     // data that are ready to be persisted to the physical database.
     // ideally we want to just use a rocksdb::WriteBatch here, but it's not
     // thread-safe.
     pending_data: RwLock<Option<PendingData>>,
+    // the snapshot being restored during state sync, if any.
+    restoration: Mutex<Option<Restoration>>,
+    // the background thread taking the latest snapshot, if any.
+    snapshot_task: Mutex<Option<JoinHandle<()>>>,
 }
 
 pub(crate) struct PendingData {
     version:          u64,
     state_commitment: Batch,
     state_storage:    Batch,
+    tx_index:         Batch,
 }
 
 impl Clone for BaseStore {
@@ -96,7 +196,17 @@ impl Clone for BaseStore {
 
 impl BaseStore {
     /// Create a BaseStore instance by opening a physical RocksDB instance.
+    /// All historical versions are kept, and no snapshot is taken.
     pub fn open(data_dir: impl AsRef<Path>) -> DbResult<Self> {
+        Self::open_with_options(data_dir, BaseStoreOptions::default())
+    }
+
+    /// Create a BaseStore instance by opening a physical RocksDB instance,
+    /// with the given pruning and snapshot options.
+    pub fn open_with_options(
+        data_dir: impl AsRef<Path>,
+        options:  BaseStoreOptions,
+    ) -> DbResult<Self> {
         // note: for default and state commitment CFs, don't enable timestamping;
         // for state storage column family, enable timestamping.
         let db = DBWithThreadMode::open_cf_with_opts(
@@ -106,13 +216,18 @@ impl BaseStore {
                 (CF_NAME_DEFAULT, Options::default()),
                 (CF_NAME_STATE_COMMITMENT, Options::default()),
                 (CF_NAME_STATE_STORAGE, new_cf_options_with_ts()),
+                (CF_NAME_SNAPSHOTS, Options::default()),
+                (CF_NAME_TX_INDEX, Options::default()),
             ],
         )?;
 
         Ok(Self {
             inner: Arc::new(BaseStoreInner {
                 db,
+                options,
                 pending_data: RwLock::new(None),
+                restoration: Mutex::new(None),
+                snapshot_task: Mutex::new(None),
             }),
         })
     }
@@ -144,6 +259,23 @@ impl BaseStore {
         }
     }
 
+    /// Return a `TxIndex` object which implements the `Storage` trait, for
+    /// reading the tx index as of the latest committed version.
+    ///
+    /// NOTE: `TxIndex` is read-only. Attempting to call write/remove/flush
+    /// leads to panicking. Wrap it in a `CacheStore`, and write the changes
+    /// using `flush_tx_index` instead.
+    pub fn tx_index(&self) -> TxIndex {
+        TxIndex {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+
+    /// Return the policy by which historical versions are pruned.
+    pub fn pruning_policy(&self) -> PruningPolicy {
+        self.inner.options.pruning
+    }
+
     /// Return the latest version of the state that the database stores.
     pub fn latest_version(&self) -> Option<u64> {
         let cf = cf_default(&self.inner.db);
@@ -174,6 +306,14 @@ impl BaseStore {
         Ok(MERKLE_TREE.prove(&self.state_commitment(), &hash(key), version)?)
     }
 
+    /// Generate a single Merkle proof for multiple keys at the given version
+    /// (default to latest version if not specified).
+    pub fn prove_multi(&self, keys: &[&[u8]], version: Option<u64>) -> DbResult<MultiProof> {
+        let version = version.unwrap_or_else(|| self.latest_version().unwrap_or(0));
+        let key_hashes = keys.iter().map(hash).collect::<Vec<_>>();
+        Ok(MERKLE_TREE.prove_multi(&self.state_commitment(), &key_hashes, version)?)
+    }
+
     /// Flush a batch of ops (inserts/deletes) into a write batch, incrementing
     /// the version. Return the updated version and hash. However, do not persist
     /// the batch to disk yet; just keep it in memory. Call `commit` to persist
@@ -203,15 +343,40 @@ impl BaseStore {
         let mut cache = CacheStore::new(self.state_commitment(), None);
         let root_hash = MERKLE_TREE.apply_raw(&mut cache, old_version, new_version, &batch)?;
 
+        // prune the state commitment according to the pruning policy. the
+        // deletions are also kept in the PendingData, so they are persisted
+        // atomically together with the new version
+        if let Some((after, up_to)) = self.inner.options.pruning.versions_to_prune(new_version) {
+            MERKLE_TREE.prune_between(&mut cache, after, Some(up_to))?;
+        }
+
         *(self.inner.pending_data.write()?) = Some(PendingData {
             version:          new_version,
             state_commitment: cache.pending,
             state_storage:    batch,
+            tx_index:         Batch::new(),
         });
 
         Ok((new_version, root_hash))
     }
 
+    /// Add a batch of ops to be written to the tx index, in addition to the
+    /// state changes that have been flushed but not committed. They are
+    /// persisted atomically together with the new version in `commit`.
+    ///
+    /// This must be called after `flush_but_not_commit` and before `commit`.
+    pub fn flush_tx_index(&self, batch: Batch) -> DbResult<()> {
+        self.inner
+            .pending_data
+            .write()?
+            .as_mut()
+            .ok_or(DbError::PendingDataNotSet)?
+            .tx_index
+            .extend(batch);
+
+        Ok(())
+    }
+
     /// Persist pending data to the physical DB.
     pub fn commit(&self) -> DbResult<()> {
         let pending = self.inner.pending_data.write()?.take().ok_or(DbError::PendingDataNotSet)?;
@@ -242,7 +407,28 @@ impl BaseStore {
             }
         }
 
-        Ok(self.inner.db.write(batch)?)
+        // writes in tx index
+        let cf = cf_tx_index(&self.inner.db);
+        for (key, op) in pending.tx_index {
+            if let Op::Insert(value) = op {
+                batch.put_cf(&cf, key, value);
+            } else {
+                batch.delete_cf(&cf, key);
+            }
+        }
+
+        self.inner.db.write(batch)?;
+
+        // take a snapshot if it's due at this version. this is done in the
+        // background, so that consensus isn't blocked while the whole state is
+        // being dumped. the block has already been persisted at this point, so
+        // a failed snapshot is logged but doesn't fail the commit.
+        let interval = self.inner.options.snapshot_interval;
+        if pending.version > 0 && interval.and_then(|i| pending.version.checked_rem(i)) == Some(0) {
+            self.spawn_snapshot(pending.version);
+        }
+
+        Ok(())
     }
 
     /// Do `flush_but_not_commit` and `commit` in one go.
@@ -256,6 +442,250 @@ impl BaseStore {
     }
 }
 
+// --------------------------------- snapshots ---------------------------------
+
+/// A snapshot being restored, and the chunks that have been applied so far.
+struct Restoration {
+    snapshot:   Snapshot,
+    app_hash:   Hash,
+    next_chunk: u32,
+    records:    Batch,
+}
+
+impl BaseStore {
+    /// Return all snapshots that are available, in ascending order by version.
+    pub fn list_snapshots(&self) -> DbResult<Vec<Snapshot>> {
+        let cf = cf_snapshots(&self.inner.db);
+        let opts = new_read_options(None, Some(b"m".as_slice()), Some(b"n".as_slice()));
+        self.inner
+            .db
+            .iterator_cf_opt(&cf, opts, IteratorMode::Start)
+            .map(|item| {
+                let (k, v) = item?;
+                let version = <[u8; 8]>::try_from(&k[1..]).unwrap_or_else(|_| {
+                    panic!("snapshot metadata key is of incorrect byte length: {}", k.len());
+                });
+                let version = u64::from_be_bytes(version);
+                let (format, metadata) = v.split_at(4);
+                let format = u32::from_be_bytes(format.try_into().unwrap());
+                Snapshot::from_metadata(version, format, metadata)
+            })
+            .collect()
+    }
+
+    /// Load a chunk of the snapshot at the given version. Return `None` if
+    /// either the snapshot or the chunk doesn't exist.
+    pub fn load_snapshot_chunk(
+        &self,
+        version: u64,
+        format:  u32,
+        index:   u32,
+    ) -> DbResult<Option<Vec<u8>>> {
+        if format != SNAPSHOT_FORMAT {
+            return Ok(None);
+        }
+
+        let cf = cf_snapshots(&self.inner.db);
+        Ok(self.inner.db.get_cf(&cf, snapshot_chunk_key(version, index))?)
+    }
+
+    /// Start restoring the given snapshot. The database must be empty, i.e.
+    /// not a single version has been committed yet.
+    ///
+    /// `app_hash` is the trusted root hash of the state commitment at the
+    /// snapshot's version, against which the restored state is checked.
+    pub fn offer_snapshot(&self, snapshot: Snapshot, app_hash: Hash) -> DbResult<()> {
+        if snapshot.format != SNAPSHOT_FORMAT {
+            return Err(DbError::unsupported_snapshot_format(snapshot.format));
+        }
+
+        if self.latest_version().is_some() {
+            return Err(DbError::SnapshotRestoreNotAllowed);
+        }
+
+        *self.restoration() = Some(Restoration {
+            snapshot,
+            app_hash,
+            next_chunk: 0,
+            records:    Batch::new(),
+        });
+
+        Ok(())
+    }
+
+    /// Apply a chunk of the snapshot being restored. Chunks must be applied in
+    /// order. Return whether the restoration is complete, i.e. this is the last
+    /// chunk.
+    ///
+    /// Once all chunks have been applied, the Merkle tree is rebuilt from the
+    /// records, and the result is checked against the app hash. If matches, the
+    /// records and tree nodes are committed to disk at the snapshot's version.
+    /// If not, the restoration is aborted.
+    ///
+    /// NOTE: the records are kept in memory until the restoration completes.
+    pub fn apply_snapshot_chunk(&self, index: u32, chunk: &[u8]) -> DbResult<bool> {
+        let mut lock = self.restoration();
+        let restoration = lock.as_mut().ok_or(DbError::SnapshotNotOffered)?;
+
+        if index != restoration.next_chunk {
+            return Err(DbError::unexpected_snapshot_chunk(restoration.next_chunk, index));
+        }
+
+        if restoration.snapshot.chunk_hashes.get(index as usize) != Some(&hash(chunk)) {
+            return Err(DbError::snapshot_chunk_hash_mismatch(index));
+        }
+
+        for (k, v) in decode_chunk(chunk)? {
+            restoration.records.insert(k, Op::Insert(v));
+        }
+
+        restoration.next_chunk += 1;
+        if restoration.next_chunk < restoration.snapshot.num_chunks() {
+            return Ok(false);
+        }
+
+        // all chunks have been applied. whether the restoration succeeds or
+        // not, it's no longer in progress.
+        let restoration = lock.take().unwrap();
+        drop(lock);
+
+        self.restore(restoration)?;
+
+        Ok(true)
+    }
+
+    fn restore(&self, restoration: Restoration) -> DbResult<()> {
+        let version = restoration.snapshot.version;
+
+        // the database is empty, so the old version doesn't matter
+        let mut cache = CacheStore::new(self.state_commitment(), None);
+        let root_hash = MERKLE_TREE.apply_raw(&mut cache, 0, version, &restoration.records)?;
+
+        if root_hash != Some(restoration.app_hash) {
+            return Err(DbError::snapshot_root_hash_mismatch(restoration.app_hash, root_hash));
+        }
+
+        if self.inner.pending_data.read()?.is_some() {
+            return Err(DbError::PendingDataAlreadySet);
+        }
+
+        *(self.inner.pending_data.write()?) = Some(PendingData {
+            version,
+            state_commitment: cache.pending,
+            state_storage:    restoration.records,
+            tx_index:         Batch::new(),
+        });
+
+        self.commit()
+    }
+
+    /// Export the state storage at the given version as a snapshot. The chunks
+    /// are written first, and the metadata last, so an incomplete snapshot is
+    /// never listed. Then, delete old snapshots if necessary.
+    fn take_snapshot(&self, version: u64) -> DbResult<()> {
+        let cf = cf_snapshots(&self.inner.db);
+        let mut chunk_hashes = vec![];
+
+        let storage = self.state_storage(Some(version));
+        let mut iter = storage.scan(None, None, Order::Ascending).peekable();
+        while iter.peek().is_some() {
+            let mut records = vec![];
+            let mut size = 0;
+            while size < SNAPSHOT_CHUNK_SIZE {
+                let Some((k, v)) = iter.next() else {
+                    break;
+                };
+                size += k.len() + v.len() + 8;
+                records.push((k, v));
+            }
+
+            let chunk = encode_chunk(&records);
+            let index = chunk_hashes.len() as u32;
+            chunk_hashes.push(hash(&chunk));
+            self.inner.db.put_cf(&cf, snapshot_chunk_key(version, index), chunk)?;
+        }
+
+        let snapshot = Snapshot {
+            version,
+            format: SNAPSHOT_FORMAT,
+            chunk_hashes,
+        };
+        let mut value = snapshot.format.to_be_bytes().to_vec();
+        value.extend(snapshot.metadata());
+        self.inner.db.put_cf(&cf, snapshot_metadata_key(version), value)?;
+
+        let keep_recent = self.inner.options.snapshot_keep_recent as usize;
+        if keep_recent == 0 {
+            return Ok(());
+        }
+
+        let snapshots = self.list_snapshots()?;
+        let num_to_delete = snapshots.len().saturating_sub(keep_recent);
+        let mut batch = WriteBatch::default();
+        for snapshot in &snapshots[..num_to_delete] {
+            batch.delete_cf(&cf, snapshot_metadata_key(snapshot.version));
+            batch.delete_range_cf(
+                &cf,
+                snapshot_chunk_key(snapshot.version, 0),
+                snapshot_chunk_key(snapshot.version + 1, 0),
+            );
+        }
+
+        Ok(self.inner.db.write(batch)?)
+    }
+
+    /// Block until the snapshot being taken in the background, if any, is done.
+    pub fn wait_for_snapshot(&self) {
+        if let Some(handle) = self.snapshot_task().take() {
+            if handle.join().is_err() {
+                error!("Snapshot thread panicked");
+            }
+        }
+    }
+
+    fn spawn_snapshot(&self, version: u64) {
+        let mut task = self.snapshot_task();
+
+        // snapshots are taken one at a time. if the previous one is still in
+        // progress, skip this one instead of making the commit wait for it.
+        if task.as_ref().is_some_and(|handle| !handle.is_finished()) {
+            warn!(version, "Skipped snapshot as the previous one is still in progress");
+            return;
+        }
+
+        let store = self.clone();
+        *task = Some(thread::spawn(move || match store.take_snapshot(version) {
+            Ok(()) => info!(version, "Took snapshot"),
+            Err(err) => error!(version, err = err.to_string(), "Failed to take snapshot"),
+        }));
+    }
+
+    fn snapshot_task(&self) -> MutexGuard<Option<JoinHandle<()>>> {
+        self.inner.snapshot_task.lock().unwrap_or_else(|err| {
+            panic!("poisoned lock: {err:?}")
+        })
+    }
+
+    fn restoration(&self) -> MutexGuard<Option<Restoration>> {
+        self.inner.restoration.lock().unwrap_or_else(|err| {
+            panic!("poisoned lock: {err:?}")
+        })
+    }
+}
+
+fn snapshot_metadata_key(version: u64) -> Vec<u8> {
+    let mut key = b"m".to_vec();
+    key.extend_from_slice(&version.to_be_bytes());
+    key
+}
+
+fn snapshot_chunk_key(version: u64, index: u32) -> Vec<u8> {
+    let mut key = b"c".to_vec();
+    key.extend_from_slice(&version.to_be_bytes());
+    key.extend_from_slice(&index.to_be_bytes());
+    key
+}
+
 // ----------------------------- state commitment ------------------------------
 
 pub struct StateCommitment {
@@ -279,11 +709,22 @@ impl Storage for StateCommitment {
 
     fn scan<'a>(
         &'a self,
-        _min:   Option<&[u8]>,
-        _max:   Option<&[u8]>,
-        _order: Order,
+        min:   Option<&[u8]>,
+        max:   Option<&[u8]>,
+        order: Order,
     ) -> Box<dyn Iterator<Item = Record> + 'a> {
-        unimplemented!("this isn't used by the MerkleTree")
+        let opts = new_read_options(None, min, max);
+        let mode = match order {
+            Order::Ascending => IteratorMode::Start,
+            Order::Descending => IteratorMode::End,
+        };
+        let iter = self.inner.db.iterator_cf_opt(&cf_state_commitment(&self.inner.db), opts, mode).map(|item| {
+            let (k, v) = item.unwrap_or_else(|err| {
+                panic!("failed to iterate in state commitment: {err}");
+            });
+            (k.to_vec(), v.to_vec())
+        });
+        Box::new(iter)
     }
 
     fn write(&mut self, _key: &[u8], _value: &[u8]) {
@@ -363,6 +804,60 @@ impl Storage for StateStorage {
     }
 }
 
+// --------------------------------- tx index ----------------------------------
+
+pub struct TxIndex {
+    inner: Arc<BaseStoreInner>,
+}
+
+impl Clone for TxIndex {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl Storage for TxIndex {
+    fn read(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.inner.db.get_cf(&cf_tx_index(&self.inner.db), key).unwrap_or_else(|err| {
+            panic!("failed to read from tx index: {err}");
+        })
+    }
+
+    fn scan<'a>(
+        &'a self,
+        min:   Option<&[u8]>,
+        max:   Option<&[u8]>,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = Record> + 'a> {
+        let opts = new_read_options(None, min, max);
+        let mode = match order {
+            Order::Ascending => IteratorMode::Start,
+            Order::Descending => IteratorMode::End,
+        };
+        let iter = self.inner.db.iterator_cf_opt(&cf_tx_index(&self.inner.db), opts, mode).map(|item| {
+            let (k, v) = item.unwrap_or_else(|err| {
+                panic!("failed to iterate in tx index: {err}");
+            });
+            (k.to_vec(), v.to_vec())
+        });
+        Box::new(iter)
+    }
+
+    fn write(&mut self, _key: &[u8], _value: &[u8]) {
+        unreachable!("write function called on read-only storage");
+    }
+
+    fn remove(&mut self, _key: &[u8]) {
+        unreachable!("write function called on read-only storage");
+    }
+
+    fn flush(&mut self, _batch: Batch) {
+        unreachable!("write function called on read-only storage");
+    }
+}
+
 // ---------------------------------- helpers ----------------------------------
 
 // TODO: rocksdb tuning? see:
@@ -424,6 +919,18 @@ fn cf_state_commitment(db: &DBWithThreadMode<MultiThreaded>) -> Arc<BoundColumnF
     })
 }
 
+fn cf_snapshots(db: &DBWithThreadMode<MultiThreaded>) -> Arc<BoundColumnFamily> {
+    db.cf_handle(CF_NAME_SNAPSHOTS).unwrap_or_else(|| {
+        panic!("failed to find snapshots column family");
+    })
+}
+
+fn cf_tx_index(db: &DBWithThreadMode<MultiThreaded>) -> Arc<BoundColumnFamily> {
+    db.cf_handle(CF_NAME_TX_INDEX).unwrap_or_else(|| {
+        panic!("failed to find tx index column family");
+    })
+}
+
 // ----------------------------------- test ------------------------------------
 
 #[cfg(test)]
@@ -704,4 +1211,174 @@ mod tests {
             .is_ok());
         }
     }
+
+    #[test]
+    fn pruning_works() {
+        let path = TempDataDir::new("_cw_db_pruning_works");
+        let store = BaseStore::open_with_options(&path, BaseStoreOptions {
+            pruning: PruningPolicy::KeepRecent(1),
+            ..Default::default()
+        })
+        .unwrap();
+
+        // write the same two batches as in the previous test
+        store.flush_and_commit(Batch::from([
+            (b"donald".to_vec(), Op::Insert(b"trump".to_vec())),
+            (b"jake".to_vec(),   Op::Insert(b"shepherd".to_vec())),
+            (b"joe".to_vec(),    Op::Insert(b"biden".to_vec())),
+            (b"larry".to_vec(),  Op::Insert(b"engineer".to_vec())),
+        ]))
+        .unwrap();
+        store.flush_and_commit(Batch::from([
+            (b"donald".to_vec(),  Op::Delete),
+            (b"joe".to_vec(),     Op::Delete),
+            (b"pumpkin".to_vec(), Op::Insert(b"cat".to_vec())),
+        ]))
+        .unwrap();
+
+        // version 1 should have been pruned, while version 2 is intact
+        assert_eq!(store.root_hash(Some(1)).unwrap(), None);
+        let root_hash = store.root_hash(Some(2)).unwrap().unwrap();
+        for (key, value) in [
+            ("donald",  None),
+            ("jake",    Some("shepherd")),
+            ("larry",   Some("engineer")),
+            ("pumpkin", Some("cat")),
+        ] {
+            let proof = store.prove(key.as_bytes(), Some(2)).unwrap();
+            assert!(verify_proof(
+                &root_hash,
+                &hash(key.as_bytes()),
+                value.map(hash).as_ref(),
+                &proof,
+            )
+            .is_ok());
+        }
+    }
+
+    #[test]
+    fn pruning_policy_works() {
+        for (policy, version, expect) in [
+            (PruningPolicy::KeepAll,       100, None),
+            (PruningPolicy::KeepRecent(1), 0,   Some((None, 0))),
+            (PruningPolicy::KeepRecent(5), 3,   None),
+            (PruningPolicy::KeepRecent(5), 100, Some((None, 96))),
+            (PruningPolicy::KeepEvery(10), 0,   None),
+            (PruningPolicy::KeepEvery(10), 15,  None),
+            (PruningPolicy::KeepEvery(10), 20,  Some((Some(10), 20))),
+        ] {
+            assert_eq!(policy.versions_to_prune(version), expect);
+        }
+    }
+
+    #[test]
+    fn checking_pruned_versions_works() {
+        for (policy, version, latest_version, expect) in [
+            (PruningPolicy::KeepAll,       1,  100, false),
+            (PruningPolicy::KeepRecent(5), 95, 100, true),
+            (PruningPolicy::KeepRecent(5), 96, 100, false),
+            (PruningPolicy::KeepRecent(5), 0,  3,   false),
+            (PruningPolicy::KeepEvery(10), 10, 25,  false),
+            (PruningPolicy::KeepEvery(10), 15, 25,  true),
+            (PruningPolicy::KeepEvery(10), 21, 25,  false),
+        ] {
+            assert_eq!(policy.is_pruned(version, latest_version), expect);
+        }
+    }
+
+    #[test]
+    fn tx_index_works() {
+        let path = TempDataDir::new("_cw_db_tx_index_works");
+        let store = BaseStore::open(&path).unwrap();
+
+        // the tx index can't be written without pending state changes
+        assert!(matches!(
+            store.flush_tx_index(Batch::new()),
+            Err(DbError::PendingDataNotSet)
+        ));
+
+        store.flush_but_not_commit(Batch::from([
+            (b"larry".to_vec(), Op::Insert(b"engineer".to_vec())),
+        ]))
+        .unwrap();
+        store.flush_tx_index(Batch::from([
+            (b"tx1".to_vec(), Op::Insert(b"ok".to_vec())),
+            (b"tx2".to_vec(), Op::Insert(b"failed".to_vec())),
+        ]))
+        .unwrap();
+
+        // nothing is visible until committed
+        assert_eq!(store.tx_index().read(b"tx1"), None);
+        store.commit().unwrap();
+        assert_eq!(store.tx_index().read(b"tx1"), Some(b"ok".to_vec()));
+
+        store.flush_but_not_commit(Batch::new()).unwrap();
+        store.flush_tx_index(Batch::from([(b"tx1".to_vec(), Op::Delete)])).unwrap();
+        store.commit().unwrap();
+
+        // the tx index isn't versioned, and isn't part of the state commitment
+        let records = store.tx_index().scan(None, None, Order::Ascending).collect::<Vec<_>>();
+        assert_eq!(records, [(b"tx2".to_vec(), b"failed".to_vec())]);
+        assert_eq!(store.state_storage(None).read(b"tx2"), None);
+        assert_eq!(store.root_hash(Some(0)).unwrap(), store.root_hash(Some(1)).unwrap());
+    }
+
+    #[test]
+    fn snapshot_works() {
+        let path = TempDataDir::new("_cw_db_snapshot_works");
+        let store = BaseStore::open_with_options(&path, BaseStoreOptions {
+            snapshot_interval:    Some(1),
+            snapshot_keep_recent: 1,
+            ..Default::default()
+        })
+        .unwrap();
+
+        // write the same two batches as in the first test
+        store.flush_and_commit(Batch::from([
+            (b"donald".to_vec(), Op::Insert(b"trump".to_vec())),
+            (b"jake".to_vec(),   Op::Insert(b"shepherd".to_vec())),
+            (b"joe".to_vec(),    Op::Insert(b"biden".to_vec())),
+            (b"larry".to_vec(),  Op::Insert(b"engineer".to_vec())),
+        ]))
+        .unwrap();
+        let (version, root_hash) = store.flush_and_commit(Batch::from([
+            (b"donald".to_vec(),  Op::Insert(b"duck".to_vec())),
+            (b"joe".to_vec(),     Op::Delete),
+            (b"pumpkin".to_vec(), Op::Insert(b"cat".to_vec())),
+        ]))
+        .unwrap();
+        let root_hash = root_hash.unwrap();
+
+        // only the most recent snapshot should have been kept
+        store.wait_for_snapshot();
+        let snapshots = store.list_snapshots().unwrap();
+        assert_eq!(snapshots.len(), 1);
+        let snapshot = snapshots[0].clone();
+        assert_eq!(snapshot.version, version);
+
+        // restore the snapshot into an empty DB
+        let restored_path = TempDataDir::new("_cw_db_snapshot_works_restored");
+        let restored = BaseStore::open(&restored_path).unwrap();
+        restored.offer_snapshot(snapshot.clone(), root_hash).unwrap();
+        for index in 0..snapshot.num_chunks() {
+            let chunk = store.load_snapshot_chunk(version, SNAPSHOT_FORMAT, index).unwrap();
+            let chunk = chunk.unwrap();
+            let done = restored.apply_snapshot_chunk(index, &chunk).unwrap();
+            assert_eq!(done, index + 1 == snapshot.num_chunks());
+        }
+
+        // the restored DB should have the same version, root hash, and data
+        assert_eq!(restored.latest_version(), Some(version));
+        assert_eq!(restored.root_hash(None).unwrap(), Some(root_hash));
+        assert_eq!(
+            restored.state_storage(None).scan(None, None, Order::Ascending).collect::<Vec<_>>(),
+            store.state_storage(None).scan(None, None, Order::Ascending).collect::<Vec<_>>(),
+        );
+
+        // a snapshot can't be restored into a non-empty DB
+        assert!(matches!(
+            restored.offer_snapshot(snapshot, root_hash),
+            Err(DbError::SnapshotRestoreNotAllowed)
+        ));
+    }
 }
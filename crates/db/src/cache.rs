@@ -84,6 +84,28 @@ impl<S: Storage + Clone> Storage for CacheStore<S> {
         self.pending.insert(key.to_vec(), Op::Delete);
     }
 
+    fn remove_range(&mut self, min: Option<&[u8]>, max: Option<&[u8]>) {
+        if let (Some(min), Some(max)) = (min, max) {
+            if min > max {
+                return;
+            }
+        }
+
+        // tombstone the keys that exist in the base store within the range.
+        // keys that only exist in the pending batch can simply be forgotten,
+        // but it's harmless to tombstone them as well, which is simpler.
+        let base_keys = self.base.scan(min, max, Order::Ascending).map(|(k, _)| k);
+
+        let min = min.map_or(Bound::Unbounded, |bytes| Bound::Included(bytes.to_vec()));
+        let max = max.map_or(Bound::Unbounded, |bytes| Bound::Excluded(bytes.to_vec()));
+        let pending_keys = self.pending.range((min, max)).map(|(k, _)| k.clone());
+
+        let keys = base_keys.chain(pending_keys).collect::<Vec<_>>();
+        for key in keys {
+            self.pending.insert(key, Op::Delete);
+        }
+    }
+
     fn flush(&mut self, batch: Batch) {
         // if we do a.extend(b), while a and b have common keys, the values in b
         // are chosen. this is exactly what we want.
@@ -207,5 +229,24 @@ mod tests {
         assert_eq!(collect_records(&cached, Order::Descending), merged);
     }
 
+    #[test]
+    fn removing_range_works() {
+        let (mut cached, _) = make_test_case();
+        cached.remove_range(Some(&[3]), Some(&[6]));
+
+        // 3 only exists in pending, 4 and 5 only exist in base; all of them
+        // should be deleted, while 6 (the exclusive max bound) remains
+        let merged = vec![
+            (vec![1], vec![1]),
+            (vec![6], vec![255]),
+            (vec![8], vec![8]),
+        ];
+        assert_eq!(collect_records(&cached, Order::Ascending), merged);
+
+        // after flushing to the base store, the results should be the same
+        let base = cached.consume();
+        assert_eq!(collect_records(&base, Order::Ascending), merged);
+    }
+
     // TODO: add fuzz test
 }
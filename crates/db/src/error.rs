@@ -1,6 +1,6 @@
 use {
     crate::base::PendingData,
-    cw_std::StdError,
+    cw_std::{Hash, StdError},
     std::sync::{PoisonError, RwLockReadGuard, RwLockWriteGuard},
     thiserror::Error,
 };
@@ -21,6 +21,58 @@ pub enum DbError {
 
     #[error("rwlock for the write batch is poisoned")]
     PendingDataPoisoned,
+
+    #[error("cannot restore snapshot when the database is not empty")]
+    SnapshotRestoreNotAllowed,
+
+    #[error("unsupported snapshot format: {format}")]
+    UnsupportedSnapshotFormat {
+        format: u32,
+    },
+
+    #[error("invalid snapshot metadata: malformed or inconsistent with the snapshot")]
+    InvalidSnapshotMetadata,
+
+    #[error("invalid snapshot chunk: data is malformed")]
+    InvalidSnapshotChunk,
+
+    #[error("cannot apply snapshot chunk when no snapshot has been offered")]
+    SnapshotNotOffered,
+
+    #[error("unexpected snapshot chunk! expecting: {expect}, actual: {actual}")]
+    UnexpectedSnapshotChunk {
+        expect: u32,
+        actual: u32,
+    },
+
+    #[error("snapshot chunk {index} doesn't match its hash")]
+    SnapshotChunkHashMismatch {
+        index: u32,
+    },
+
+    #[error("restored root hash mismatches app hash! expecting: {expect}, actual: {actual:?}")]
+    SnapshotRootHashMismatch {
+        expect: Hash,
+        actual: Option<Hash>,
+    },
+}
+
+impl DbError {
+    pub fn unsupported_snapshot_format(format: u32) -> Self {
+        Self::UnsupportedSnapshotFormat { format }
+    }
+
+    pub fn unexpected_snapshot_chunk(expect: u32, actual: u32) -> Self {
+        Self::UnexpectedSnapshotChunk { expect, actual }
+    }
+
+    pub fn snapshot_chunk_hash_mismatch(index: u32) -> Self {
+        Self::SnapshotChunkHashMismatch { index }
+    }
+
+    pub fn snapshot_root_hash_mismatch(expect: Hash, actual: Option<Hash>) -> Self {
+        Self::SnapshotRootHashMismatch { expect, actual }
+    }
 }
 
 impl<'a> From<PoisonError<RwLockReadGuard<'a, Option<PendingData>>>> for DbError {
@@ -2,16 +2,18 @@ mod base;
 mod cache;
 mod prefix;
 mod shared;
+mod snapshot;
 mod testing;
 mod timestamp;
 mod error;
 
 pub use {
-    base::{BaseStore, StateCommitment, StateStorage},
+    base::{BaseStore, BaseStoreOptions, PruningPolicy, StateCommitment, StateStorage, TxIndex},
     cache::CacheStore,
     error::{DbError, DbResult},
-    prefix::PrefixStore,
+    prefix::{prefixed_bounds, PrefixStore},
     shared::SharedStore,
+    snapshot::{decode_chunk, encode_chunk, Snapshot, SNAPSHOT_CHUNK_SIZE, SNAPSHOT_FORMAT},
     testing::TempDataDir,
     timestamp::{U64Comparator, U64Timestamp},
 };
@@ -84,6 +84,20 @@ impl<S: Storage> BackendStorage for PrefixStore<S> {
 
         Ok(())
     }
+
+    fn remove_range(&mut self, min: Option<&[u8]>, max: Option<&[u8]>) -> VmResult<usize> {
+        let (min, max) = prefixed_bounds(&self.namespace, min, max);
+
+        // count the records to be deleted, which gas is charged for
+        let count = self.store.scan(Some(&min), Some(&max), Order::Ascending).count();
+        self.store.remove_range(Some(&min), Some(&max));
+
+        // whenever KV data is mutated, delete all existing iterators to avoid
+        // race conditions.
+        self.iterators.clear();
+
+        Ok(count)
+    }
 }
 
 struct Iter {
@@ -95,14 +109,7 @@ struct Iter {
 
 impl Iter {
     pub fn new(namespace: &[u8], min: Option<&[u8]>, max: Option<&[u8]>, order: Order) -> Self {
-        let min = match min {
-            None => namespace.to_vec(),
-            Some(min) => concat(namespace, min),
-        };
-        let max = match max {
-            None => increment_last_byte(namespace.to_vec()),
-            Some(max) => concat(namespace, max),
-        };
+        let (min, max) = prefixed_bounds(namespace, min, max);
 
         Self {
             namespace: namespace.to_vec(),
@@ -124,3 +131,70 @@ impl Iter {
         Some((trim(&self.namespace, &k), v))
     }
 }
+
+/// Prefix the given bounds with the namespace. An unbounded min or max becomes
+/// the start or end of the namespace, respectively.
+///
+/// Also used by the test harness's contract storage, such that it handles the
+/// bounds the same way `PrefixStore` does.
+pub fn prefixed_bounds(
+    namespace: &[u8],
+    min:       Option<&[u8]>,
+    max:       Option<&[u8]>,
+) -> (Vec<u8>, Vec<u8>) {
+    let min = match min {
+        None => namespace.to_vec(),
+        Some(min) => concat(namespace, min),
+    };
+    let max = match max {
+        None => increment_last_byte(namespace.to_vec()),
+        Some(max) => concat(namespace, max),
+    };
+    (min, max)
+}
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {super::*, cw_std::MockStorage};
+
+    #[test]
+    fn removing_range_works() -> VmResult<()> {
+        let mut base = MockStorage::new();
+        // keys right outside the namespace on either side, which must not be
+        // affected
+        base.write(b"fon", b"1");
+        base.write(b"fop", b"2");
+
+        let mut store = PrefixStore::new(base, &[b"fo", b"o"]);
+        for key in [b"a", b"b", b"c", b"d"] {
+            store.write(key, key)?;
+        }
+
+        // removing the range invalidates existing iterators
+        let iterator_id = store.scan(None, None, Order::Ascending)?;
+        assert_eq!(store.remove_range(Some(b"b"), Some(b"d"))?, 2);
+        assert!(matches!(store.next(iterator_id), Err(VmError::IteratorNotFound { .. })));
+
+        assert_eq!(store.read(b"a")?, Some(b"a".to_vec()));
+        assert_eq!(store.read(b"b")?, None);
+        assert_eq!(store.read(b"c")?, None);
+        assert_eq!(store.read(b"d")?, Some(b"d".to_vec()));
+
+        // min > max; nothing is removed
+        assert_eq!(store.remove_range(Some(b"d"), Some(b"a"))?, 0);
+
+        // unbounded on both sides; everything in the namespace is removed
+        assert_eq!(store.remove_range(None, None)?, 2);
+
+        let base = store.disassemble();
+        let records = base.scan(None, None, Order::Ascending).collect::<Vec<_>>();
+        assert_eq!(records, vec![
+            (b"fon".to_vec(), b"1".to_vec()),
+            (b"fop".to_vec(), b"2".to_vec()),
+        ]);
+
+        Ok(())
+    }
+}
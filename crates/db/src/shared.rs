@@ -102,6 +102,10 @@ impl<S: Storage> Storage for SharedStore<S> {
         self.write_access().remove(key)
     }
 
+    fn remove_range(&mut self, min: Option<&[u8]>, max: Option<&[u8]>) {
+        self.write_access().remove_range(min, max)
+    }
+
     fn flush(&mut self, batch: Batch) {
         self.write_access().flush(batch)
     }
@@ -206,4 +210,21 @@ mod tests {
             .collect::<Vec<_>>();
         assert_eq!(records, mock_records(1, 100, Order::Descending));
     }
+
+    #[test]
+    fn removing_range_works() {
+        let mut store = SharedStore::new(MockStorage::new());
+        for (k, v) in mock_records(1, 100, Order::Ascending) {
+            store.write(&k, &v);
+        }
+
+        // the removal is visible through other handles of the same store
+        let other = store.share();
+        store.remove_range(Some(&12u32.to_be_bytes()), Some(&89u32.to_be_bytes()));
+
+        let records = other.scan(None, None, Order::Ascending).collect::<Vec<_>>();
+        let mut expect = mock_records(1, 12, Order::Ascending);
+        expect.extend(mock_records(89, 100, Order::Ascending));
+        assert_eq!(records, expect);
+    }
 }
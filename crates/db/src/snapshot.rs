@@ -0,0 +1,139 @@
+use {
+    crate::{DbError, DbResult},
+    cw_std::{hash, Hash, Record},
+};
+
+/// The format of snapshots we currently produce and accept. Increment this if
+/// the way chunks are encoded is ever changed.
+pub const SNAPSHOT_FORMAT: u32 = 1;
+
+/// Once the records collected into a chunk exceed this size (in bytes), the
+/// chunk is closed and a new one is started. CometBFT doesn't accept chunks
+/// larger than 16 MiB, so we leave some room here.
+pub const SNAPSHOT_CHUNK_SIZE: usize = 10 * 1024 * 1024;
+
+/// A snapshot of the state storage at a specific version, split into chunks.
+///
+/// Each chunk consists of a number of KV pairs, ordered ascendingly by keys,
+/// such that the state can be restored by applying the chunks one by one. The
+/// snapshot commits to its chunks by their hashes, which are transferred to
+/// other nodes as the snapshot's metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub version:      u64,
+    pub format:       u32,
+    pub chunk_hashes: Vec<Hash>,
+}
+
+impl Snapshot {
+    /// Recover a snapshot from the metadata as produced by `Snapshot::metadata`.
+    pub fn from_metadata(version: u64, format: u32, metadata: &[u8]) -> DbResult<Self> {
+        if metadata.len() % Hash::LENGTH != 0 {
+            return Err(DbError::InvalidSnapshotMetadata);
+        }
+
+        let chunk_hashes = metadata
+            .chunks(Hash::LENGTH)
+            .map(Hash::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self { version, format, chunk_hashes })
+    }
+
+    /// The snapshot's metadata, which is simply the concatenation of its chunk
+    /// hashes.
+    pub fn metadata(&self) -> Vec<u8> {
+        self.chunk_hashes.iter().flat_map(|chunk_hash| chunk_hash.to_vec()).collect()
+    }
+
+    /// The snapshot's hash, which is the hash of its metadata.
+    pub fn hash(&self) -> Hash {
+        hash(self.metadata())
+    }
+
+    pub fn num_chunks(&self) -> u32 {
+        self.chunk_hashes.len() as u32
+    }
+}
+
+/// Encode a list of KV pairs into a chunk, in the following format:
+///
+/// ```plain
+/// len(key) | key | len(value) | value | len(key) | key | ...
+/// ```
+///
+/// where len() is four bytes (u32 big endian).
+pub fn encode_chunk(records: &[Record]) -> Vec<u8> {
+    let size = records.iter().map(|(k, v)| k.len() + v.len() + 8).sum();
+    let mut chunk = Vec::with_capacity(size);
+    for (k, v) in records {
+        chunk.extend_from_slice(&(k.len() as u32).to_be_bytes());
+        chunk.extend_from_slice(k);
+        chunk.extend_from_slice(&(v.len() as u32).to_be_bytes());
+        chunk.extend_from_slice(v);
+    }
+    chunk
+}
+
+/// Decode a chunk encoded by `encode_chunk` back to a list of KV pairs.
+pub fn decode_chunk(mut chunk: &[u8]) -> DbResult<Vec<Record>> {
+    // take a length-prefixed byte slice from the front of the chunk
+    fn take<'a>(chunk: &mut &'a [u8]) -> DbResult<&'a [u8]> {
+        if chunk.len() < 4 {
+            return Err(DbError::InvalidSnapshotChunk);
+        }
+        let (len, rest) = chunk.split_at(4);
+        let len = u32::from_be_bytes(len.try_into().unwrap()) as usize;
+        if rest.len() < len {
+            return Err(DbError::InvalidSnapshotChunk);
+        }
+        let (bytes, rest) = rest.split_at(len);
+        *chunk = rest;
+        Ok(bytes)
+    }
+
+    let mut records = vec![];
+    while !chunk.is_empty() {
+        let k = take(&mut chunk)?;
+        let v = take(&mut chunk)?;
+        records.push((k.to_vec(), v.to_vec()));
+    }
+
+    Ok(records)
+}
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encoding_chunk_works() {
+        let records = vec![
+            (b"donald".to_vec(), b"trump".to_vec()),
+            (b"empty".to_vec(),  vec![]),
+            (b"joe".to_vec(),    b"biden".to_vec()),
+        ];
+        let chunk = encode_chunk(&records);
+        assert_eq!(decode_chunk(&chunk).unwrap(), records);
+
+        // a truncated chunk should fail to decode
+        assert!(matches!(
+            decode_chunk(&chunk[..chunk.len() - 1]),
+            Err(DbError::InvalidSnapshotChunk)
+        ));
+    }
+
+    #[test]
+    fn snapshot_metadata_works() {
+        let snapshot = Snapshot {
+            version:      123,
+            format:       SNAPSHOT_FORMAT,
+            chunk_hashes: vec![hash(b"chunk 0"), hash(b"chunk 1")],
+        };
+        let metadata = snapshot.metadata();
+        assert_eq!(metadata.len(), Hash::LENGTH * 2);
+        assert_eq!(Snapshot::from_metadata(123, SNAPSHOT_FORMAT, &metadata).unwrap(), snapshot);
+    }
+}
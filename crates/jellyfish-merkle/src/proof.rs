@@ -1,6 +1,7 @@
 use {
     crate::{hash_internal_node, hash_leaf_node, BitArray},
     cw_std::{cw_serde, Hash, Order},
+    std::collections::BTreeMap,
     thiserror::Error,
 };
 
@@ -25,6 +26,14 @@ pub enum ProofError {
     // TODO: add more details to the error message?
     #[error("expecting bitarrays to share a common prefix but they do not")]
     NotCommonPrefix,
+
+    #[error("key hash {key_hash} is not on any path expanded by the multi proof")]
+    KeyNotExpanded {
+        key_hash: Hash,
+    },
+
+    #[error("multi proof is deeper than the maximum tree depth")]
+    ProofTooDeep,
 }
 
 #[cw_serde]
@@ -59,6 +68,35 @@ pub enum ProofNode {
     },
 }
 
+/// A proof of the membership or non-membership of multiple keys at once,
+/// against the same root hash.
+///
+/// It's a partial view of the tree, in which the paths from the root to each
+/// of the keys are expanded, while subtrees branching off these paths are only
+/// represented by their hashes. This way, siblings shared by multiple paths are
+/// only included once, and so are the nodes near the root.
+#[cw_serde]
+pub enum MultiProof {
+    /// An internal node on the path of at least one of the keys.
+    Internal {
+        left:  Box<MultiProof>,
+        right: Box<MultiProof>,
+    },
+    /// A subtree that doesn't contain any of the keys, represented by its hash,
+    /// or `None` if the subtree is empty. An empty subtree on the path of some
+    /// keys proves these keys don't exist.
+    Sibling(Option<Hash>),
+    /// A leaf on the path of some keys, none of which matches the leaf's key.
+    /// This proves these keys don't exist.
+    Leaf {
+        key_hash:   Hash,
+        value_hash: Hash,
+    },
+    /// The leaf of a key that exists. Its key and value hashes are known to the
+    /// verifier so don't need to be included.
+    Member,
+}
+
 pub fn verify_proof(
     root_hash:  &Hash,
     key_hash:   &Hash,
@@ -141,6 +179,112 @@ pub fn verify_non_membership_proof(
     compute_and_compare_root_hash(root_hash, &bitarray, &proof.sibling_hashes, hash)
 }
 
+/// Verify a multi proof. `items` maps the key hashes to be proved to their
+/// value hashes, `None` meaning the key is to be proved not to exist.
+pub fn verify_multi_proof(
+    root_hash: &Hash,
+    items:     &BTreeMap<Hash, Option<Hash>>,
+    proof:     &MultiProof,
+) -> Result<(), ProofError> {
+    // the key hashes are sorted, so at each internal node, those that go left
+    // (bit being 0) always come before those that go right (bit being 1)
+    let items = items.iter().map(|(k, v)| (k, v.as_ref())).collect::<Vec<_>>();
+    let hash = compute_multi_proof_hash(proof, 0, &items)?;
+
+    if hash.as_ref() != Some(root_hash) {
+        return Err(ProofError::RootHashMismatch {
+            // the hash can only be None if the proof claims the tree is empty
+            computed: hash.unwrap_or(Hash::ZERO),
+            actual:   root_hash.clone(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Compute the hash of the subtree at the given depth, given the items whose
+/// paths go through it.
+fn compute_multi_proof_hash(
+    proof: &MultiProof,
+    depth: usize,
+    items: &[(&Hash, Option<&Hash>)],
+) -> Result<Option<Hash>, ProofError> {
+    match proof {
+        MultiProof::Internal { left, right } => {
+            if depth >= BitArray::MAX_BIT_LENGTH {
+                return Err(ProofError::ProofTooDeep);
+            }
+            let split = items.partition_point(|(key_hash, _)| {
+                BitArray::from_bytes(key_hash).bit_at_index(depth) == 0
+            });
+            let (left_items, right_items) = items.split_at(split);
+            let left_hash = compute_multi_proof_hash(left, depth + 1, left_items)?;
+            let right_hash = compute_multi_proof_hash(right, depth + 1, right_items)?;
+            Ok(Some(hash_internal_node(left_hash.as_ref(), right_hash.as_ref())))
+        },
+        // a non-empty subtree tells us nothing about the keys in it. an empty
+        // one proves that all of them don't exist.
+        MultiProof::Sibling(hash) => {
+            for (key_hash, value_hash) in items {
+                if hash.is_some() {
+                    return Err(ProofError::KeyNotExpanded {
+                        key_hash: (*key_hash).clone(),
+                    });
+                }
+                if value_hash.is_some() {
+                    return Err(ProofError::IncorrectProofType {
+                        expect: "membership",
+                        actual: "non-membership",
+                    });
+                }
+            }
+            Ok(hash.clone())
+        },
+        // a leaf proves that all keys whose paths go through it, other than the
+        // leaf's own key, don't exist
+        MultiProof::Leaf { key_hash, value_hash } => {
+            for (item_key_hash, item_value_hash) in items {
+                if item_value_hash.is_some() {
+                    return Err(ProofError::IncorrectProofType {
+                        expect: "membership",
+                        actual: "non-membership",
+                    });
+                }
+                if *item_key_hash == key_hash {
+                    return Err(ProofError::IncorrectProofType {
+                        expect: "non-membership",
+                        actual: "membership",
+                    });
+                }
+            }
+            Ok(Some(hash_leaf_node(key_hash, value_hash)))
+        },
+        // exactly one of the keys whose paths go through this leaf must exist,
+        // which is the leaf's own key. the others are proved to not exist.
+        MultiProof::Member => {
+            let mut member = None;
+            for (key_hash, value_hash) in items {
+                if let Some(value_hash) = value_hash {
+                    if member.is_some() {
+                        return Err(ProofError::IncorrectProofType {
+                            expect: "membership",
+                            actual: "non-membership",
+                        });
+                    }
+                    member = Some((*key_hash, *value_hash));
+                }
+            }
+            let Some((key_hash, value_hash)) = member else {
+                return Err(ProofError::IncorrectProofType {
+                    expect: "non-membership",
+                    actual: "membership",
+                });
+            };
+            Ok(Some(hash_leaf_node(key_hash, value_hash)))
+        },
+    }
+}
+
 fn compute_and_compare_root_hash(
     root_hash:      &Hash,
     bitarray:       &BitArray,
@@ -1,9 +1,9 @@
 use {
     crate::{
-        BitArray, Child, InternalNode, LeafNode, MembershipProof, Node, NonMembershipProof, Proof,
-        ProofNode,
+        BitArray, Child, InternalNode, LeafNode, MembershipProof, MultiProof, Node,
+        NonMembershipProof, Proof, ProofNode,
     },
-    cw_std::{hash, Batch, Hash, Map, Op, Order, Set, StdResult, Storage},
+    cw_std::{hash, Batch, Bound, Hash, Map, Op, Order, Set, StdResult, Storage},
     tracing::trace,
 };
 
@@ -14,6 +14,10 @@ pub const DEFAULT_ORPHAN_NAMESPACE: &str = "o";
 /// The bit path of the root node, which is just empty
 pub const ROOT_BITS: &BitArray = &BitArray::new_empty();
 
+/// When pruning, orphans are loaded and deleted in batches of this size, so
+/// that we don't need to hold all of them in memory at once.
+pub const PRUNE_BATCH_SIZE: usize = 1000;
+
 /// Describes what happens after applying ops (a slice of `HashedPair`) at a
 /// node and its subtree.
 #[derive(Debug)]
@@ -210,11 +214,13 @@ impl<'a> MerkleTree<'a> {
             // left child is a leaf, right child is deleted.
             // delete the current internal node and move left child up.
             (Outcome::Updated(left) | Outcome::Unchanged(Some(left)), Outcome::Deleted | Outcome::Unchanged(None)) if left.is_leaf() => {
+                self.mark_moved_child_as_orphaned(store, new_version, bits, true, internal_node.left_child.as_ref())?;
                 Ok(Outcome::Updated(left))
             },
             // left child is deleted, right child is a leaf.
             // delete the current internal node and move right child up.
             (Outcome::Deleted | Outcome::Unchanged(None), Outcome::Updated(right) | Outcome::Unchanged(Some(right))) if right.is_leaf() => {
+                self.mark_moved_child_as_orphaned(store, new_version, bits, false, internal_node.right_child.as_ref())?;
                 Ok(Outcome::Updated(right))
             },
             // at least one child is updated and the path can't be collapsed.
@@ -475,11 +481,145 @@ impl<'a> MerkleTree<'a> {
         }
     }
 
+    /// Generate a single proof for the membership or non-membership of multiple
+    /// keys. Compared to proving the keys one by one, siblings shared by their
+    /// paths are only included once.
+    pub fn prove_multi(
+        &self,
+        store:      &dyn Storage,
+        key_hashes: &[Hash],
+        version:    u64,
+    ) -> StdResult<MultiProof> {
+        // sort the key hashes, such that at each internal node, those that go
+        // left come before those that go right
+        let mut key_hashes = key_hashes.to_vec();
+        key_hashes.sort();
+        key_hashes.dedup();
+
+        let root_node = self.nodes.load(store, (version, ROOT_BITS))?;
+
+        self.prove_multi_at(store, root_node, ROOT_BITS.clone(), &key_hashes)
+    }
+
+    fn prove_multi_at(
+        &self,
+        store:      &dyn Storage,
+        node:       Node,
+        bits:       BitArray,
+        key_hashes: &[Hash],
+    ) -> StdResult<MultiProof> {
+        match node {
+            Node::Leaf(leaf) => {
+                if key_hashes.contains(&leaf.key_hash) {
+                    Ok(MultiProof::Member)
+                } else {
+                    Ok(MultiProof::Leaf {
+                        key_hash:   leaf.key_hash,
+                        value_hash: leaf.value_hash,
+                    })
+                }
+            },
+            Node::Internal(InternalNode { left_child, right_child }) => {
+                let split = key_hashes.partition_point(|key_hash| {
+                    BitArray::from_bytes(key_hash).bit_at_index(bits.num_bits) == 0
+                });
+                let (left_key_hashes, right_key_hashes) = key_hashes.split_at(split);
+                let left = self.prove_multi_child(store, &bits, 0, left_child, left_key_hashes)?;
+                let right =
+                    self.prove_multi_child(store, &bits, 1, right_child, right_key_hashes)?;
+                Ok(MultiProof::Internal {
+                    left:  Box::new(left),
+                    right: Box::new(right),
+                })
+            },
+        }
+    }
+
+    fn prove_multi_child(
+        &self,
+        store:       &dyn Storage,
+        parent_bits: &BitArray,
+        bit:         u8,
+        child:       Option<Child>,
+        key_hashes:  &[Hash],
+    ) -> StdResult<MultiProof> {
+        match child {
+            // the child is on the path of some keys. expand it
+            Some(child) if !key_hashes.is_empty() => {
+                let mut bits = parent_bits.clone();
+                bits.push(bit);
+                let node = self.nodes.load(store, (child.version, &bits))?;
+                self.prove_multi_at(store, node, bits, key_hashes)
+            },
+            // the child isn't on the path of any key, or it's empty. either way
+            // its hash is all we need
+            _ => Ok(MultiProof::Sibling(hash_of(child))),
+        }
+    }
+
     /// Delete nodes that are no longer part of the tree as of `up_to_version`.
     /// If no `up_to_version` is provided then delete all orphans.
-    pub fn prune(&self, _store: &mut dyn Storage, _up_to_version: Option<u64>) -> StdResult<()> {
-        // we should first implement a `range_remove` method on Storage trait
-        todo!()
+    ///
+    /// After this, the tree can no longer be queried or proved at versions
+    /// older than `up_to_version`.
+    pub fn prune(&self, store: &mut dyn Storage, up_to_version: Option<u64>) -> StdResult<()> {
+        self.prune_between(store, None, up_to_version)
+    }
+
+    /// Delete nodes that were orphaned after `after_version` and no later than
+    /// `up_to_version`, except those that are still part of the tree as of
+    /// `after_version`.
+    ///
+    /// In other words, the tree remains intact at `after_version` and at
+    /// `up_to_version` and later, while versions in between are pruned. This
+    /// is used to keep periodic snapshots of the tree. If `after_version` is
+    /// `None`, this is equivalent to `prune`.
+    pub fn prune_between(
+        &self,
+        store:         &mut dyn Storage,
+        after_version: Option<u64>,
+        up_to_version: Option<u64>,
+    ) -> StdResult<()> {
+        // no node can have been orphaned after the max version
+        if after_version == Some(u64::MAX) {
+            return Ok(());
+        }
+
+        // orphans are keyed by (orphaned_since_version, version, bits), with
+        // ROOT_BITS being the smallest bits, so (v, 0, ROOT_BITS) is smaller
+        // than any orphan whose orphaned_since_version is v.
+        let min = after_version.and_then(|v| v.checked_add(1));
+        let max = up_to_version.and_then(|v| v.checked_add(1));
+
+        loop {
+            // collect a batch of orphans first, because we can't mutate the
+            // store while iterating it
+            let batch = self
+                .orphans
+                .range(
+                    store,
+                    min.map(|v| Bound::Inclusive((v, 0, ROOT_BITS))),
+                    max.map(|v| Bound::Exclusive((v, 0, ROOT_BITS))),
+                    Order::Ascending,
+                )
+                .take(PRUNE_BATCH_SIZE)
+                .collect::<StdResult<Vec<_>>>()?;
+
+            for (orphaned_since_version, version, bits) in &batch {
+                // nodes that are still part of the tree as of `after_version`
+                // are kept; only their orphan records are removed
+                if after_version.map_or(true, |after| *version > after) {
+                    trace!(orphaned_since_version, version, ?bits, "Pruning node");
+                    self.nodes.remove(store, (*version, bits));
+                }
+
+                self.orphans.remove(store, (*orphaned_since_version, *version, bits));
+            }
+
+            if batch.len() < PRUNE_BATCH_SIZE {
+                break Ok(());
+            }
+        }
     }
 
     #[inline]
@@ -515,6 +655,30 @@ impl<'a> MerkleTree<'a> {
         self.nodes.save(store, (version, bits), node)
     }
 
+    /// When a path is collapsed, the leaf child that is moved up is no longer
+    /// referenced at its original position, so mark it as orphaned. If the
+    /// child was updated in this version, it's the node that has just been
+    /// written at the new version that becomes orphaned; otherwise it's the
+    /// node of the child's original version.
+    #[inline]
+    fn mark_moved_child_as_orphaned(
+        &self,
+        store:       &mut dyn Storage,
+        new_version: u64,
+        parent_bits: &BitArray,
+        is_left:     bool,
+        child:       Option<&Child>,
+    ) -> StdResult<()> {
+        let child_bits = parent_bits.extend_one_bit(is_left);
+        if self.nodes.has(store, (new_version, &child_bits)) {
+            self.mark_node_as_orphaned(store, new_version, new_version, &child_bits)
+        } else if let Some(child) = child {
+            self.mark_node_as_orphaned(store, new_version, child.version, &child_bits)
+        } else {
+            Ok(())
+        }
+    }
+
     #[inline]
     fn mark_node_as_orphaned(
         &self,
@@ -684,8 +848,8 @@ fn into_child(version: u64, outcome: Outcome) -> Option<Child> {
 #[cfg(test)]
 mod tests {
     use {
-        super::*, cw_std::MockStorage, hex_literal::hex, test_case::test_case,
-        tracing_test::traced_test,
+        super::*, cw_std::MockStorage, hex_literal::hex, std::collections::BTreeMap,
+        test_case::test_case, tracing_test::traced_test,
     };
 
     const TREE: MerkleTree = MerkleTree::new_default();
@@ -750,6 +914,46 @@ mod tests {
         assert_eq!(new_root_hash, Some(Hash::from_slice(hex!("b3e4002b2d95d57ab44bbf64c8cfb04904c02fb2df9c859a75d82b02fd087dbf"))));
     }
 
+    // collapse the path as in the previous test, then prune up to version 2.
+    // the nodes of version 1 that are no longer part of the tree should be
+    // deleted, while the tree at version 2 can still be proved.
+    #[test]
+    #[traced_test]
+    fn pruning() {
+        let (mut store, _) = build_test_case().unwrap();
+        let root_hash = TREE.apply_raw(&mut store, 1, 2, &Batch::from([
+            (b"r".to_vec(), Op::Delete),
+            (b"m".to_vec(), Op::Delete),
+        ]))
+        .unwrap()
+        .unwrap();
+
+        TREE.prune(&mut store, Some(2)).unwrap();
+
+        // only the three nodes of the version 2 tree should remain
+        let nodes = TREE
+            .nodes
+            .keys(&store, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(nodes, [
+            (1, ROOT_BITS.extend_one_bit(false)),
+            (2, ROOT_BITS.clone()),
+            (2, ROOT_BITS.extend_one_bit(true)),
+        ]);
+
+        // all orphans should have been deleted
+        assert!(TREE.orphans.is_empty(&store));
+
+        // the old version can no longer be proved, but the new one can
+        assert!(TREE.root_hash(&store, 1).unwrap().is_none());
+        for (key, value) in [("L", "fuzz"), ("a", "buzz")] {
+            let key_hash = hash(key.as_bytes());
+            let proof = TREE.prove(&store, &key_hash, 2).unwrap();
+            crate::verify_proof(&root_hash, &key_hash, Some(&hash(value.as_bytes())), &proof).unwrap();
+        }
+    }
+
     // try deleting every single node. the function should return None as the
     // new root hash. see that nodes have been properly marked as orphaned.
     #[test]
@@ -888,4 +1092,49 @@ mod tests {
         let (store, _) = build_test_case().unwrap();
         assert_eq!(TREE.prove(&store, &hash(key.as_bytes()), 1).unwrap(), proof);
     }
+
+    // prove r, m (members) and b, o (non-members) at once. the siblings shared
+    // by their paths, e.g. node 1, should only be included once.
+    #[test]
+    fn proving_multi() {
+        let (store, _) = build_test_case().unwrap();
+        let key_hashes = ["r", "m", "b", "o"].map(|key| hash(key.as_bytes()));
+        let proof = TREE.prove_multi(&store, &key_hashes, 1).unwrap();
+
+        let internal = |left, right| MultiProof::Internal {
+            left:  Box::new(left),
+            right: Box::new(right),
+        };
+        assert_eq!(
+            proof,
+            internal(
+                internal(
+                    MultiProof::Sibling(None),
+                    internal(
+                        MultiProof::Member,
+                        internal(
+                            MultiProof::Member,
+                            MultiProof::Sibling(Some(HASH_0111)),
+                        ),
+                    ),
+                ),
+                MultiProof::Sibling(Some(HASH_1)),
+            ),
+        );
+
+        let mut items = [("r", Some("foo")), ("m", Some("bar")), ("b", None), ("o", None)]
+            .into_iter()
+            .map(|(key, value)| (hash(key.as_bytes()), value.map(|v| hash(v.as_bytes()))))
+            .collect::<BTreeMap<_, _>>();
+        assert!(crate::verify_multi_proof(&HASH_ROOT, &items, &proof).is_ok());
+
+        // claiming a non-member to exist should fail
+        items.insert(hash(b"b"), Some(hash(b"fake")));
+        assert!(crate::verify_multi_proof(&HASH_ROOT, &items, &proof).is_err());
+
+        // proving a key whose path isn't expanded should fail
+        items.insert(hash(b"b"), None);
+        items.insert(hash(b"a"), Some(hash(b"buzz")));
+        assert!(crate::verify_multi_proof(&HASH_ROOT, &items, &proof).is_err());
+    }
 }
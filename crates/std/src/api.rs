@@ -0,0 +1,63 @@
+use crate::StdResult;
+
+/// Methods the host provides to contracts other than storage and querying:
+/// debug printing, signature verification, and hashing.
+///
+/// In Wasm, this is implemented by `ExternalApi`, which calls the host's
+/// imports. For unit testing contracts natively, use `MockApi` instead, which
+/// implements the same methods using `cw_crypto` directly.
+///
+/// The verification methods return `StdError::VerificationFailed` if the
+/// signature is invalid for any reason, including malformed inputs.
+pub trait Api {
+    /// Print a debug message to the client's CLI output.
+    fn debug(&self, msg: &str);
+
+    /// NOTE: This function takes the hash of the message, not the prehash.
+    fn secp256k1_verify(&self, msg_hash: &[u8], sig: &[u8], pk: &[u8]) -> StdResult<()>;
+
+    /// NOTE: This function takes the hash of the message, not the prehash.
+    fn secp256r1_verify(&self, msg_hash: &[u8], sig: &[u8], pk: &[u8]) -> StdResult<()>;
+
+    /// Recover the secp256k1 public key that produced the signature, in
+    /// compressed or uncompressed SEC1 encoding as specified.
+    ///
+    /// NOTE: This function takes the hash of the message, not the prehash.
+    fn secp256k1_pubkey_recover(
+        &self,
+        msg_hash:    &[u8],
+        sig:         &[u8],
+        recovery_id: u8,
+        compressed:  bool,
+    ) -> StdResult<Vec<u8>>;
+
+    /// NOTE: Unlike the secp256k1 and secp256r1 functions, this function takes
+    /// the message itself, not its hash.
+    fn ed25519_verify(&self, msg: &[u8], sig: &[u8], pk: &[u8]) -> StdResult<()>;
+
+    /// Verify multiple Ed25519 signatures at once. The i-th signature is
+    /// verified against the i-th message and the i-th public key.
+    /// Succeeds if and only if all signatures are valid.
+    fn ed25519_batch_verify(&self, msgs: &[&[u8]], sigs: &[&[u8]], pks: &[&[u8]]) -> StdResult<()>;
+
+    /// Verify a BLS12-381 aggregate signature, with public keys in G1 and the
+    /// signature in G2. If only one message is given, it's considered to be
+    /// signed by all the public keys; otherwise, the i-th message is considered
+    /// to be signed by the i-th public key.
+    fn bls12_381_aggregate_verify(
+        &self,
+        msgs: &[&[u8]],
+        sig:  &[u8],
+        pks:  &[&[u8]],
+    ) -> StdResult<()>;
+
+    fn sha2_256(&self, data: &[u8]) -> [u8; 32];
+
+    fn sha3_256(&self, data: &[u8]) -> [u8; 32];
+
+    /// NOTE: This is the original Keccak-256 as used by Ethereum, which differs
+    /// from the standardized SHA3-256 in its padding.
+    fn keccak256(&self, data: &[u8]) -> [u8; 32];
+
+    fn blake3(&self, data: &[u8]) -> [u8; 32];
+}
@@ -1,10 +1,36 @@
 use {
+    crate::GenericError,
     data_encoding::BASE64,
     hex::FromHexError,
-    std::{any::type_name, array::TryFromSliceError, num::ParseIntError},
+    std::{any::type_name, array::TryFromSliceError, fmt::Display, num::ParseIntError},
     thiserror::Error,
 };
 
+/// An error that can be identified by a codespace and a numeric code, such
+/// that clients can tell the kind of an error without matching its message.
+///
+/// A code is only unique within its codespace. Once assigned, a code must not
+/// be changed or reused for a different kind of error. Zero is reserved for
+/// success, following the ABCI convention.
+pub trait CodedError: Display {
+    fn codespace(&self) -> &str;
+
+    fn code(&self) -> u32;
+}
+
+impl<E> CodedError for &E
+where
+    E: CodedError + ?Sized,
+{
+    fn codespace(&self) -> &str {
+        (**self).codespace()
+    }
+
+    fn code(&self) -> u32 {
+        (**self).code()
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum StdError {
     #[error(transparent)]
@@ -106,6 +132,12 @@ pub enum StdError {
         ty:     &'static str,
         reason: String,
     },
+
+    /// An error returned from the other side of the FFI boundary, i.e. by a
+    /// contract to the host or by the host to a contract, which keeps its
+    /// original codespace and code.
+    #[error("{0}")]
+    Forwarded(GenericError),
 }
 
 impl StdError {
@@ -202,4 +234,215 @@ impl StdError {
     }
 }
 
+impl CodedError for StdError {
+    fn codespace(&self) -> &str {
+        match self {
+            Self::Forwarded(err) => &err.codespace,
+            _ => StdErrorCode::CODESPACE,
+        }
+    }
+
+    fn code(&self) -> u32 {
+        let code = match self {
+            Self::FromHex(_) => StdErrorCode::FromHex,
+            Self::ParseInt(_) => StdErrorCode::ParseInt,
+            Self::TryFromSlice(_) => StdErrorCode::TryFromSlice,
+            Self::Generic(_) => StdErrorCode::Generic,
+            Self::VerificationFailed => StdErrorCode::VerificationFailed,
+            Self::ParseCoins { .. } => StdErrorCode::ParseCoins,
+            Self::Payment { .. } => StdErrorCode::Payment,
+            Self::DenomNotFound { .. } => StdErrorCode::DenomNotFound,
+            Self::DataNotFound { .. } => StdErrorCode::DataNotFound,
+            Self::OverflowAdd { .. } => StdErrorCode::OverflowAdd,
+            Self::OverflowSub { .. } => StdErrorCode::OverflowSub,
+            Self::OverflowMul { .. } => StdErrorCode::OverflowMul,
+            Self::OverflowPow { .. } => StdErrorCode::OverflowPow,
+            Self::OverflowShl { .. } => StdErrorCode::OverflowShl,
+            Self::OverflowShr { .. } => StdErrorCode::OverflowShr,
+            Self::DivisionByZero { .. } => StdErrorCode::DivisionByZero,
+            Self::RemainerByZero { .. } => StdErrorCode::RemainderByZero,
+            Self::Serialize { .. } => StdErrorCode::Serialize,
+            Self::Deserialize { .. } => StdErrorCode::Deserialize,
+            Self::Forwarded(err) => return err.code,
+        };
+        code as u32
+    }
+}
+
+/// The codes of `StdError` variants, in the `std` codespace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum StdErrorCode {
+    FromHex            = 1,
+    ParseInt           = 2,
+    TryFromSlice       = 3,
+    Generic            = 4,
+    VerificationFailed = 5,
+    ParseCoins         = 6,
+    Payment            = 7,
+    DenomNotFound      = 8,
+    DataNotFound       = 9,
+    OverflowAdd        = 10,
+    OverflowSub        = 11,
+    OverflowMul        = 12,
+    OverflowPow        = 13,
+    OverflowShl        = 14,
+    OverflowShr        = 15,
+    DivisionByZero     = 16,
+    RemainderByZero    = 17,
+    Serialize          = 18,
+    Deserialize        = 19,
+}
+
+impl StdErrorCode {
+    pub const CODESPACE: &'static str = "std";
+
+    /// Find the variant corresponding to the code. Return `None` if the code
+    /// isn't defined.
+    pub fn from_code(code: u32) -> Option<Self> {
+        let code = match code {
+            1 => Self::FromHex,
+            2 => Self::ParseInt,
+            3 => Self::TryFromSlice,
+            4 => Self::Generic,
+            5 => Self::VerificationFailed,
+            6 => Self::ParseCoins,
+            7 => Self::Payment,
+            8 => Self::DenomNotFound,
+            9 => Self::DataNotFound,
+            10 => Self::OverflowAdd,
+            11 => Self::OverflowSub,
+            12 => Self::OverflowMul,
+            13 => Self::OverflowPow,
+            14 => Self::OverflowShl,
+            15 => Self::OverflowShr,
+            16 => Self::DivisionByZero,
+            17 => Self::RemainderByZero,
+            18 => Self::Serialize,
+            19 => Self::Deserialize,
+            _ => return None,
+        };
+        Some(code)
+    }
+}
+
+/// The codes of `cw_app::AppError` variants, in the `app` codespace.
+///
+/// This and `VmErrorCode` are defined here instead of in the host crates, such
+/// that clients can decode errors without depending on them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum AppErrorCode {
+    Db                   = 1,
+    ProofNotSupported    = 2,
+    TxQueryNotSupported  = 3,
+    IncorrectBlockHeight = 4,
+    OwnerNotSet          = 5,
+    NotOwner             = 6,
+    AdminNotSet          = 7,
+    NotAdmin             = 8,
+    CodeExists           = 9,
+    AccountExists        = 10,
+    UnknownQueryPath     = 11,
+    QueryDepthExceeded   = 12,
+    QueryGasExceeded     = 13,
+    MaxDepthExceeded     = 14,
+    HeightPruned         = 15,
+    HeightNotReached     = 16,
+}
+
+impl AppErrorCode {
+    pub const CODESPACE: &'static str = "app";
+
+    /// Find the variant corresponding to the code. Return `None` if the code
+    /// isn't defined.
+    pub fn from_code(code: u32) -> Option<Self> {
+        let code = match code {
+            1 => Self::Db,
+            2 => Self::ProofNotSupported,
+            3 => Self::TxQueryNotSupported,
+            4 => Self::IncorrectBlockHeight,
+            5 => Self::OwnerNotSet,
+            6 => Self::NotOwner,
+            7 => Self::AdminNotSet,
+            8 => Self::NotAdmin,
+            9 => Self::CodeExists,
+            10 => Self::AccountExists,
+            11 => Self::UnknownQueryPath,
+            12 => Self::QueryDepthExceeded,
+            13 => Self::QueryGasExceeded,
+            14 => Self::MaxDepthExceeded,
+            15 => Self::HeightPruned,
+            16 => Self::HeightNotReached,
+            _ => return None,
+        };
+        Some(code)
+    }
+}
+
+/// The codes of `cw_vm::VmError` variants, in the `vm` codespace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum VmErrorCode {
+    FromUtf8              = 1,
+    Io                    = 2,
+    Export                = 3,
+    MemoryAccess          = 4,
+    Runtime               = 5,
+    Instantiation         = 6,
+    MemoryNotSet          = 7,
+    StoreNotSet           = 8,
+    WasmerInstanceNotSet  = 9,
+    FailedReadLock        = 10,
+    FailedWriteLock       = 11,
+    FailedLockModuleCache = 12,
+    IteratorNotFound      = 13,
+    RegionTooSmall        = 14,
+    ReturnCount           = 15,
+    ReturnType            = 16,
+    OutOfGas              = 17,
+    InvalidWasm           = 18,
+    WasmTooLarge          = 19,
+    TooManyFunctions      = 20,
+    MissingExport         = 21,
+    UnsupportedImport     = 22,
+    FloatsNotAllowed      = 23,
+}
+
+impl VmErrorCode {
+    pub const CODESPACE: &'static str = "vm";
+
+    /// Find the variant corresponding to the code. Return `None` if the code
+    /// isn't defined.
+    pub fn from_code(code: u32) -> Option<Self> {
+        let code = match code {
+            1 => Self::FromUtf8,
+            2 => Self::Io,
+            3 => Self::Export,
+            4 => Self::MemoryAccess,
+            5 => Self::Runtime,
+            6 => Self::Instantiation,
+            7 => Self::MemoryNotSet,
+            8 => Self::StoreNotSet,
+            9 => Self::WasmerInstanceNotSet,
+            10 => Self::FailedReadLock,
+            11 => Self::FailedWriteLock,
+            12 => Self::FailedLockModuleCache,
+            13 => Self::IteratorNotFound,
+            14 => Self::RegionTooSmall,
+            15 => Self::ReturnCount,
+            16 => Self::ReturnType,
+            17 => Self::OutOfGas,
+            18 => Self::InvalidWasm,
+            19 => Self::WasmTooLarge,
+            20 => Self::TooManyFunctions,
+            21 => Self::MissingExport,
+            22 => Self::UnsupportedImport,
+            23 => Self::FloatsNotAllowed,
+            _ => return None,
+        };
+        Some(code)
+    }
+}
+
 pub type StdResult<T> = std::result::Result<T, StdError>;
@@ -1,30 +1,41 @@
 // -------------------------------- all targets --------------------------------
 
+mod api;
 mod error;
+mod querier;
 mod serde;
 mod storage;
 mod testing;
 mod types;
 
 pub use crate::{
-    error::{StdError, StdResult},
-    serde::{from_json, to_json},
+    api::Api,
+    error::{AppErrorCode, CodedError, StdError, StdErrorCode, StdResult, VmErrorCode},
+    querier::Querier,
+    serde::{from_json, to_canonical_json, to_json},
     storage::{
         concat, encode_length, extend_one_byte, increment_last_byte, nested_namespaces_with_key,
         split_one_key, trim, Batch, Bound, Item, Map, MapKey, Op, Order, Path, PathBuf, Prefix,
         RawBound, RawKey, Record, Set, Storage,
     },
-    testing::MockStorage,
+    testing::{MockQuerier, MockStorage},
     types::{
-        hash, Account, AccountResponse, Addr, Attribute, BankQuery, BankQueryResponse, BeforeTxCtx,
-        Binary, BlockInfo, Coin, CoinRef, Coins, CoinsIntoIter, CoinsIter, Config, Context, Empty,
-        Event, ExecuteCtx, GenericResult, GenesisState, Hash, InfoResponse, InstantiateCtx,
-        Message, MigrateCtx, QueryCtx, QueryRequest, QueryResponse, ReceiveCtx, ReplyCtx, ReplyOn,
-        Response, SubMessage, Timestamp, TransferCtx, TransferMsg, Tx, Uint128, Uint64,
-        WasmRawResponse, WasmSmartResponse, GENESIS_BLOCK_HASH, GENESIS_SENDER,
+        hash, Account, AccountResponse, Addr, AfterBlockCtx, Attribute, BankQuery,
+        BankQueryResponse, BeforeBlockCtx, BeforeTxCtx, Binary, BlockInfo, Coin, CoinRef, Coins,
+        CoinsIntoIter, CoinsIter, Config, Context, Cronjob, Empty, Event, ExecuteCtx, GenericError,
+        GenericResult, GenesisState, Hash, InfoResponse, InstantiateCtx, Message, MigrateCtx,
+        QueryCtx, QueryRequest, QueryResponse, ReceiveCtx, ReplyCtx, ReplyOn, Response, SignDoc,
+        SimulateResponse, SubMessage, TaxmanCtx, Timestamp, TransferCtx, TransferMsg, Tx, TxOutcome,
+        TxResponse, Uint128, Uint64, WasmRawResponse, WasmSmartResponse, ACCOUNTS, CODES,
+        CONTRACT_NAMESPACE, GENESIS_BLOCK_HASH, GENESIS_SENDER, SIGN_DOC_DOMAIN,
     },
 };
 
+// -------------------------- non-wasm32 targets only --------------------------
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use crate::testing::MockApi;
+
 // ---------------------------- wasm32 target only -----------------------------
 
 // #[cfg(target_arch = "wasm32")]
@@ -32,8 +43,9 @@ mod wasm;
 
 // #[cfg(target_arch = "wasm32")]
 pub use crate::wasm::{
-    do_before_tx, do_execute, do_instantiate, do_migrate, do_query, do_query_bank, do_receive,
-    do_reply, do_transfer, ExternalIterator, ExternalStorage, Region,
+    do_after_block, do_before_block, do_before_tx, do_execute, do_finalize_fee, do_instantiate,
+    do_migrate, do_query, do_query_bank, do_receive, do_reply, do_transfer, do_withhold_fee,
+    ExternalApi, ExternalIterator, ExternalQuerier, ExternalStorage, Region,
 };
 
 // -------------------------------- re-exports ---------------------------------
@@ -0,0 +1,14 @@
+use crate::{QueryRequest, QueryResponse, StdResult};
+
+/// An object that can answer queries about the chain's state on behalf of a
+/// contract, e.g. its config, token balances, or other contracts' states.
+///
+/// In Wasm, this is implemented by `ExternalQuerier`, which calls the host's
+/// `query_chain` import. When running contracts natively (e.g. in tests), the
+/// host provides an implementation that processes the query directly.
+///
+/// Contracts typically don't call this trait directly, but rather the typed
+/// methods on the contexts, such as `query_info` and `query_wasm_smart`.
+pub trait Querier {
+    fn query_chain(&self, req: &QueryRequest) -> StdResult<QueryResponse>;
+}
@@ -1,6 +1,7 @@
 use {
     crate::{Binary, StdError, StdResult},
     serde::{de::DeserializeOwned, ser::Serialize},
+    std::collections::{btree_map::Entry, BTreeMap},
 };
 
 pub fn from_json<T>(bytes: impl AsRef<[u8]>) -> StdResult<T>
@@ -16,3 +17,254 @@ where
 {
     serde_json_wasm::to_vec(data).map(Into::into).map_err(StdError::serialize::<T>)
 }
+
+/// Serialize the data into a canonical JSON encoding, meaning the same data is
+/// always encoded into exactly the same bytes, such that the output can be
+/// reproduced by clients in other languages, e.g. for signing. Specifically:
+///
+/// - there is no whitespace between tokens;
+/// - the keys of every object are sorted ascendingly by the UTF-8 bytes of the
+///   unescaped keys, i.e. the keys as strings, not as they appear in JSON;
+/// - strings and numbers are encoded the same way as in `to_json`.
+pub fn to_canonical_json<T>(data: &T) -> StdResult<Binary>
+where
+    T: Serialize,
+{
+    let json = to_json(data)?;
+
+    let mut parser = JsonParser {
+        bytes: &json,
+        pos:   0,
+    };
+    let value = parser.parse_value().map_err(StdError::serialize::<T>)?;
+    if !parser.is_finished() {
+        return Err(StdError::serialize::<T>("trailing bytes after JSON value"));
+    }
+
+    let mut canonical = Vec::with_capacity(json.len());
+    value.write_to(&mut canonical);
+
+    Ok(canonical.into())
+}
+
+// ----------------------------- canonicalization ------------------------------
+
+// for the purpose of canonicalization, we don't need to interpret the scalar
+// values (strings, numbers, booleans, null), only to find where they start and
+// end, so that we can copy them over as-is.
+// object keys however are unescaped for sorting, as the order of the escaped
+// forms differs from that of the strings themselves. e.g. `"a"` sorts after
+// `"a b"` because `"` is greater than a space. the escaped form is kept for
+// output.
+enum JsonValue<'a> {
+    Scalar(&'a [u8]),
+    Array(Vec<JsonValue<'a>>),
+    Object(BTreeMap<String, (&'a [u8], JsonValue<'a>)>),
+}
+
+impl JsonValue<'_> {
+    fn write_to(&self, out: &mut Vec<u8>) {
+        match self {
+            JsonValue::Scalar(bytes) => {
+                out.extend_from_slice(bytes);
+            },
+            JsonValue::Array(values) => {
+                out.push(b'[');
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        out.push(b',');
+                    }
+                    value.write_to(out);
+                }
+                out.push(b']');
+            },
+            JsonValue::Object(entries) => {
+                out.push(b'{');
+                for (i, (key, value)) in entries.values().enumerate() {
+                    if i > 0 {
+                        out.push(b',');
+                    }
+                    out.extend_from_slice(key);
+                    out.push(b':');
+                    value.write_to(out);
+                }
+                out.push(b'}');
+            },
+        }
+    }
+}
+
+struct JsonParser<'a> {
+    bytes: &'a [u8],
+    pos:   usize,
+}
+
+impl<'a> JsonParser<'a> {
+    fn is_finished(&mut self) -> bool {
+        self.skip_whitespace();
+        self.pos == self.bytes.len()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.bytes.get(self.pos), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Result<u8, String> {
+        self.skip_whitespace();
+        self.bytes.get(self.pos).copied().ok_or_else(|| "unexpected end of JSON".to_string())
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), String> {
+        if self.peek()? != byte {
+            return Err(format!("expecting `{}` at position {}", byte as char, self.pos));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn parse_value(&mut self) -> Result<JsonValue<'a>, String> {
+        match self.peek()? {
+            b'{' => self.parse_object(),
+            b'[' => self.parse_array(),
+            b'"' => self.parse_string().map(JsonValue::Scalar),
+            _ => self.parse_literal().map(JsonValue::Scalar),
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<JsonValue<'a>, String> {
+        let mut entries = BTreeMap::new();
+        self.expect(b'{')?;
+        if self.peek()? == b'}' {
+            self.pos += 1;
+            return Ok(JsonValue::Object(entries));
+        }
+        loop {
+            let key = self.parse_string()?;
+            let unescaped = serde_json_wasm::from_slice::<String>(key)
+                .map_err(|err| format!("invalid key in JSON object: {err}"))?;
+            self.expect(b':')?;
+            let value = self.parse_value()?;
+            match entries.entry(unescaped) {
+                Entry::Vacant(entry) => entry.insert((key, value)),
+                Entry::Occupied(_) => return Err("duplicate key in JSON object".to_string()),
+            };
+            match self.peek()? {
+                b',' => self.pos += 1,
+                b'}' => {
+                    self.pos += 1;
+                    return Ok(JsonValue::Object(entries));
+                },
+                _ => return Err(format!("expecting `,` or `}}` at position {}", self.pos)),
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<JsonValue<'a>, String> {
+        let mut values = vec![];
+        self.expect(b'[')?;
+        if self.peek()? == b']' {
+            self.pos += 1;
+            return Ok(JsonValue::Array(values));
+        }
+        loop {
+            values.push(self.parse_value()?);
+            match self.peek()? {
+                b',' => self.pos += 1,
+                b']' => {
+                    self.pos += 1;
+                    return Ok(JsonValue::Array(values));
+                },
+                _ => return Err(format!("expecting `,` or `]` at position {}", self.pos)),
+            }
+        }
+    }
+
+    // returns the string including the quotation marks
+    fn parse_string(&mut self) -> Result<&'a [u8], String> {
+        let start = self.pos;
+        self.expect(b'"')?;
+        loop {
+            match self.bytes.get(self.pos) {
+                Some(b'"') => {
+                    self.pos += 1;
+                    return Ok(&self.bytes[start..self.pos]);
+                },
+                // skip the escaped character, which may be a quotation mark
+                Some(b'\\') => self.pos += 2,
+                Some(_) => self.pos += 1,
+                None => return Err("unterminated string in JSON".to_string()),
+            }
+        }
+    }
+
+    // numbers, booleans, and null
+    fn parse_literal(&mut self) -> Result<&'a [u8], String> {
+        let start = self.pos;
+        while let Some(b'0'..=b'9' | b'a'..=b'z' | b'-' | b'+' | b'.' | b'E') =
+            self.bytes.get(self.pos)
+        {
+            self.pos += 1;
+        }
+        if self.pos == start {
+            return Err(format!("unexpected character at position {start}"));
+        }
+        Ok(&self.bytes[start..self.pos])
+    }
+}
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {super::*, serde::Serialize};
+
+    #[derive(Serialize)]
+    struct Inner {
+        zebra: Vec<u32>,
+        apple: Option<bool>,
+    }
+
+    #[derive(Serialize)]
+    struct Outer {
+        name:  String,
+        inner: Inner,
+        empty: Vec<Inner>,
+        count: u64,
+    }
+
+    #[test]
+    fn canonical_json_sorts_keys() {
+        let data = Outer {
+            name:  "fo\"o}".to_string(),
+            inner: Inner {
+                zebra: vec![3, 1, 2],
+                apple: None,
+            },
+            empty: vec![],
+            count: 123,
+        };
+        assert_eq!(
+            to_canonical_json(&data).unwrap().as_ref(),
+            br#"{"count":123,"empty":[],"inner":{"apple":null,"zebra":[3,1,2]},"name":"fo\"o}"}"#,
+        );
+    }
+
+    // sorted by the quoted and escaped keys, the order would be different, as
+    // `"` is greater than a space or `!`, and `\` is greater than all of them
+    #[test]
+    fn canonical_json_sorts_unescaped_keys() {
+        let data = BTreeMap::from([
+            ("a\"", 1),
+            ("a!",  2),
+            ("a b", 3),
+            ("a\n", 4),
+            ("a",   5),
+        ]);
+        assert_eq!(
+            to_canonical_json(&data).unwrap().as_ref(),
+            br#"{"a":5,"a\n":4,"a b":3,"a!":2,"a\"":1}"#,
+        );
+    }
+}
@@ -25,6 +25,10 @@ impl Storage for Box<dyn Storage> {
         self.as_mut().remove(key)
     }
 
+    fn remove_range(&mut self, min: Option<&[u8]>, max: Option<&[u8]>) {
+        self.as_mut().remove_range(min, max)
+    }
+
     fn flush(&mut self, batch: Batch) {
         self.as_mut().flush(batch)
     }
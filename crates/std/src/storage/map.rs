@@ -32,6 +32,12 @@ where
         PathBuf::new(self.namespace, &raw_keys, last_raw_key.as_ref())
     }
 
+    /// The key under which the value corresponding to `key` is saved in the
+    /// underlying storage.
+    pub fn storage_key(&self, key: K) -> Vec<u8> {
+        self.path(key).storage_key().to_vec()
+    }
+
     fn no_prefix(&self) -> Prefix<K, T> {
         Prefix::new(self.namespace, &[])
     }
@@ -17,6 +17,10 @@ impl<T> PathBuf<T> {
         }
     }
 
+    pub fn storage_key(&self) -> &[u8] {
+        &self.storage_key
+    }
+
     pub fn as_path(&self) -> Path<'_, T> {
         Path {
             storage_key: self.storage_key.as_slice(),
@@ -74,14 +74,33 @@ where
         Box::new(iter)
     }
 
+    /// Delete data under the prefix within the given bounds. If a limit is
+    /// specified, delete at most that many records, starting from the min.
     pub fn clear(
         &self,
-        _store: &mut dyn Storage,
-        _min:   Option<Bound<K>>,
-        _max:   Option<Bound<K>>,
-        _limit: Option<usize>,
+        store: &mut dyn Storage,
+        min:   Option<Bound<K>>,
+        max:   Option<Bound<K>>,
+        limit: Option<usize>,
     ) -> StdResult<()> {
-        todo!()
+        let (min, max) = range_bounds(&self.prefix, min, max);
+
+        // without a limit, we can delete the entire range in one go
+        let Some(limit) = limit else {
+            store.remove_range(Some(&min), Some(&max));
+            return Ok(());
+        };
+
+        let keys = store
+            .scan(Some(&min), Some(&max), Order::Ascending)
+            .take(limit)
+            .map(|(k, _)| k)
+            .collect::<Vec<_>>();
+        for key in keys {
+            store.remove(&key);
+        }
+
+        Ok(())
     }
 }
 
@@ -107,6 +107,20 @@ pub trait Storage: DynClone {
 
     fn remove(&mut self, key: &[u8]);
 
+    /// Delete all data in the KV store under the given bounds. Same as `scan`,
+    /// minimum bound is inclusive, maximum bound is exclusive. If min > max,
+    /// nothing is to be deleted.
+    ///
+    /// The default implementation here is collecting the keys in the range and
+    /// removing them one by one, which is inefficient.
+    /// Overwrite this implementation if there are more efficient approaches.
+    fn remove_range(&mut self, min: Option<&[u8]>, max: Option<&[u8]>) {
+        let keys = self.scan(min, max, Order::Ascending).map(|(k, _)| k).collect::<Vec<_>>();
+        for key in keys {
+            self.remove(&key);
+        }
+    }
+
     /// Perform a batch of writes and removes altogether, ideally atomically.
     ///
     /// The batch is provided by value instead of by reference (unlike other
@@ -0,0 +1,100 @@
+use crate::{Api, StdError, StdResult};
+
+/// An implementation of the host's debug, crypto, and hash methods for unit
+/// testing contracts natively, using `cw_crypto` directly instead of calling
+/// the Wasm imports.
+#[derive(Default, Debug, Clone, Copy)]
+pub struct MockApi;
+
+impl Api for MockApi {
+    fn debug(&self, msg: &str) {
+        println!("{msg}");
+    }
+
+    fn secp256k1_verify(&self, msg_hash: &[u8], sig: &[u8], pk: &[u8]) -> StdResult<()> {
+        cw_crypto::secp256k1_verify(msg_hash, sig, pk).map_err(|_| StdError::VerificationFailed)
+    }
+
+    fn secp256r1_verify(&self, msg_hash: &[u8], sig: &[u8], pk: &[u8]) -> StdResult<()> {
+        cw_crypto::secp256r1_verify(msg_hash, sig, pk).map_err(|_| StdError::VerificationFailed)
+    }
+
+    fn secp256k1_pubkey_recover(
+        &self,
+        msg_hash:    &[u8],
+        sig:         &[u8],
+        recovery_id: u8,
+        compressed:  bool,
+    ) -> StdResult<Vec<u8>> {
+        cw_crypto::secp256k1_pubkey_recover(msg_hash, sig, recovery_id, compressed)
+            .map_err(|_| StdError::VerificationFailed)
+    }
+
+    fn ed25519_verify(&self, msg: &[u8], sig: &[u8], pk: &[u8]) -> StdResult<()> {
+        cw_crypto::ed25519_verify(msg, sig, pk).map_err(|_| StdError::VerificationFailed)
+    }
+
+    fn ed25519_batch_verify(&self, msgs: &[&[u8]], sigs: &[&[u8]], pks: &[&[u8]]) -> StdResult<()> {
+        cw_crypto::ed25519_batch_verify(msgs, sigs, pks).map_err(|_| StdError::VerificationFailed)
+    }
+
+    fn bls12_381_aggregate_verify(
+        &self,
+        msgs: &[&[u8]],
+        sig:  &[u8],
+        pks:  &[&[u8]],
+    ) -> StdResult<()> {
+        cw_crypto::bls12_381_aggregate_verify(msgs, sig, pks)
+            .map_err(|_| StdError::VerificationFailed)
+    }
+
+    fn sha2_256(&self, data: &[u8]) -> [u8; 32] {
+        cw_crypto::sha2_256(data)
+    }
+
+    fn sha3_256(&self, data: &[u8]) -> [u8; 32] {
+        cw_crypto::sha3_256(data)
+    }
+
+    fn keccak256(&self, data: &[u8]) -> [u8; 32] {
+        cw_crypto::keccak256(data)
+    }
+
+    fn blake3(&self, data: &[u8]) -> [u8; 32] {
+        cw_crypto::blake3(data)
+    }
+}
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{hash, Addr, MockQuerier, MockStorage, QueryCtx, Timestamp, Uint64},
+    };
+
+    #[test]
+    fn mock_api_works_in_context() {
+        let store = MockStorage::new();
+        let ctx = QueryCtx {
+            store:           &store,
+            api:             &MockApi,
+            querier:         &MockQuerier::new(),
+            chain_id:        "dev-1".to_string(),
+            block_height:    Uint64::new(1),
+            block_timestamp: Timestamp::from_seconds(0),
+            block_hash:      hash(""),
+            contract:        Addr::mock(1),
+        };
+
+        // the host's SHA-256 is the same as the one used natively
+        assert_eq!(ctx.sha2_256(b"Jake"), *hash(b"Jake"));
+
+        // malformed inputs result in verification failure, not panicking
+        assert!(matches!(
+            ctx.secp256k1_verify(b"", b"", b""),
+            Err(StdError::VerificationFailed)
+        ));
+    }
+}
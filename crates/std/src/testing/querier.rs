@@ -0,0 +1,36 @@
+use crate::{Querier, QueryRequest, QueryResponse, StdError, StdResult};
+
+type QueryHandler = Box<dyn Fn(&QueryRequest) -> StdResult<QueryResponse>>;
+
+/// A querier for unit testing contracts natively, which answers queries using
+/// a handler function provided by the test. Without a handler, every query
+/// fails.
+#[derive(Default)]
+pub struct MockQuerier {
+    handler: Option<QueryHandler>,
+}
+
+impl MockQuerier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_handler<F>(handler: F) -> Self
+    where
+        F: Fn(&QueryRequest) -> StdResult<QueryResponse> + 'static,
+    {
+        Self {
+            handler: Some(Box::new(handler)),
+        }
+    }
+}
+
+impl Querier for MockQuerier {
+    fn query_chain(&self, req: &QueryRequest) -> StdResult<QueryResponse> {
+        let Some(handler) = &self.handler else {
+            return Err(StdError::Generic("MockQuerier has no query handler".into()));
+        };
+
+        handler(req)
+    }
+}
@@ -1,5 +1,5 @@
 use {
-    crate::{Addr, Hash, Message, Timestamp, Uint64},
+    crate::{Addr, Binary, Hash, Map, Message, Timestamp, Uint64},
     hex_literal::hex,
     serde::{Deserialize, Serialize},
     serde_with::skip_serializing_none,
@@ -30,6 +30,42 @@ pub struct Config {
     /// using an approach similar to Solana's Metaplex standard:
     /// https://twitter.com/octalmage/status/1695165358955487426
     pub bank: Addr,
+    /// A contract that levies transaction fees. Its `withhold_fee` and
+    /// `finalize_fee` entry points are called before and after each tx.
+    ///
+    /// Setting this to None makes transactions free of charge.
+    pub taxman: Option<Addr>,
+    /// Contracts whose `before_block` entry point is to be called at the
+    /// beginning of every block, before any transaction is executed.
+    pub begin_blockers: Vec<Cronjob>,
+    /// Contracts whose `after_block` entry point is to be called at the end of
+    /// every block, after all transactions have been executed.
+    pub end_blockers: Vec<Cronjob>,
+    /// The maximum depth of messages. A message in a transaction is at depth
+    /// zero, and a submessage is one level deeper than the message that led to
+    /// it. A message that exceeds this fails, and so does the transaction
+    /// (unless a contract handles the error in its `reply` entry point).
+    ///
+    /// If None, the app's default is used. Either way, the depth is capped at
+    /// a hard maximum set by the app.
+    pub max_msg_depth: Option<u32>,
+}
+
+/// A contract to be automatically called by the chain at regular intervals.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Cronjob {
+    pub contract: Addr,
+    /// The contract is called at blocks whose height is a multiple of this
+    /// number. E.g. 1 means every block, 10 means every ten blocks. Zero means
+    /// the contract is never called.
+    pub interval: Uint64,
+}
+
+impl Cronjob {
+    /// Whether this cronjob is to be called at the given block height.
+    pub fn is_due(&self, height: Uint64) -> bool {
+        height.u64().checked_rem(self.interval.u64()) == Some(0)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -51,3 +87,45 @@ pub struct Account {
     pub code_hash: Hash,
     pub admin:     Option<Addr>,
 }
+
+// --------------------------- state storage layout ----------------------------
+
+// the parts of the app's state storage layout that are also needed outside of
+// the app, e.g. by clients to find the keys that Merkle proofs are made against
+
+/// Wasm contract byte codes: code_hash => byte_code
+pub const CODES: Map<&Hash, Binary> = Map::new("code");
+
+/// Account metadata: address => account
+pub const ACCOUNTS: Map<&Addr, Account> = Map::new("account");
+
+/// Each contract has its own storage called, which we term the "substore".
+/// A key in a contract's substore is prefixed by this namespace + contract address.
+pub const CONTRACT_NAMESPACE: &[u8] = b"wasm";
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cronjob_is_due_works() {
+        for (interval, height, expect) in [
+            (1,  1,  true),
+            (1,  7,  true),
+            (10, 10, true),
+            (10, 20, true),
+            (10, 15, false),
+            (10, 5,  false),
+            (0,  0,  false),
+            (0,  10, false),
+        ] {
+            let cronjob = Cronjob {
+                contract: Addr::mock(1),
+                interval: Uint64::new(interval),
+            };
+            assert_eq!(cronjob.is_due(Uint64::new(height)), expect);
+        }
+    }
+}
@@ -1,6 +1,6 @@
 //! The bank contract is one of the two "core" contracts required by CWD,
 //! meaning contracts that provide core functionalities of the chain, the other
-//! being the tax man, which levies transaction fees.
+//! being the tax man, which levies transaction fees (see the `taxman` module).
 //!
 //! The bank contract MUST implement the following two entry points:
 //!
@@ -1,5 +1,5 @@
 use {
-    crate::{Addr, Coins, Event, GenericResult, Hash, Storage, Timestamp, Uint64},
+    crate::{Addr, Api, Coins, Event, GenericResult, Hash, Querier, Storage, Timestamp, Uint64},
     serde::{Deserialize, Serialize},
     serde_with::skip_serializing_none,
 };
@@ -27,6 +27,8 @@ pub struct Context {
 
 pub struct InstantiateCtx<'a> {
     pub store:           &'a mut dyn Storage,
+    pub api:             &'a dyn Api,
+    pub querier:         &'a dyn Querier,
     pub chain_id:        String,
     pub block_height:    Uint64,
     pub block_timestamp: Timestamp,
@@ -38,6 +40,8 @@ pub struct InstantiateCtx<'a> {
 
 pub struct ExecuteCtx<'a> {
     pub store:           &'a mut dyn Storage,
+    pub api:             &'a dyn Api,
+    pub querier:         &'a dyn Querier,
     pub chain_id:        String,
     pub block_height:    Uint64,
     pub block_timestamp: Timestamp,
@@ -49,6 +53,8 @@ pub struct ExecuteCtx<'a> {
 
 pub struct QueryCtx<'a> {
     pub store:           &'a dyn Storage,
+    pub api:             &'a dyn Api,
+    pub querier:         &'a dyn Querier,
     pub chain_id:        String,
     pub block_height:    Uint64,
     pub block_timestamp: Timestamp,
@@ -58,6 +64,8 @@ pub struct QueryCtx<'a> {
 
 pub struct MigrateCtx<'a> {
     pub store:           &'a mut dyn Storage,
+    pub api:             &'a dyn Api,
+    pub querier:         &'a dyn Querier,
     pub chain_id:        String,
     pub block_height:    Uint64,
     pub block_timestamp: Timestamp,
@@ -68,6 +76,8 @@ pub struct MigrateCtx<'a> {
 
 pub struct ReplyCtx<'a> {
     pub store:           &'a mut dyn Storage,
+    pub api:             &'a dyn Api,
+    pub querier:         &'a dyn Querier,
     pub chain_id:        String,
     pub block_height:    Uint64,
     pub block_timestamp: Timestamp,
@@ -78,6 +88,8 @@ pub struct ReplyCtx<'a> {
 
 pub struct ReceiveCtx<'a> {
     pub store:           &'a mut dyn Storage,
+    pub api:             &'a dyn Api,
+    pub querier:         &'a dyn Querier,
     pub chain_id:        String,
     pub block_height:    Uint64,
     pub block_timestamp: Timestamp,
@@ -89,6 +101,20 @@ pub struct ReceiveCtx<'a> {
 
 pub struct BeforeTxCtx<'a> {
     pub store:           &'a mut dyn Storage,
+    pub api:             &'a dyn Api,
+    pub querier:         &'a dyn Querier,
+    pub chain_id:        String,
+    pub block_height:    Uint64,
+    pub block_timestamp: Timestamp,
+    pub block_hash:      Hash,
+    pub contract:        Addr,
+    pub simulate:        bool,
+}
+
+pub struct TaxmanCtx<'a> {
+    pub store:           &'a mut dyn Storage,
+    pub api:             &'a dyn Api,
+    pub querier:         &'a dyn Querier,
     pub chain_id:        String,
     pub block_height:    Uint64,
     pub block_timestamp: Timestamp,
@@ -99,6 +125,30 @@ pub struct BeforeTxCtx<'a> {
 
 pub struct TransferCtx<'a> {
     pub store:           &'a mut dyn Storage,
+    pub api:             &'a dyn Api,
+    pub querier:         &'a dyn Querier,
+    pub chain_id:        String,
+    pub block_height:    Uint64,
+    pub block_timestamp: Timestamp,
+    pub block_hash:      Hash,
+    pub contract:        Addr,
+}
+
+pub struct BeforeBlockCtx<'a> {
+    pub store:           &'a mut dyn Storage,
+    pub api:             &'a dyn Api,
+    pub querier:         &'a dyn Querier,
+    pub chain_id:        String,
+    pub block_height:    Uint64,
+    pub block_timestamp: Timestamp,
+    pub block_hash:      Hash,
+    pub contract:        Addr,
+}
+
+pub struct AfterBlockCtx<'a> {
+    pub store:           &'a mut dyn Storage,
+    pub api:             &'a dyn Api,
+    pub querier:         &'a dyn Querier,
     pub chain_id:        String,
     pub block_height:    Uint64,
     pub block_timestamp: Timestamp,
@@ -10,6 +10,7 @@ mod hash;
 mod query;
 mod response;
 mod result;
+mod taxman;
 mod timestamp;
 mod tx;
 mod uint128;
@@ -17,25 +18,29 @@ mod uint64;
 
 pub use {
     address::Addr,
-    app::{Account, BlockInfo, Config, GenesisState, GENESIS_BLOCK_HASH, GENESIS_SENDER},
+    app::{
+        Account, BlockInfo, Config, Cronjob, GenesisState, ACCOUNTS, CODES, CONTRACT_NAMESPACE,
+        GENESIS_BLOCK_HASH, GENESIS_SENDER,
+    },
     bank::{BankQuery, BankQueryResponse, TransferMsg},
     binary::Binary,
     coin::{Coin, CoinRef, Coins, CoinsIntoIter, CoinsIter},
     context::{
-        BeforeTxCtx, Context, ExecuteCtx, InstantiateCtx, MigrateCtx, QueryCtx, ReceiveCtx,
-        ReplyCtx, TransferCtx,
+        AfterBlockCtx, BeforeBlockCtx, BeforeTxCtx, Context, ExecuteCtx, InstantiateCtx,
+        MigrateCtx, QueryCtx, ReceiveCtx, ReplyCtx, TaxmanCtx, TransferCtx,
     },
     empty::Empty,
     event::{Attribute, Event},
     hash::{hash, Hash},
     query::{
-        AccountResponse, InfoResponse, QueryRequest, QueryResponse, WasmRawResponse,
-        WasmSmartResponse,
+        AccountResponse, InfoResponse, QueryRequest, QueryResponse, SimulateResponse, TxResponse,
+        WasmRawResponse, WasmSmartResponse,
     },
     response::{ReplyOn, Response, SubMessage},
-    result::GenericResult,
+    result::{GenericError, GenericResult},
+    taxman::TxOutcome,
     timestamp::Timestamp,
-    tx::{Message, Tx},
+    tx::{Message, SignDoc, Tx, SIGN_DOC_DOMAIN},
     uint128::Uint128,
     uint64::Uint64,
 };
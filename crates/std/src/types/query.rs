@@ -1,5 +1,8 @@
 use {
-    crate::{Addr, Binary, BlockInfo, Coin, Coins, Config, Hash},
+    crate::{
+        Addr, Binary, BlockInfo, Coin, Coins, Config, Event, GenericResult, Hash, Tx, TxOutcome,
+        Uint64,
+    },
     serde::{Deserialize, Serialize},
     serde_with::skip_serializing_none,
 };
@@ -71,6 +74,35 @@ pub enum QueryRequest {
         contract: Addr,
         msg:      Binary,
     },
+    /// Execute a transaction against the latest state without committing any
+    /// state changes, for the purpose of estimating its gas consumption and
+    /// events. The sender account's `before_tx` is called with
+    /// `simulate: Some(true)`, so it may skip verifying the credential.
+    /// If made by a contract, the tx's gas limit is capped at the gas left for
+    /// the query. Otherwise, it's capped at a maximum set by the node.
+    /// Returns: SimulateResponse
+    Simulate {
+        tx: Tx,
+    },
+    /// A transaction that has been included in a block, and its outcome, by
+    /// the tx hash. Served from the node's local tx index, which only covers
+    /// blocks that haven't been pruned. Not available to contracts.
+    /// Returns: TxResponse
+    Tx {
+        hash: Hash,
+    },
+    /// Enumerate transactions that emitted an event of the given type with an
+    /// attribute of the given key and value, in the order they were included
+    /// in blocks. Served from the node's local tx index, which only covers
+    /// blocks that haven't been pruned. Not available to contracts.
+    /// Returns: Vec<TxResponse>
+    SearchTxs {
+        event_type:  String,
+        key:         String,
+        value:       String,
+        start_after: Option<Hash>,
+        limit:       Option<u32>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -102,6 +134,24 @@ pub struct WasmSmartResponse {
     pub data:     Binary,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SimulateResponse {
+    pub gas_limit: Uint64,
+    pub gas_used:  Uint64,
+    pub result:    GenericResult<Vec<Event>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TxResponse {
+    pub hash:    Hash,
+    /// The height of the block that included the tx.
+    pub height:  Uint64,
+    /// The position of the tx in the block.
+    pub index:   u32,
+    pub tx:      Tx,
+    pub outcome: TxOutcome,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryResponse {
@@ -116,6 +166,9 @@ pub enum QueryResponse {
     Accounts(Vec<AccountResponse>),
     WasmRaw(WasmRawResponse),
     WasmSmart(WasmSmartResponse),
+    Simulate(SimulateResponse),
+    Tx(TxResponse),
+    SearchTxs(Vec<TxResponse>),
 }
 
 // TODO: can we use a macro to implement these?
@@ -196,4 +249,25 @@ impl QueryResponse {
         };
         resp
     }
+
+    pub fn as_simulate(self) -> SimulateResponse {
+        let Self::Simulate(resp) = self else {
+            panic!("QueryResponse is not Simulate");
+        };
+        resp
+    }
+
+    pub fn as_tx(self) -> TxResponse {
+        let Self::Tx(resp) = self else {
+            panic!("QueryResponse is not Tx");
+        };
+        resp
+    }
+
+    pub fn as_search_txs(self) -> Vec<TxResponse> {
+        let Self::SearchTxs(resp) = self else {
+            panic!("QueryResponse is not SearchTxs");
+        };
+        resp
+    }
 }
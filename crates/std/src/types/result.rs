@@ -1,6 +1,7 @@
 use {
-    crate::{StdError, StdResult},
+    crate::{CodedError, StdError, StdResult},
     serde::{Deserialize, Serialize},
+    std::fmt,
 };
 
 /// A result type that can be serialized into a string and thus passed over the
@@ -13,28 +14,28 @@ use {
 #[serde(rename_all = "snake_case")]
 pub enum GenericResult<T> {
     Ok(T),
-    Err(String),
+    Err(GenericError),
 }
 
 impl<T, E> From<Result<T, E>> for GenericResult<T>
 where
-    E: ToString,
+    E: Into<GenericError>,
 {
     fn from(res: Result<T, E>) -> Self {
         match res {
             Result::Ok(data) => Self::Ok(data),
-            Result::Err(err) => Self::Err(err.to_string()),
+            Result::Err(err) => Self::Err(err.into()),
         }
     }
 }
 
 impl<T> GenericResult<T> {
     /// Convert the GenericResult to a StdResult, so that it can be unwrapped
-    /// with the `?` operator.
+    /// with the `?` operator. The error keeps its codespace and code.
     pub fn into_std_result(self) -> StdResult<T> {
         match self {
             GenericResult::Ok(data) => Ok(data),
-            GenericResult::Err(err) => Err(StdError::Generic(err)),
+            GenericResult::Err(err) => Err(StdError::Forwarded(err)),
         }
     }
 
@@ -50,15 +51,95 @@ impl<T> GenericResult<T> {
         }
     }
 
-    /// Assume the GenericResult is an Err, get the error message. Error if it
-    /// is an Ok.
+    /// Assume the GenericResult is an Err, get the error. Error if it is an Ok.
     /// This is useful if you're sure the result is an Err, e.g. when handling a
     /// submessage result in the `reply` entry point, when you have configured
     /// it to reply only on error.
-    pub fn as_err(self) -> String {
+    pub fn as_err(self) -> GenericError {
         match self {
             GenericResult::Ok(_) => unreachable!(),
             GenericResult::Err(err) => err,
         }
     }
 }
+
+/// An error that has been serialized, such that it can be passed over the FFI
+/// boundary, or returned to clients. The codespace and code identify the kind
+/// of the error; see `CodedError`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct GenericError {
+    pub codespace: String,
+    pub code:      u32,
+    pub msg:       String,
+}
+
+impl GenericError {
+    /// The codespace of errors that don't come with a code, e.g. an error
+    /// created by `anyhow::bail!` in a contract. Such errors all have the code
+    /// `UNDEFINED_CODE`.
+    pub const UNDEFINED_CODESPACE: &'static str = "undefined";
+    pub const UNDEFINED_CODE: u32 = 1;
+
+    pub fn new(codespace: impl Into<String>, code: u32, msg: impl Into<String>) -> Self {
+        Self {
+            codespace: codespace.into(),
+            code,
+            msg: msg.into(),
+        }
+    }
+
+    pub fn undefined(msg: impl Into<String>) -> Self {
+        Self::new(Self::UNDEFINED_CODESPACE, Self::UNDEFINED_CODE, msg)
+    }
+}
+
+impl fmt::Display for GenericError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.msg)
+    }
+}
+
+impl<E> From<E> for GenericError
+where
+    E: CodedError,
+{
+    fn from(err: E) -> Self {
+        Self::new(err.codespace(), err.code(), err.to_string())
+    }
+}
+
+/// Contracts that don't define their own error types typically use `anyhow`.
+/// If the error was originally a `StdError`, its codespace and code are kept;
+/// otherwise, the error is undefined.
+impl From<anyhow::Error> for GenericError {
+    fn from(err: anyhow::Error) -> Self {
+        match err.downcast_ref::<StdError>() {
+            Some(std_err) => Self::new(std_err.codespace(), std_err.code(), err.to_string()),
+            None => Self::undefined(err.to_string()),
+        }
+    }
+}
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::StdErrorCode};
+
+    #[test]
+    fn converting_anyhow_error_works() {
+        // a std error wrapped in anyhow keeps its codespace and code
+        let err = GenericError::from(anyhow::Error::from(StdError::VerificationFailed));
+        assert_eq!(err.codespace, StdErrorCode::CODESPACE);
+        assert_eq!(err.code, StdErrorCode::VerificationFailed as u32);
+        assert_eq!(StdErrorCode::from_code(err.code), Some(StdErrorCode::VerificationFailed));
+
+        // any other error is undefined
+        let err = GenericError::from(anyhow::anyhow!("foo"));
+        assert_eq!(err, GenericError::undefined("foo"));
+
+        // forwarding an error over the FFI boundary and back keeps the code
+        let res = GenericResult::<()>::Err(err.clone()).into_std_result();
+        assert_eq!(GenericResult::from(res), GenericResult::Err(err));
+    }
+}
@@ -0,0 +1,40 @@
+//! The tax man is the second "core" contract required by CWD, besides the bank.
+//! It levies transaction fees. Chains that don't need fees may leave it unset
+//! in the config, in which case transactions are free of charge.
+//!
+//! The tax man contract MUST implement the following two entry points:
+//!
+//! ```ignore
+//! #[entry_point]
+//! fn withhold_fee<E>(ctx: TaxmanCtx, tx: Tx) -> Result<Response, E>;
+//!
+//! #[entry_point]
+//! fn finalize_fee<E>(ctx: TaxmanCtx, tx: Tx, outcome: TxOutcome) -> Result<Response, E>;
+//! ```
+//!
+//! `withhold_fee` is called before the sender account's `before_tx`. Typically
+//! the tax man withholds the maximum amount of fee the tx may incur (based on
+//! the tx's gas limit) from the sender. If this call fails, the tx is rejected,
+//! and none of its state changes are committed.
+//!
+//! `finalize_fee` is called after the tx's messages have been processed,
+//! regardless of whether they succeeded, with the amount of gas actually used.
+//! Typically the tax man refunds the unused portion of the withheld fee here.
+//!
+//! Calls to these two entry points are not metered against the tx's gas limit.
+//! Instead, each call may consume up to a fixed amount of gas set by the chain,
+//! and fails if it runs out.
+
+use {
+    crate::{Event, GenericResult, Uint64},
+    serde::{Deserialize, Serialize},
+};
+
+/// The outcome of processing a transaction, passed to the tax man's
+/// `finalize_fee` entry point.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TxOutcome {
+    pub gas_limit: Uint64,
+    pub gas_used:  Uint64,
+    pub result:    GenericResult<Vec<Event>>,
+}
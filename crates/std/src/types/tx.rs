@@ -1,5 +1,5 @@
 use {
-    crate::{Addr, Binary, Coins, Config, Hash},
+    crate::{hash, to_canonical_json, Addr, Binary, Coins, Config, Hash, StdResult, Uint64},
     serde::{Deserialize, Serialize},
     serde_with::skip_serializing_none,
 };
@@ -7,10 +7,67 @@ use {
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct Tx {
     pub sender:     Addr,
+    /// The maximum amount of gas this transaction is allowed to consume,
+    /// including the sender account's authentication (`before_tx`). If the
+    /// limit is exceeded, the transaction aborts with an out of gas error.
+    pub gas_limit:  Uint64,
     pub msgs:       Vec<Message>,
     pub credential: Binary,
 }
 
+/// Domain tag included in every `SignDoc`, such that a signature over a
+/// transaction can't be mistaken for a signature over some other kind of data.
+pub const SIGN_DOC_DOMAIN: &str = "cwd/tx/v1";
+
+/// The data that the sender of a transaction signs.
+///
+/// The bytes to be signed are the SHA-256 hash of the sign doc's canonical JSON
+/// encoding (see `to_canonical_json`), i.e. with no whitespace, and with the
+/// keys of every object sorted ascendingly. For example:
+///
+/// ```json
+/// {"chain_id":"dev-1","domain":"cwd/tx/v1","gas_limit":"1000000","msgs":[],"sender":"0x…","sequence":0}
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SignDoc {
+    pub domain:    String,
+    pub chain_id:  String,
+    pub sender:    Addr,
+    pub sequence:  u32,
+    pub gas_limit: Uint64,
+    pub msgs:      Vec<Message>,
+}
+
+impl SignDoc {
+    pub fn new(
+        chain_id:  impl Into<String>,
+        sender:    Addr,
+        sequence:  u32,
+        gas_limit: Uint64,
+        msgs:      Vec<Message>,
+    ) -> Self {
+        Self {
+            domain: SIGN_DOC_DOMAIN.to_string(),
+            chain_id: chain_id.into(),
+            sender,
+            sequence,
+            gas_limit,
+            msgs,
+        }
+    }
+
+    /// The canonical JSON encoding of the sign doc.
+    pub fn to_canonical_json(&self) -> StdResult<Binary> {
+        to_canonical_json(self)
+    }
+
+    /// The SHA-256 hash of the canonical JSON encoding, which is what's to be
+    /// signed by the sender.
+    pub fn sign_bytes(&self) -> StdResult<Hash> {
+        self.to_canonical_json().map(hash)
+    }
+}
+
 #[skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -56,3 +113,51 @@ pub enum Message {
         msg:           Binary,
     },
 }
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::Uint128, test_case::test_case};
+
+    // test vectors for clients implementing the sign doc in other languages
+    #[test_case(
+        SignDoc::new("dev-1", Addr::mock(1), 0, Uint64::new(1_000_000), vec![]),
+        concat!(
+            r#"{"chain_id":"dev-1","domain":"cwd/tx/v1","gas_limit":"1000000","msgs":[],"#,
+            r#""sender":"0x0000000000000000000000000000000000000000000000000000000000000001","#,
+            r#""sequence":0}"#,
+        ),
+        "27d406c6cf5f45373e0b92b48fa4385ba1306726fc60ed57e83ca38b4126c8da";
+        "no message"
+    )]
+    #[test_case(
+        SignDoc::new("dev-1", Addr::mock(1), 12, Uint64::new(2_500_000), vec![
+            Message::Transfer {
+                to:    Addr::mock(2),
+                coins: Coins::new_one("uatom", Uint128::new(100)),
+            },
+            Message::Execute {
+                contract: Addr::mock(3),
+                msg:      br#"{"foo":"bar"}"#.to_vec().into(),
+                funds:    Coins::new_empty(),
+            },
+        ]),
+        concat!(
+            r#"{"chain_id":"dev-1","domain":"cwd/tx/v1","gas_limit":"2500000","msgs":["#,
+            r#"{"transfer":{"coins":[{"amount":"100","denom":"uatom"}],"#,
+            r#""to":"0x0000000000000000000000000000000000000000000000000000000000000002"}},"#,
+            r#"{"execute":{"#,
+            r#""contract":"0x0000000000000000000000000000000000000000000000000000000000000003","#,
+            r#""funds":[],"msg":"eyJmb28iOiJiYXIifQ=="}}],"#,
+            r#""sender":"0x0000000000000000000000000000000000000000000000000000000000000001","#,
+            r#""sequence":12}"#,
+        ),
+        "82bb82b75681c8728340981cc921c79d0b950650b29d18530c1dc9c447f28ddb";
+        "transfer and execute"
+    )]
+    fn sign_doc_vectors(sign_doc: SignDoc, canonical_json: &str, sign_bytes: &str) {
+        assert_eq!(sign_doc.to_canonical_json().unwrap().as_ref(), canonical_json.as_bytes());
+        assert_eq!(sign_doc.sign_bytes().unwrap().to_string(), sign_bytes);
+    }
+}
@@ -1,8 +1,9 @@
 use {
     crate::{
-        from_json, to_json, BankQuery, BankQueryResponse, BeforeTxCtx, Binary, Context, ExecuteCtx,
-        ExternalStorage, GenericResult, InstantiateCtx, MigrateCtx, QueryCtx, ReceiveCtx, Region,
-        ReplyCtx, Response, TransferCtx, TransferMsg, Tx,
+        from_json, to_json, AfterBlockCtx, BankQuery, BankQueryResponse, BeforeBlockCtx,
+        BeforeTxCtx, Binary, Context, ExecuteCtx, ExternalApi, ExternalQuerier, ExternalStorage,
+        GenericError, GenericResult, InstantiateCtx, MigrateCtx, QueryCtx, ReceiveCtx, Region,
+        ReplyCtx, Response, TaxmanCtx, TransferCtx, TransferMsg, Tx, TxOutcome,
     },
     serde::de::DeserializeOwned,
 };
@@ -31,7 +32,7 @@ macro_rules! try_into_generic_result {
         match $expr {
             Ok(val) => val,
             Err(err) => {
-                return GenericResult::Err(err.to_string());
+                return GenericResult::Err(err.into());
             },
         }
     };
@@ -46,7 +47,7 @@ pub fn do_instantiate<M, E>(
 ) -> usize
 where
     M: DeserializeOwned,
-    E: ToString,
+    E: Into<GenericError>,
 {
     let ctx_bytes = unsafe { Region::consume(ctx_ptr as *mut Region) };
     let msg_bytes = unsafe { Region::consume(msg_ptr as *mut Region) };
@@ -64,13 +65,15 @@ fn _do_instantiate<M, E>(
 ) -> GenericResult<Response>
 where
     M: DeserializeOwned,
-    E: ToString,
+    E: Into<GenericError>,
 {
     let ctx: Context = try_into_generic_result!(from_json(ctx_bytes));
     let msg = try_into_generic_result!(from_json(msg_bytes));
 
     let ctx = InstantiateCtx {
         store:           &mut ExternalStorage,
+        api:             &ExternalApi,
+        querier:         &ExternalQuerier,
         chain_id:        ctx.chain_id,
         block_height:    ctx.block_height,
         block_timestamp: ctx.block_timestamp,
@@ -92,7 +95,7 @@ pub fn do_execute<M, E>(
 ) -> usize
 where
     M: DeserializeOwned,
-    E: ToString,
+    E: Into<GenericError>,
 {
     let ctx_bytes = unsafe { Region::consume(ctx_ptr as *mut Region) };
     let msg_bytes = unsafe { Region::consume(msg_ptr as *mut Region) };
@@ -110,13 +113,15 @@ fn _do_execute<M, E>(
 ) -> GenericResult<Response>
 where
     M: DeserializeOwned,
-    E: ToString,
+    E: Into<GenericError>,
 {
     let ctx: Context = try_into_generic_result!(from_json(ctx_bytes));
     let msg = try_into_generic_result!(from_json(msg_bytes));
 
     let ctx = ExecuteCtx {
         store:           &mut ExternalStorage,
+        api:             &ExternalApi,
+        querier:         &ExternalQuerier,
         chain_id:        ctx.chain_id,
         block_height:    ctx.block_height,
         block_timestamp: ctx.block_timestamp,
@@ -138,7 +143,7 @@ pub fn do_query<M, E>(
 ) -> usize
 where
     M: DeserializeOwned,
-    E: ToString,
+    E: Into<GenericError>,
 {
     let ctx_bytes = unsafe { Region::consume(ctx_ptr as *mut Region) };
     let msg_bytes = unsafe { Region::consume(msg_ptr as *mut Region) };
@@ -156,13 +161,15 @@ fn _do_query<M, E>(
 ) -> GenericResult<Binary>
 where
     M: DeserializeOwned,
-    E: ToString,
+    E: Into<GenericError>,
 {
     let ctx: Context = try_into_generic_result!(from_json(ctx_bytes));
     let msg = try_into_generic_result!(from_json(msg_bytes));
 
     let ctx = QueryCtx {
         store:           &ExternalStorage,
+        api:             &ExternalApi,
+        querier:         &ExternalQuerier,
         chain_id:        ctx.chain_id,
         block_height:    ctx.block_height,
         block_timestamp: ctx.block_timestamp,
@@ -182,7 +189,7 @@ pub fn do_migrate<M, E>(
 ) -> usize
 where
     M: DeserializeOwned,
-    E: ToString,
+    E: Into<GenericError>,
 {
     let ctx_bytes = unsafe { Region::consume(ctx_ptr as *mut Region) };
     let msg_bytes = unsafe { Region::consume(msg_ptr as *mut Region) };
@@ -200,13 +207,15 @@ fn _do_migrate<M, E>(
 ) -> GenericResult<Response>
 where
     M: DeserializeOwned,
-    E: ToString,
+    E: Into<GenericError>,
 {
     let ctx: Context = try_into_generic_result!(from_json(ctx_bytes));
     let msg = try_into_generic_result!(from_json(msg_bytes));
 
     let ctx = MigrateCtx {
         store:           &mut ExternalStorage,
+        api:             &ExternalApi,
+        querier:         &ExternalQuerier,
         chain_id:        ctx.chain_id,
         block_height:    ctx.block_height,
         block_timestamp: ctx.block_timestamp,
@@ -227,7 +236,7 @@ pub fn do_reply<M, E>(
 ) -> usize
 where
     M: DeserializeOwned,
-    E: ToString,
+    E: Into<GenericError>,
 {
     let ctx_bytes = unsafe { Region::consume(ctx_ptr as *mut Region) };
     let msg_bytes = unsafe { Region::consume(msg_ptr as *mut Region) };
@@ -245,13 +254,15 @@ fn _do_reply<M, E>(
 ) -> GenericResult<Response>
 where
     M: DeserializeOwned,
-    E: ToString,
+    E: Into<GenericError>,
 {
     let ctx: Context = try_into_generic_result!(from_json(ctx_bytes));
     let msg = try_into_generic_result!(from_json(msg_bytes));
 
     let ctx = ReplyCtx {
         store: &mut ExternalStorage,
+        api:             &ExternalApi,
+        querier:         &ExternalQuerier,
         chain_id:        ctx.chain_id,
         block_height:    ctx.block_height,
         block_timestamp: ctx.block_timestamp,
@@ -270,7 +281,7 @@ pub fn do_receive<E>(
     ctx_ptr:    usize,
 ) -> usize
 where
-    E: ToString,
+    E: Into<GenericError>,
 {
     let ctx_bytes = unsafe { Region::consume(ctx_ptr as *mut Region) };
 
@@ -285,12 +296,14 @@ fn _do_receive<E>(
     ctx_bytes:  &[u8],
 ) -> GenericResult<Response>
 where
-    E: ToString,
+    E: Into<GenericError>,
 {
     let ctx: Context = try_into_generic_result!(from_json(ctx_bytes));
 
     let ctx = ReceiveCtx {
         store:           &mut ExternalStorage,
+        api:             &ExternalApi,
+        querier:         &ExternalQuerier,
         chain_id:        ctx.chain_id,
         block_height:    ctx.block_height,
         block_timestamp: ctx.block_timestamp,
@@ -311,7 +324,7 @@ pub fn do_before_tx<E>(
     tx_ptr:       usize,
 ) -> usize
 where
-    E: ToString,
+    E: Into<GenericError>,
 {
     let ctx_bytes = unsafe { Region::consume(ctx_ptr as *mut Region) };
     let tx_bytes = unsafe { Region::consume(tx_ptr as *mut Region) };
@@ -328,13 +341,15 @@ fn _do_before_tx<E>(
     tx_bytes:     &[u8],
 ) -> GenericResult<Response>
 where
-    E: ToString,
+    E: Into<GenericError>,
 {
     let ctx: Context = try_into_generic_result!(from_json(ctx_bytes));
     let tx = try_into_generic_result!(from_json(tx_bytes));
 
     let ctx = BeforeTxCtx {
         store:           &mut ExternalStorage,
+        api:             &ExternalApi,
+        querier:         &ExternalQuerier,
         chain_id:        ctx.chain_id,
         block_height:    ctx.block_height,
         block_timestamp: ctx.block_timestamp,
@@ -346,6 +361,86 @@ where
     before_tx_fn(ctx, tx).into()
 }
 
+// ------------------------------- before block --------------------------------
+
+pub fn do_before_block<E>(
+    before_block_fn: &dyn Fn(BeforeBlockCtx) -> Result<Response, E>,
+    ctx_ptr:         usize,
+) -> usize
+where
+    E: Into<GenericError>,
+{
+    let ctx_bytes = unsafe { Region::consume(ctx_ptr as *mut Region) };
+
+    let res = _do_before_block(before_block_fn, &ctx_bytes);
+    let res_bytes = to_json(&res).unwrap();
+
+    Region::release_buffer(res_bytes.into()) as usize
+}
+
+fn _do_before_block<E>(
+    before_block_fn: &dyn Fn(BeforeBlockCtx) -> Result<Response, E>,
+    ctx_bytes:       &[u8],
+) -> GenericResult<Response>
+where
+    E: Into<GenericError>,
+{
+    let ctx: Context = try_into_generic_result!(from_json(ctx_bytes));
+
+    let ctx = BeforeBlockCtx {
+        store:           &mut ExternalStorage,
+        api:             &ExternalApi,
+        querier:         &ExternalQuerier,
+        chain_id:        ctx.chain_id,
+        block_height:    ctx.block_height,
+        block_timestamp: ctx.block_timestamp,
+        block_hash:      ctx.block_hash,
+        contract:        ctx.contract,
+    };
+
+    before_block_fn(ctx).into()
+}
+
+// -------------------------------- after block --------------------------------
+
+pub fn do_after_block<E>(
+    after_block_fn: &dyn Fn(AfterBlockCtx) -> Result<Response, E>,
+    ctx_ptr:        usize,
+) -> usize
+where
+    E: Into<GenericError>,
+{
+    let ctx_bytes = unsafe { Region::consume(ctx_ptr as *mut Region) };
+
+    let res = _do_after_block(after_block_fn, &ctx_bytes);
+    let res_bytes = to_json(&res).unwrap();
+
+    Region::release_buffer(res_bytes.into()) as usize
+}
+
+fn _do_after_block<E>(
+    after_block_fn: &dyn Fn(AfterBlockCtx) -> Result<Response, E>,
+    ctx_bytes:      &[u8],
+) -> GenericResult<Response>
+where
+    E: Into<GenericError>,
+{
+    let ctx: Context = try_into_generic_result!(from_json(ctx_bytes));
+
+    let ctx = AfterBlockCtx {
+        store:           &mut ExternalStorage,
+        api:             &ExternalApi,
+        querier:         &ExternalQuerier,
+        chain_id:        ctx.chain_id,
+        block_height:    ctx.block_height,
+        block_timestamp: ctx.block_timestamp,
+        block_hash:      ctx.block_hash,
+        contract:        ctx.contract,
+    };
+
+    after_block_fn(ctx).into()
+}
+
 // --------------------------------- transfer ----------------------------------
 
 pub fn do_transfer<E>(
@@ -354,7 +449,7 @@ pub fn do_transfer<E>(
     msg_ptr:     usize,
 ) -> usize
 where
-    E: ToString,
+    E: Into<GenericError>,
 {
     let ctx_bytes = unsafe { Region::consume(ctx_ptr as *mut Region) };
     let msg_bytes = unsafe { Region::consume(msg_ptr as *mut Region) };
@@ -371,13 +466,15 @@ fn _do_transfer<E>(
     msg_bytes:    &[u8],
 ) -> GenericResult<Response>
 where
-    E: ToString,
+    E: Into<GenericError>,
 {
     let ctx: Context = try_into_generic_result!(from_json(ctx_bytes));
     let msg = try_into_generic_result!(from_json(msg_bytes));
 
     let ctx = TransferCtx {
         store:           &mut ExternalStorage,
+        api:             &ExternalApi,
+        querier:         &ExternalQuerier,
         chain_id:        ctx.chain_id,
         block_height:    ctx.block_height,
         block_timestamp: ctx.block_timestamp,
@@ -388,6 +485,100 @@ where
     transfer_fn(ctx, msg).into()
 }
 
+// ------------------------------- withhold fee --------------------------------
+
+pub fn do_withhold_fee<E>(
+    withhold_fee_fn: &dyn Fn(TaxmanCtx, Tx) -> Result<Response, E>,
+    ctx_ptr:         usize,
+    tx_ptr:          usize,
+) -> usize
+where
+    E: Into<GenericError>,
+{
+    let ctx_bytes = unsafe { Region::consume(ctx_ptr as *mut Region) };
+    let tx_bytes = unsafe { Region::consume(tx_ptr as *mut Region) };
+
+    let res = _do_withhold_fee(withhold_fee_fn, &ctx_bytes, &tx_bytes);
+    let res_bytes = to_json(&res).unwrap();
+
+    Region::release_buffer(res_bytes.into()) as usize
+}
+
+fn _do_withhold_fee<E>(
+    withhold_fee_fn: &dyn Fn(TaxmanCtx, Tx) -> Result<Response, E>,
+    ctx_bytes:       &[u8],
+    tx_bytes:        &[u8],
+) -> GenericResult<Response>
+where
+    E: Into<GenericError>,
+{
+    let ctx: Context = try_into_generic_result!(from_json(ctx_bytes));
+    let tx = try_into_generic_result!(from_json(tx_bytes));
+
+    let ctx = TaxmanCtx {
+        store:           &mut ExternalStorage,
+        api:             &ExternalApi,
+        querier:         &ExternalQuerier,
+        chain_id:        ctx.chain_id,
+        block_height:    ctx.block_height,
+        block_timestamp: ctx.block_timestamp,
+        block_hash:      ctx.block_hash,
+        contract:        ctx.contract,
+        simulate:        ctx.simulate.expect("host failed to specify whether it's simulation mode"),
+    };
+
+    withhold_fee_fn(ctx, tx).into()
+}
+
+// ------------------------------- finalize fee --------------------------------
+
+pub fn do_finalize_fee<E>(
+    finalize_fee_fn: &dyn Fn(TaxmanCtx, Tx, TxOutcome) -> Result<Response, E>,
+    ctx_ptr:         usize,
+    tx_ptr:          usize,
+    outcome_ptr:     usize,
+) -> usize
+where
+    E: Into<GenericError>,
+{
+    let ctx_bytes = unsafe { Region::consume(ctx_ptr as *mut Region) };
+    let tx_bytes = unsafe { Region::consume(tx_ptr as *mut Region) };
+    let outcome_bytes = unsafe { Region::consume(outcome_ptr as *mut Region) };
+
+    let res = _do_finalize_fee(finalize_fee_fn, &ctx_bytes, &tx_bytes, &outcome_bytes);
+    let res_bytes = to_json(&res).unwrap();
+
+    Region::release_buffer(res_bytes.into()) as usize
+}
+
+fn _do_finalize_fee<E>(
+    finalize_fee_fn: &dyn Fn(TaxmanCtx, Tx, TxOutcome) -> Result<Response, E>,
+    ctx_bytes:       &[u8],
+    tx_bytes:        &[u8],
+    outcome_bytes:   &[u8],
+) -> GenericResult<Response>
+where
+    E: Into<GenericError>,
+{
+    let ctx: Context = try_into_generic_result!(from_json(ctx_bytes));
+    let tx = try_into_generic_result!(from_json(tx_bytes));
+    let outcome = try_into_generic_result!(from_json(outcome_bytes));
+
+    let ctx = TaxmanCtx {
+        store:           &mut ExternalStorage,
+        api:             &ExternalApi,
+        querier:         &ExternalQuerier,
+        chain_id:        ctx.chain_id,
+        block_height:    ctx.block_height,
+        block_timestamp: ctx.block_timestamp,
+        block_hash:      ctx.block_hash,
+        contract:        ctx.contract,
+        simulate:        ctx.simulate.expect("host failed to specify whether it's simulation mode"),
+    };
+
+    finalize_fee_fn(ctx, tx, outcome).into()
+}
+
 // -------------------------------- bank query ---------------------------------
 
 pub fn do_query_bank<E>(
@@ -396,7 +587,7 @@ pub fn do_query_bank<E>(
     msg_ptr:       usize,
 ) -> usize
 where
-    E: ToString,
+    E: Into<GenericError>,
 {
     let ctx_bytes = unsafe { Region::consume(ctx_ptr as *mut Region) };
     let msg_bytes = unsafe { Region::consume(msg_ptr as *mut Region) };
@@ -413,13 +604,15 @@ fn _do_query_bank<E>(
     msg_bytes:      &[u8],
 ) -> GenericResult<BankQueryResponse>
 where
-    E: ToString,
+    E: Into<GenericError>,
 {
     let ctx: Context = try_into_generic_result!(from_json(ctx_bytes));
     let msg = try_into_generic_result!(from_json(msg_bytes));
 
     let ctx = QueryCtx {
         store:           &ExternalStorage,
+        api:             &ExternalApi,
+        querier:         &ExternalQuerier,
         chain_id:        ctx.chain_id,
         block_height:    ctx.block_height,
         block_timestamp: ctx.block_timestamp,
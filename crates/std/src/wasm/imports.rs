@@ -1,9 +1,9 @@
 use {
     crate::{
-        from_json, to_json, Account, AccountResponse, Addr, BeforeTxCtx, Binary, Coins, ExecuteCtx,
-        GenericResult, Hash, InfoResponse, InstantiateCtx, MigrateCtx, Order, QueryCtx,
-        QueryRequest, QueryResponse, ReceiveCtx, Record, Region, ReplyCtx, StdError, StdResult,
-        Storage, TransferCtx, Uint128,
+        from_json, to_json, Account, AccountResponse, Addr, AfterBlockCtx, Api, BeforeBlockCtx,
+        BeforeTxCtx, Binary, Coins, ExecuteCtx, GenericResult, Hash, InfoResponse, InstantiateCtx,
+        MigrateCtx, Order, QueryCtx, QueryRequest, QueryResponse, Querier, ReceiveCtx, Record,
+        Region, ReplyCtx, StdError, StdResult, Storage, TaxmanCtx, TransferCtx, Uint128,
     },
     serde::{de::DeserializeOwned, ser::Serialize},
 };
@@ -26,6 +26,7 @@ extern "C" {
     // write ops (mutate the state):
     fn db_write(key_ptr: usize, value_ptr: usize);
     fn db_remove(key_ptr: usize);
+    fn db_remove_range(min_ptr: usize, max_ptr: usize);
 
     // print a debug message to the client's CLI output. the client must have
     // set tracing level to DEBUG to see it.
@@ -39,6 +40,25 @@ extern "C" {
     // return value of 0 means ok; any value other than 0 means error.
     fn secp256k1_verify(msg_hash_ptr: usize, sig_ptr: usize, pk_ptr: usize) -> i32;
     fn secp256r1_verify(msg_hash_ptr: usize, sig_ptr: usize, pk_ptr: usize) -> i32;
+    fn ed25519_verify(msg_ptr: usize, sig_ptr: usize, pk_ptr: usize) -> i32;
+    fn ed25519_batch_verify(msgs_ptr: usize, sigs_ptr: usize, pks_ptr: usize) -> i32;
+    fn bls12_381_aggregate_verify(msgs_ptr: usize, sig_ptr: usize, pks_ptr: usize) -> i32;
+
+    // return value of 0 means recovery failed; otherwise, it's the address of
+    // the region containing the recovered public key.
+    fn secp256k1_pubkey_recover(
+        msg_hash_ptr: usize,
+        sig_ptr:      usize,
+        recovery_id:  u8,
+        compressed:   bool,
+    ) -> usize;
+
+    // hash methods
+    // return value is the address of the region containing the 32-byte hash.
+    fn sha2_256(data_ptr: usize) -> usize;
+    fn sha3_256(data_ptr: usize) -> usize;
+    fn keccak256(data_ptr: usize) -> usize;
+    fn blake3(data_ptr: usize) -> usize;
 }
 
 /// A zero-size convenience wrapper around the database imports. Provides more
@@ -109,6 +129,17 @@ impl Storage for ExternalStorage {
 
         unsafe { db_remove(key_ptr as usize) }
     }
+
+    fn remove_range(&mut self, min: Option<&[u8]>, max: Option<&[u8]>) {
+        // same as in `scan`, keep the Regions in scope until end of the func
+        let min_region = min.map(Region::build);
+        let min_ptr = get_optional_region_ptr(min_region.as_ref());
+
+        let max_region = max.map(Region::build);
+        let max_ptr = get_optional_region_ptr(max_region.as_ref());
+
+        unsafe { db_remove_range(min_ptr, max_ptr) }
+    }
 }
 
 pub struct ExternalIterator {
@@ -169,7 +200,216 @@ fn split_tail(mut data: Vec<u8>) -> Record {
     (data, value)
 }
 
-// implement debug, query, and crypto methods for each context type
+/// A zero-size convenience wrapper around the debug, crypto, and hash imports.
+#[derive(Clone)]
+pub struct ExternalApi;
+
+impl Api for ExternalApi {
+    fn debug(&self, msg: &str) {
+        let region = Region::build(msg.as_bytes());
+        let ptr = &*region as *const Region;
+
+        unsafe { debug(ptr as usize) }
+    }
+
+    fn secp256k1_verify(&self, msg_hash: &[u8], sig: &[u8], pk: &[u8]) -> StdResult<()> {
+        let msg_hash_region = Region::build(msg_hash);
+        let msg_hash_ptr = &*msg_hash_region as *const Region;
+
+        let sig_region = Region::build(sig);
+        let sig_ptr = &*sig_region as *const Region;
+
+        let pk_region = Region::build(pk);
+        let pk_ptr = &*pk_region as *const Region;
+
+        let return_value =
+            unsafe { secp256k1_verify(msg_hash_ptr as usize, sig_ptr as usize, pk_ptr as usize) };
+
+        into_verification_result(return_value)
+    }
+
+    fn secp256r1_verify(&self, msg_hash: &[u8], sig: &[u8], pk: &[u8]) -> StdResult<()> {
+        let msg_hash_region = Region::build(msg_hash);
+        let msg_hash_ptr = &*msg_hash_region as *const Region;
+
+        let sig_region = Region::build(sig);
+        let sig_ptr = &*sig_region as *const Region;
+
+        let pk_region = Region::build(pk);
+        let pk_ptr = &*pk_region as *const Region;
+
+        let return_value =
+            unsafe { secp256r1_verify(msg_hash_ptr as usize, sig_ptr as usize, pk_ptr as usize) };
+
+        into_verification_result(return_value)
+    }
+
+    fn secp256k1_pubkey_recover(
+        &self,
+        msg_hash:    &[u8],
+        sig:         &[u8],
+        recovery_id: u8,
+        compressed:  bool,
+    ) -> StdResult<Vec<u8>> {
+        let msg_hash_region = Region::build(msg_hash);
+        let msg_hash_ptr = &*msg_hash_region as *const Region;
+
+        let sig_region = Region::build(sig);
+        let sig_ptr = &*sig_region as *const Region;
+
+        let pk_ptr = unsafe {
+            secp256k1_pubkey_recover(msg_hash_ptr as usize, sig_ptr as usize, recovery_id, compressed)
+        };
+
+        if pk_ptr == 0 {
+            // TODO: more useful error codes
+            return Err(StdError::VerificationFailed);
+        }
+
+        Ok(unsafe { Region::consume(pk_ptr as *mut Region) })
+    }
+
+    fn ed25519_verify(&self, msg: &[u8], sig: &[u8], pk: &[u8]) -> StdResult<()> {
+        let msg_region = Region::build(msg);
+        let msg_ptr = &*msg_region as *const Region;
+
+        let sig_region = Region::build(sig);
+        let sig_ptr = &*sig_region as *const Region;
+
+        let pk_region = Region::build(pk);
+        let pk_ptr = &*pk_region as *const Region;
+
+        let return_value =
+            unsafe { ed25519_verify(msg_ptr as usize, sig_ptr as usize, pk_ptr as usize) };
+
+        into_verification_result(return_value)
+    }
+
+    fn ed25519_batch_verify(&self, msgs: &[&[u8]], sigs: &[&[u8]], pks: &[&[u8]]) -> StdResult<()> {
+        let msgs_bytes = to_json_list(msgs)?;
+        let msgs_region = Region::build(&msgs_bytes);
+        let msgs_ptr = &*msgs_region as *const Region;
+
+        let sigs_bytes = to_json_list(sigs)?;
+        let sigs_region = Region::build(&sigs_bytes);
+        let sigs_ptr = &*sigs_region as *const Region;
+
+        let pks_bytes = to_json_list(pks)?;
+        let pks_region = Region::build(&pks_bytes);
+        let pks_ptr = &*pks_region as *const Region;
+
+        let return_value = unsafe {
+            ed25519_batch_verify(msgs_ptr as usize, sigs_ptr as usize, pks_ptr as usize)
+        };
+
+        into_verification_result(return_value)
+    }
+
+    fn bls12_381_aggregate_verify(
+        &self,
+        msgs: &[&[u8]],
+        sig:  &[u8],
+        pks:  &[&[u8]],
+    ) -> StdResult<()> {
+        let msgs_bytes = to_json_list(msgs)?;
+        let msgs_region = Region::build(&msgs_bytes);
+        let msgs_ptr = &*msgs_region as *const Region;
+
+        let sig_region = Region::build(sig);
+        let sig_ptr = &*sig_region as *const Region;
+
+        let pks_bytes = to_json_list(pks)?;
+        let pks_region = Region::build(&pks_bytes);
+        let pks_ptr = &*pks_region as *const Region;
+
+        let return_value = unsafe {
+            bls12_381_aggregate_verify(msgs_ptr as usize, sig_ptr as usize, pks_ptr as usize)
+        };
+
+        into_verification_result(return_value)
+    }
+
+    fn sha2_256(&self, data: &[u8]) -> [u8; 32] {
+        let data_region = Region::build(data);
+        let data_ptr = &*data_region as *const Region;
+
+        let hash_ptr = unsafe { sha2_256(data_ptr as usize) };
+
+        unsafe { consume_hash(hash_ptr) }
+    }
+
+    fn sha3_256(&self, data: &[u8]) -> [u8; 32] {
+        let data_region = Region::build(data);
+        let data_ptr = &*data_region as *const Region;
+
+        let hash_ptr = unsafe { sha3_256(data_ptr as usize) };
+
+        unsafe { consume_hash(hash_ptr) }
+    }
+
+    fn keccak256(&self, data: &[u8]) -> [u8; 32] {
+        let data_region = Region::build(data);
+        let data_ptr = &*data_region as *const Region;
+
+        let hash_ptr = unsafe { keccak256(data_ptr as usize) };
+
+        unsafe { consume_hash(hash_ptr) }
+    }
+
+    fn blake3(&self, data: &[u8]) -> [u8; 32] {
+        let data_region = Region::build(data);
+        let data_ptr = &*data_region as *const Region;
+
+        let hash_ptr = unsafe { blake3(data_ptr as usize) };
+
+        unsafe { consume_hash(hash_ptr) }
+    }
+}
+
+/// A zero-size convenience wrapper around the `query_chain` import.
+#[derive(Clone)]
+pub struct ExternalQuerier;
+
+impl Querier for ExternalQuerier {
+    fn query_chain(&self, req: &QueryRequest) -> StdResult<QueryResponse> {
+        let req_bytes = to_json(req)?;
+        let req_region = Region::build(&req_bytes);
+        let req_ptr = &*req_region as *const Region;
+
+        let res_ptr = unsafe { query_chain(req_ptr as usize) };
+        let res_bytes = unsafe { Region::consume(res_ptr as *mut Region) };
+        let res: GenericResult<QueryResponse> = from_json(&res_bytes)?;
+
+        res.into_std_result()
+    }
+}
+
+#[inline]
+fn into_verification_result(return_value: i32) -> StdResult<()> {
+    if return_value == 0 {
+        Ok(())
+    } else {
+        // TODO: more useful error codes
+        Err(StdError::VerificationFailed)
+    }
+}
+
+// lists of byte slices are passed to the host as JSON arrays of base64 strings
+fn to_json_list(items: &[&[u8]]) -> StdResult<Binary> {
+    to_json(&items.iter().map(|item| Binary::from(item.to_vec())).collect::<Vec<_>>())
+}
+
+// the host always returns 32-byte hashes, so if the length doesn't match, the
+// host must be faulty and we panic
+unsafe fn consume_hash(hash_ptr: usize) -> [u8; 32] {
+    Region::consume(hash_ptr as *mut Region)
+        .try_into()
+        .unwrap_or_else(|_| panic!("[ExternalApi]: host returned a hash of incorrect length"))
+}
+
+// implement debug, query, crypto, and hash methods for each context type.
+// the debug, crypto, and hash methods are simply forwarded to the api, and the
+// query methods to the querier.
 macro_rules! impl_methods {
     ($($t:ty),+ $(,)?) => {
         $(impl<'a> $t {
@@ -178,10 +418,7 @@ macro_rules! impl_methods {
                 // TODO: ideally, only emit the debug message in debug build
                 // composing the debug message may consume resources (e.g. if
                 // using the format! macro), so we want to do nothing in release
-                let region = Region::build(msg.as_ref().as_bytes());
-                let ptr = &*region as *const Region;
-
-                unsafe { debug(ptr as usize) }
+                self.api.debug(msg.as_ref())
             }
 
             /// NOTE: This function takes the hash of the message, not the prehash.
@@ -191,25 +428,7 @@ macro_rules! impl_methods {
                 sig:      impl AsRef<[u8]>,
                 pk:       impl AsRef<[u8]>,
             ) -> StdResult<()> {
-                let msg_hash_region = Region::build(msg_hash.as_ref());
-                let msg_hash_ptr = &*msg_hash_region as *const Region;
-
-                let sig_region = Region::build(sig.as_ref());
-                let sig_ptr = &*sig_region as *const Region;
-
-                let pk_region = Region::build(pk.as_ref());
-                let pk_ptr = &*pk_region as *const Region;
-
-                let return_value = unsafe {
-                    secp256k1_verify(msg_hash_ptr as usize, sig_ptr as usize, pk_ptr as usize)
-                };
-
-                if return_value == 0 {
-                    Ok(())
-                } else {
-                    // TODO: more useful error codes
-                    Err(StdError::VerificationFailed)
-                }
+                self.api.secp256k1_verify(msg_hash.as_ref(), sig.as_ref(), pk.as_ref())
             }
 
             /// NOTE: This function takes the hash of the message, not the prehash.
@@ -219,37 +438,81 @@ macro_rules! impl_methods {
                 sig:      impl AsRef<[u8]>,
                 pk:       impl AsRef<[u8]>,
             ) -> StdResult<()> {
-                let msg_hash_region = Region::build(msg_hash.as_ref());
-                let msg_hash_ptr = &*msg_hash_region as *const Region;
+                self.api.secp256r1_verify(msg_hash.as_ref(), sig.as_ref(), pk.as_ref())
+            }
+
+            /// Recover the secp256k1 public key that produced the signature,
+            /// in compressed or uncompressed SEC1 encoding as specified.
+            ///
+            /// NOTE: This function takes the hash of the message, not the prehash.
+            pub fn secp256k1_pubkey_recover(
+                &self,
+                msg_hash:    impl AsRef<[u8]>,
+                sig:         impl AsRef<[u8]>,
+                recovery_id: u8,
+                compressed:  bool,
+            ) -> StdResult<Vec<u8>> {
+                self.api.secp256k1_pubkey_recover(
+                    msg_hash.as_ref(),
+                    sig.as_ref(),
+                    recovery_id,
+                    compressed,
+                )
+            }
+
+            /// NOTE: Unlike the secp256k1 and secp256r1 functions, this function
+            /// takes the message itself, not its hash.
+            pub fn ed25519_verify(
+                &self,
+                msg: impl AsRef<[u8]>,
+                sig: impl AsRef<[u8]>,
+                pk:  impl AsRef<[u8]>,
+            ) -> StdResult<()> {
+                self.api.ed25519_verify(msg.as_ref(), sig.as_ref(), pk.as_ref())
+            }
 
-                let sig_region = Region::build(sig.as_ref());
-                let sig_ptr = &*sig_region as *const Region;
+            /// Verify multiple Ed25519 signatures at once. The i-th signature is
+            /// verified against the i-th message and the i-th public key.
+            /// Succeeds if and only if all signatures are valid.
+            pub fn ed25519_batch_verify(
+                &self,
+                msgs: &[&[u8]],
+                sigs: &[&[u8]],
+                pks:  &[&[u8]],
+            ) -> StdResult<()> {
+                self.api.ed25519_batch_verify(msgs, sigs, pks)
+            }
 
-                let pk_region = Region::build(pk.as_ref());
-                let pk_ptr = &*pk_region as *const Region;
+            /// Verify a BLS12-381 aggregate signature. If only one message is
+            /// given, it's considered to be signed by all the public keys;
+            /// otherwise, the i-th message is signed by the i-th public key.
+            pub fn bls12_381_aggregate_verify(
+                &self,
+                msgs: &[&[u8]],
+                sig:  impl AsRef<[u8]>,
+                pks:  &[&[u8]],
+            ) -> StdResult<()> {
+                self.api.bls12_381_aggregate_verify(msgs, sig.as_ref(), pks)
+            }
 
-                let return_value = unsafe {
-                    secp256r1_verify(msg_hash_ptr as usize, sig_ptr as usize, pk_ptr as usize)
-                };
+            pub fn sha2_256(&self, data: impl AsRef<[u8]>) -> [u8; 32] {
+                self.api.sha2_256(data.as_ref())
+            }
 
-                if return_value == 0 {
-                    Ok(())
-                } else {
-                    // TODO: more useful error codes
-                    Err(StdError::VerificationFailed)
-                }
+            pub fn sha3_256(&self, data: impl AsRef<[u8]>) -> [u8; 32] {
+                self.api.sha3_256(data.as_ref())
             }
 
-            pub fn query(&self, req: &QueryRequest) -> StdResult<QueryResponse> {
-                let req_bytes = to_json(req)?;
-                let req_region = Region::build(&req_bytes);
-                let req_ptr = &*req_region as *const Region;
+            pub fn keccak256(&self, data: impl AsRef<[u8]>) -> [u8; 32] {
+                self.api.keccak256(data.as_ref())
+            }
 
-                let res_ptr = unsafe { query_chain(req_ptr as usize) };
-                let res_bytes = unsafe { Region::consume(res_ptr as *mut Region) };
-                let res: GenericResult<QueryResponse> = from_json(&res_bytes)?;
+            pub fn blake3(&self, data: impl AsRef<[u8]>) -> [u8; 32] {
+                self.api.blake3(data.as_ref())
+            }
 
-                res.into_std_result()
+            pub fn query(&self, req: &QueryRequest) -> StdResult<QueryResponse> {
+                self.querier.query_chain(req)
             }
 
             pub fn query_info(&self) -> StdResult<InfoResponse> {
@@ -342,6 +605,8 @@ macro_rules! impl_methods {
 }
 
 impl_methods!(
+    AfterBlockCtx<'a>,
+    BeforeBlockCtx<'a>,
     BeforeTxCtx<'a>,
     ExecuteCtx<'a>,
     InstantiateCtx<'a>,
@@ -349,6 +614,7 @@ impl_methods!(
     QueryCtx<'a>,
     ReceiveCtx<'a>,
     ReplyCtx<'a>,
+    TaxmanCtx<'a>,
     TransferCtx<'a>,
 );
 
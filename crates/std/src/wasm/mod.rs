@@ -4,9 +4,10 @@ mod memory;
 
 pub use {
     exports::{
-        do_before_tx, do_execute, do_instantiate, do_migrate, do_query, do_query_bank, do_receive,
-        do_reply, do_transfer,
+        do_after_block, do_before_block, do_before_tx, do_execute, do_finalize_fee,
+        do_instantiate, do_migrate, do_query, do_query_bank, do_receive, do_reply, do_transfer,
+        do_withhold_fee,
     },
-    imports::{ExternalIterator, ExternalStorage},
+    imports::{ExternalApi, ExternalIterator, ExternalQuerier, ExternalStorage},
     memory::Region,
 };
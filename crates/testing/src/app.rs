@@ -0,0 +1,611 @@
+use {
+    crate::{ContractBuilder, NativeContract, NativeVm},
+    cw_app::{
+        call_begin_blockers, call_end_blockers, process_msg, process_query, run_tx, AppResult,
+        TxOutcome, CHAIN_ID, CONFIG, LAST_FINALIZED_BLOCK,
+    },
+    cw_bank::Balance,
+    cw_db::{CacheStore, SharedStore},
+    cw_std::{
+        from_json, hash, testing::MockStorage, to_json, Addr, Binary, BlockInfo, Coins, Config,
+        Event, Hash, Message, QueryRequest, QueryResponse, Timestamp, Tx, Uint128, Uint64,
+        GENESIS_BLOCK_HASH, GENESIS_SENDER,
+    },
+    cw_vm::GasTracker,
+    serde::{de::DeserializeOwned, ser::Serialize},
+};
+
+const TEST_CHAIN_ID: &str = "cw-testing";
+
+// the genesis block's timestamp, and the interval between blocks
+const GENESIS_TIMESTAMP: Timestamp = Timestamp::from_seconds(0);
+const BLOCK_TIME_SECONDS: u64 = 5;
+
+/// A chain that runs natively compiled contracts, for testing interactions
+/// between contracts without having to compile them to Wasm.
+///
+/// Messages are processed by the same `process_msg` function, and queries by
+/// the same `process_query` function, as those used by the real chain, on an
+/// in-memory store. A message that fails doesn't change the state.
+///
+/// At genesis, the `cw-bank` contract is deployed as the bank. The config has
+/// no owner or taxman; use `update_config` to change this.
+pub struct TestApp {
+    store: SharedStore<MockStorage>,
+    vm:    NativeVm,
+    block: BlockInfo,
+}
+
+impl TestApp {
+    /// Create a chain with the given initial token balances. Messages sent
+    /// afterwards are processed in block 1.
+    pub fn new(initial_balances: Vec<Balance>) -> AppResult<Self> {
+        let mut app = Self {
+            store: SharedStore::new(MockStorage::new()),
+            vm:    NativeVm::new(),
+            block: BlockInfo {
+                height:    Uint64::new(0),
+                timestamp: GENESIS_TIMESTAMP,
+                hash:      GENESIS_BLOCK_HASH,
+            },
+        };
+
+        // deploy the bank contract, the same way a genesis state would do it.
+        // the config needs to be saved first, as the bank address is used
+        // during instantiation (e.g. for transferring funds).
+        let bank_code = app.vm.register(
+            ContractBuilder::new(cw_bank::instantiate)
+                .with_execute(cw_bank::execute)
+                .with_transfer(cw_bank::transfer)
+                .with_receive(cw_bank::receive)
+                .with_query_bank(cw_bank::query_bank)
+                .build(),
+        );
+        let bank_code_hash = hash(&bank_code);
+        let bank_salt = Binary::from(b"bank".to_vec());
+        let cfg = Config {
+            owner:          None,
+            bank:           Addr::compute(&GENESIS_SENDER, &bank_code_hash, &bank_salt),
+            taxman:         None,
+            begin_blockers: vec![],
+            end_blockers:   vec![],
+            max_msg_depth:  None,
+        };
+
+        CHAIN_ID.save(&mut app.store, &TEST_CHAIN_ID.to_string())?;
+        CONFIG.save(&mut app.store, &cfg)?;
+        LAST_FINALIZED_BLOCK.save(&mut app.store, &app.block)?;
+
+        app.process_msg(&GENESIS_SENDER, Message::StoreCode {
+            wasm_byte_code: bank_code,
+        })?;
+        app.process_msg(&GENESIS_SENDER, Message::Instantiate {
+            code_hash: bank_code_hash,
+            msg:       to_json(&cw_bank::InstantiateMsg { initial_balances })?,
+            salt:      bank_salt,
+            funds:     Coins::new_empty(),
+            admin:     None,
+        })?;
+
+        // genesis is done; move on to block 1
+        app.advance_block();
+
+        Ok(app)
+    }
+
+    /// The block in which messages are currently processed.
+    pub fn block(&self) -> &BlockInfo {
+        &self.block
+    }
+
+    /// The chain's current config.
+    pub fn config(&self) -> AppResult<Config> {
+        CONFIG.load(&self.store).map_err(Into::into)
+    }
+
+    /// Update the chain's config, bypassing the owner check.
+    pub fn update_config(&mut self, update: impl FnOnce(&mut Config)) -> AppResult<()> {
+        let mut cfg = self.config()?;
+        update(&mut cfg);
+        CONFIG.save(&mut self.store, &cfg).map_err(Into::into)
+    }
+
+    /// Register a native contract, and upload its code. Return the code hash,
+    /// which can then be used to instantiate the contract.
+    pub fn upload(&mut self, uploader: &Addr, contract: NativeContract) -> AppResult<Hash> {
+        let code = self.vm.register(contract);
+        let code_hash = hash(&code);
+
+        self.process_msg(uploader, Message::StoreCode {
+            wasm_byte_code: code,
+        })?;
+
+        Ok(code_hash)
+    }
+
+    /// Instantiate a contract. Return its address, and the events emitted.
+    pub fn instantiate<M: Serialize>(
+        &mut self,
+        sender:    &Addr,
+        code_hash: &Hash,
+        msg:       &M,
+        salt:      impl Into<Binary>,
+        funds:     Coins,
+        admin:     Option<Addr>,
+    ) -> AppResult<(Addr, Vec<Event>)> {
+        let salt = salt.into();
+        let address = Addr::compute(sender, code_hash, &salt);
+
+        let events = self.process_msg(sender, Message::Instantiate {
+            code_hash: code_hash.clone(),
+            msg: to_json(msg)?,
+            salt,
+            funds,
+            admin,
+        })?;
+
+        Ok((address, events))
+    }
+
+    pub fn execute<M: Serialize>(
+        &mut self,
+        sender:   &Addr,
+        contract: &Addr,
+        msg:      &M,
+        funds:    Coins,
+    ) -> AppResult<Vec<Event>> {
+        self.process_msg(sender, Message::Execute {
+            contract: contract.clone(),
+            msg: to_json(msg)?,
+            funds,
+        })
+    }
+
+    pub fn transfer(&mut self, sender: &Addr, to: &Addr, coins: Coins) -> AppResult<Vec<Event>> {
+        self.process_msg(sender, Message::Transfer {
+            to: to.clone(),
+            coins,
+        })
+    }
+
+    /// Process a message on behalf of the sender, without going through
+    /// transaction authentication or fee handling. If it fails, state changes
+    /// it made are discarded.
+    pub fn process_msg(&mut self, sender: &Addr, msg: Message) -> AppResult<Vec<Event>> {
+        let cached = SharedStore::new(CacheStore::new(self.store.share(), None));
+
+        let events = process_msg(
+            cached.share(),
+            self.vm.clone(),
+            &self.block,
+            GasTracker::new_limitless(),
+            0,
+            sender,
+            msg,
+        )?;
+
+        cached.disassemble().consume();
+
+        Ok(events)
+    }
+
+    /// Run a transaction, the same way it would be in `FinalizeBlock`: the
+    /// sender account authenticates it, and the taxman (if any) handles fees.
+    pub fn execute_tx(&mut self, tx: Tx) -> TxOutcome {
+        run_tx(self.store.share(), self.vm.clone(), &self.block, tx, false)
+    }
+
+    pub fn query(&self, req: QueryRequest) -> AppResult<QueryResponse> {
+        process_query(
+            self.store.share(),
+            self.vm.clone(),
+            &self.block,
+            GasTracker::new_limitless(),
+            req,
+        )
+    }
+
+    pub fn query_balance(&self, address: &Addr, denom: impl Into<String>) -> AppResult<Uint128> {
+        self.query(QueryRequest::Balance {
+            address: address.clone(),
+            denom:   denom.into(),
+        })
+        .map(|res| res.as_balance().amount)
+    }
+
+    pub fn query_wasm_smart<M, R>(&self, contract: &Addr, msg: &M) -> AppResult<R>
+    where
+        M: Serialize,
+        R: DeserializeOwned,
+    {
+        let res = self.query(QueryRequest::WasmSmart {
+            contract: contract.clone(),
+            msg:      to_json(msg)?,
+        })?;
+
+        from_json(res.as_wasm_smart().data).map_err(Into::into)
+    }
+
+    /// End the current block, and start the next one, which is five seconds
+    /// later. Return the events emitted by the end blockers of the current
+    /// block and the begin blockers of the next one.
+    ///
+    /// Same as on the real chain, a cronjob that fails doesn't halt the chain;
+    /// its state changes are discarded, and it doesn't emit any event.
+    pub fn advance_block(&mut self) -> Vec<Event> {
+        let mut events = vec![];
+
+        // the block height is zero only during genesis, when cronjobs aren't
+        // called
+        if self.block.height.u64() > 0 {
+            events.extend(
+                call_end_blockers(self.store.share(), self.vm.clone(), &self.block)
+                    .expect("failed to call end blockers"),
+            );
+        }
+
+        LAST_FINALIZED_BLOCK
+            .save(&mut self.store, &self.block)
+            .expect("failed to save last finalized block");
+
+        let height = self.block.height.u64() + 1;
+        self.block = BlockInfo {
+            height:    Uint64::new(height),
+            timestamp: self.block.timestamp.plus_seconds(BLOCK_TIME_SECONDS),
+            hash:      hash(height.to_be_bytes()),
+        };
+
+        events.extend(
+            call_begin_blockers(self.store.share(), self.vm.clone(), &self.block)
+                .expect("failed to call begin blockers"),
+        );
+
+        events
+    }
+
+    /// Advance the given number of blocks. Return the events emitted by the
+    /// cronjobs.
+    pub fn advance_blocks(&mut self, num_blocks: u64) -> Vec<Event> {
+        (0..num_blocks).flat_map(|_| self.advance_block()).collect()
+    }
+}
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::assert_event,
+        cw_app::{AppError, DEFAULT_MAX_MSG_DEPTH, MAX_MSG_DEPTH, MAX_QUERY_DEPTH},
+        cw_std::{
+            cw_serde, AfterBlockCtx, BeforeBlockCtx, CodedError, Coin, Cronjob, Empty, ExecuteCtx,
+            InstantiateCtx, Item, QueryCtx, ReceiveCtx, Response, StdResult,
+        },
+    };
+
+    const COUNT: Item<u32> = Item::new("count");
+
+    #[cw_serde]
+    enum ExecuteMsg {
+        /// Send the funds received to the given address.
+        Forward {
+            to: Addr,
+        },
+        /// Execute the contract itself with `times - 1`, until it's zero.
+        Recurse {
+            times: u32,
+        },
+    }
+
+    #[cw_serde]
+    enum QueryMsg {
+        /// The contract's own balance, queried from the bank.
+        Balance {
+            denom: String,
+        },
+        /// The number of times `before_block` has been called.
+        Count {},
+        /// Make the same query to the contract itself, endlessly.
+        Recurse {},
+    }
+
+    fn instantiate(_ctx: InstantiateCtx, _msg: Empty) -> StdResult<Response> {
+        Ok(Response::new())
+    }
+
+    fn execute(ctx: ExecuteCtx, msg: ExecuteMsg) -> StdResult<Response> {
+        match msg {
+            ExecuteMsg::Forward {
+                to,
+            } => Ok(Response::new().add_message(Message::Transfer {
+                to,
+                coins: ctx.funds,
+            })),
+            ExecuteMsg::Recurse {
+                times: 0,
+            } => Ok(Response::new()),
+            ExecuteMsg::Recurse {
+                times,
+            } => Ok(Response::new().add_message(Message::Execute {
+                contract: ctx.contract,
+                msg:      to_json(&ExecuteMsg::Recurse {
+                    times: times - 1,
+                })?,
+                funds:    Coins::new_empty(),
+            })),
+        }
+    }
+
+    fn receive(_ctx: ReceiveCtx) -> StdResult<Response> {
+        Ok(Response::new())
+    }
+
+    fn before_block(ctx: BeforeBlockCtx) -> StdResult<Response> {
+        let count = COUNT.may_load(ctx.store)?.unwrap_or(0);
+        COUNT.save(ctx.store, &(count + 1))?;
+        Ok(Response::new())
+    }
+
+    // increments the count, but then fails, so the increment is to be discarded
+    fn after_block(ctx: AfterBlockCtx) -> StdResult<Response> {
+        let count = COUNT.may_load(ctx.store)?.unwrap_or(0);
+        COUNT.save(ctx.store, &(count + 1))?;
+        // this item is never saved, so loading it fails
+        Item::<u32>::new("missing").load(ctx.store)?;
+        Ok(Response::new())
+    }
+
+    fn query(ctx: QueryCtx, msg: QueryMsg) -> StdResult<Binary> {
+        match msg {
+            QueryMsg::Balance {
+                denom,
+            } => to_json(&ctx.query_balance(ctx.contract.clone(), denom)?),
+            QueryMsg::Count {} => to_json(&COUNT.may_load(ctx.store)?.unwrap_or(0)),
+            QueryMsg::Recurse {} => {
+                let contract = ctx.contract.clone();
+                let res: Binary = ctx.query_wasm_smart(contract, &QueryMsg::Recurse {})?;
+                to_json(&res)
+            },
+        }
+    }
+
+    fn setup() -> AppResult<(TestApp, Addr, Addr, Addr)> {
+        let alice = Addr::mock(1);
+        let mut app = TestApp::new(vec![Balance {
+            address: alice.clone(),
+            coins:   Coin::new("uatom", 100).into(),
+        }])?;
+
+        let code_hash = app.upload(
+            &alice,
+            ContractBuilder::new(instantiate)
+                .with_execute(execute)
+                .with_receive(receive)
+                .with_before_block(before_block)
+                .with_after_block(after_block)
+                .with_query(query)
+                .build(),
+        )?;
+        let (vault1, _) = app.instantiate(
+            &alice,
+            &code_hash,
+            &Empty {},
+            b"vault1".to_vec(),
+            Coins::new_empty(),
+            None,
+        )?;
+        let (vault2, _) = app.instantiate(
+            &alice,
+            &code_hash,
+            &Empty {},
+            b"vault2".to_vec(),
+            Coins::new_empty(),
+            None,
+        )?;
+
+        Ok((app, alice, vault1, vault2))
+    }
+
+    #[test]
+    fn forwarding_funds_works() -> AppResult<()> {
+        let (mut app, alice, vault1, vault2) = setup()?;
+
+        let funds = Coins::from(Coin::new("uatom", 30));
+        let events = app.execute(&alice, &vault1, &ExecuteMsg::Forward {
+            to: vault2.clone(),
+        }, funds.clone())?;
+        assert_event(&events, "transfer", &[
+            ("from", &vault1.to_string()),
+            ("to", &vault2.to_string()),
+            ("coins", &funds.to_string()),
+        ]);
+
+        assert_eq!(app.query_balance(&alice, "uatom")?, Uint128::new(70));
+        assert_eq!(app.query_balance(&vault1, "uatom")?, Uint128::new(0));
+        // the contract queries its own balance from the bank
+        let balance: Uint128 = app.query_wasm_smart(&vault2, &QueryMsg::Balance {
+            denom: "uatom".into(),
+        })?;
+        assert_eq!(balance, Uint128::new(30));
+
+        // alice can't afford this. the transfer into vault1 must be reverted
+        // along with the rest of the message
+        let res = app.execute(&alice, &vault1, &ExecuteMsg::Forward {
+            to: vault2.clone(),
+        }, Coin::new("uatom", 71).into());
+        assert!(res.is_err());
+        assert_eq!(app.query_balance(&alice, "uatom")?, Uint128::new(70));
+        assert_eq!(app.query_balance(&vault1, "uatom")?, Uint128::new(0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn advancing_blocks_works() -> AppResult<()> {
+        let (mut app, _, vault1, _) = setup()?;
+
+        app.update_config(|cfg| {
+            cfg.begin_blockers.push(Cronjob {
+                contract: vault1.clone(),
+                interval: Uint64::new(2),
+            });
+        })?;
+
+        // blocks 2, 3, 4, 5; the cronjob is due at 2 and 4
+        let events = app.advance_blocks(4);
+        assert_eq!(app.block().height, Uint64::new(5));
+        assert_eq!(events.len(), 2);
+        assert_event(&events, "before_block", &[("_contract_address", &vault1.to_string())]);
+
+        let count: u32 = app.query_wasm_smart(&vault1, &QueryMsg::Count {})?;
+        assert_eq!(count, 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn failing_cronjobs_are_skipped() -> AppResult<()> {
+        let (mut app, _, vault1, vault2) = setup()?;
+
+        app.update_config(|cfg| {
+            cfg.begin_blockers.push(Cronjob {
+                contract: vault1.clone(),
+                interval: Uint64::new(1),
+            });
+            cfg.end_blockers.push(Cronjob {
+                contract: vault2.clone(),
+                interval: Uint64::new(1),
+            });
+        })?;
+
+        // the end blocker fails at blocks 1, 2, 3. it doesn't emit any event,
+        // its state changes are discarded, and the begin blocker at blocks 2,
+        // 3, 4 isn't affected
+        let events = app.advance_blocks(3);
+        assert_eq!(events.len(), 3);
+        assert!(events.iter().all(|event| event.r#type == "before_block"));
+
+        let count: u32 = app.query_wasm_smart(&vault1, &QueryMsg::Count {})?;
+        assert_eq!(count, 3);
+        let count: u32 = app.query_wasm_smart(&vault2, &QueryMsg::Count {})?;
+        assert_eq!(count, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn recursive_queries_are_limited() -> AppResult<()> {
+        let (app, _, vault1, _) = setup()?;
+
+        // the error of the innermost query is forwarded by each of the
+        // contracts, and keeps its codespace and code
+        let err = app.query_wasm_smart::<_, Binary>(&vault1, &QueryMsg::Recurse {}).unwrap_err();
+        let expect = AppError::query_depth_exceeded(MAX_QUERY_DEPTH);
+        assert_eq!(err.codespace(), expect.codespace());
+        assert_eq!(err.code(), expect.code());
+
+        // the depth is restored once the queries are done, so the contract can
+        // still make queries
+        let balance: Uint128 = app.query_wasm_smart(&vault1, &QueryMsg::Balance {
+            denom: "uatom".into(),
+        })?;
+        assert_eq!(balance, Uint128::new(0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn message_depth_is_limited() -> AppResult<()> {
+        let (mut app, alice, vault1, _) = setup()?;
+
+        let recurse = |app: &mut TestApp, times| {
+            app.execute(&alice, &vault1, &ExecuteMsg::Recurse { times }, Coins::new_empty())
+        };
+
+        // the message sent by alice is at depth zero, so the deepest submessage
+        // is at the same depth as the number of times
+        recurse(&mut app, DEFAULT_MAX_MSG_DEPTH)?;
+
+        let err = recurse(&mut app, DEFAULT_MAX_MSG_DEPTH + 1).unwrap_err();
+        assert!(matches!(err, AppError::MaxDepthExceeded { max } if max == DEFAULT_MAX_MSG_DEPTH));
+
+        // the limit can be changed in the config
+        app.update_config(|cfg| cfg.max_msg_depth = Some(2))?;
+        recurse(&mut app, 2)?;
+
+        let err = recurse(&mut app, 3).unwrap_err();
+        assert!(matches!(err, AppError::MaxDepthExceeded { max: 2 }));
+
+        // but not beyond the hard maximum
+        app.update_config(|cfg| cfg.max_msg_depth = Some(u32::MAX))?;
+        recurse(&mut app, MAX_MSG_DEPTH)?;
+
+        let err = recurse(&mut app, MAX_MSG_DEPTH + 1).unwrap_err();
+        assert!(matches!(err, AppError::MaxDepthExceeded { max } if max == MAX_MSG_DEPTH));
+
+        Ok(())
+    }
+
+    #[test]
+    fn unauthenticated_txs_pay_no_fee() -> AppResult<()> {
+        let (mut app, alice, ..) = setup()?;
+
+        // a taxman that charges 1 uatom per unit of gas
+        let code_hash = app.upload(
+            &alice,
+            ContractBuilder::new(cw_taxman::instantiate)
+                .with_receive(cw_taxman::receive)
+                .with_withhold_fee(cw_taxman::withhold_fee)
+                .with_finalize_fee(cw_taxman::finalize_fee)
+                .build(),
+        )?;
+        let (taxman, _) = app.instantiate(
+            &alice,
+            &code_hash,
+            &cw_taxman::InstantiateMsg {
+                config: cw_taxman::FeeConfig {
+                    fee_denom: "uatom".into(),
+                    fee_rate:  Uint128::new(1),
+                },
+            },
+            b"taxman".to_vec(),
+            Coins::new_empty(),
+            None,
+        )?;
+        app.update_config(|cfg| cfg.taxman = Some(taxman.clone()))?;
+
+        // an account whose public key no one has the private key of
+        let code_hash = app.upload(
+            &alice,
+            ContractBuilder::new(cw_account::instantiate)
+                .with_receive(cw_account::receive)
+                .with_before_tx(cw_account::before_tx)
+                .build(),
+        )?;
+        let (account, _) = app.instantiate(
+            &alice,
+            &code_hash,
+            &cw_account::InstantiateMsg {
+                pubkey: cw_account::PubKey::Secp256k1(vec![2; 33].into()),
+            },
+            b"account".to_vec(),
+            Coin::new("uatom", 50).into(),
+            None,
+        )?;
+
+        // a tx with a forged credential fails authentication. the fee withheld
+        // from the sender before that must be discarded as well
+        let outcome = app.execute_tx(Tx {
+            sender:     account.clone(),
+            gas_limit:  Uint64::new(10),
+            msgs:       vec![],
+            credential: vec![0; 64].into(),
+        });
+        assert!(outcome.result.is_err());
+        assert_eq!(app.query_balance(&account, "uatom")?, Uint128::new(50));
+        assert_eq!(app.query_balance(&taxman, "uatom")?, Uint128::new(0));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,252 @@
+use {
+    cw_std::{
+        from_json, AfterBlockCtx, BankQuery, BankQueryResponse, BeforeBlockCtx, BeforeTxCtx,
+        Binary, ExecuteCtx, GenericError, GenericResult, InstantiateCtx, MigrateCtx, QueryCtx,
+        ReceiveCtx, ReplyCtx, Response, TaxmanCtx, TransferCtx, TransferMsg, Tx, TxOutcome,
+    },
+    serde::de::DeserializeOwned,
+};
+
+// the entry points, with the messages still in raw bytes. a message that fails
+// to deserialize is returned as an error, same as in `cw_std::do_*`.
+pub(crate) type InstantiateFn = Box<dyn Fn(InstantiateCtx, &[u8]) -> GenericResult<Response>>;
+pub(crate) type ExecuteFn = Box<dyn Fn(ExecuteCtx, &[u8]) -> GenericResult<Response>>;
+pub(crate) type QueryFn = Box<dyn Fn(QueryCtx, &[u8]) -> GenericResult<Binary>>;
+pub(crate) type MigrateFn = Box<dyn Fn(MigrateCtx, &[u8]) -> GenericResult<Response>>;
+pub(crate) type ReplyFn = Box<dyn Fn(ReplyCtx, &[u8]) -> GenericResult<Response>>;
+pub(crate) type ReceiveFn = Box<dyn Fn(ReceiveCtx) -> GenericResult<Response>>;
+pub(crate) type BeforeTxFn = Box<dyn Fn(BeforeTxCtx, &Tx) -> GenericResult<Response>>;
+pub(crate) type BeforeBlockFn = Box<dyn Fn(BeforeBlockCtx) -> GenericResult<Response>>;
+pub(crate) type AfterBlockFn = Box<dyn Fn(AfterBlockCtx) -> GenericResult<Response>>;
+pub(crate) type TransferFn = Box<dyn Fn(TransferCtx, &TransferMsg) -> GenericResult<Response>>;
+pub(crate) type QueryBankFn =
+    Box<dyn Fn(QueryCtx, &BankQuery) -> GenericResult<BankQueryResponse>>;
+pub(crate) type WithholdFeeFn = Box<dyn Fn(TaxmanCtx, &Tx) -> GenericResult<Response>>;
+pub(crate) type FinalizeFeeFn =
+    Box<dyn Fn(TaxmanCtx, &Tx, &TxOutcome) -> GenericResult<Response>>;
+
+/// A contract compiled natively instead of to Wasm, consisting of the Rust
+/// functions that serve as its entry points. Create one using `ContractBuilder`.
+pub struct NativeContract {
+    pub(crate) instantiate:  InstantiateFn,
+    pub(crate) execute:      Option<ExecuteFn>,
+    pub(crate) query:        Option<QueryFn>,
+    pub(crate) migrate:      Option<MigrateFn>,
+    pub(crate) reply:        Option<ReplyFn>,
+    pub(crate) receive:      Option<ReceiveFn>,
+    pub(crate) before_tx:    Option<BeforeTxFn>,
+    pub(crate) before_block: Option<BeforeBlockFn>,
+    pub(crate) after_block:  Option<AfterBlockFn>,
+    pub(crate) transfer:     Option<TransferFn>,
+    pub(crate) query_bank:   Option<QueryBankFn>,
+    pub(crate) withhold_fee: Option<WithholdFeeFn>,
+    pub(crate) finalize_fee: Option<FinalizeFeeFn>,
+}
+
+/// Assembles a `NativeContract` from the contract's entry point functions,
+/// i.e. the ones marked with `#[entry_point]` when compiled to Wasm.
+///
+/// Every contract must have an `instantiate` entry point. The others are
+/// optional; calling one that isn't provided results in an error, same as
+/// calling a Wasm contract that doesn't export it.
+///
+/// ```ignore
+/// let bank = ContractBuilder::new(cw_bank::instantiate)
+///     .with_execute(cw_bank::execute)
+///     .with_transfer(cw_bank::transfer)
+///     .with_receive(cw_bank::receive)
+///     .with_query_bank(cw_bank::query_bank)
+///     .build();
+/// ```
+pub struct ContractBuilder {
+    contract: NativeContract,
+}
+
+impl ContractBuilder {
+    pub fn new<M, E>(instantiate_fn: fn(InstantiateCtx, M) -> Result<Response, E>) -> Self
+    where
+        M: DeserializeOwned + 'static,
+        E: Into<GenericError> + 'static,
+    {
+        Self {
+            contract: NativeContract {
+                instantiate:  Box::new(move |ctx, msg| {
+                    with_msg(msg, |msg| instantiate_fn(ctx, msg).into())
+                }),
+                execute:      None,
+                query:        None,
+                migrate:      None,
+                reply:        None,
+                receive:      None,
+                before_tx:    None,
+                before_block: None,
+                after_block:  None,
+                transfer:     None,
+                query_bank:   None,
+                withhold_fee: None,
+                finalize_fee: None,
+            },
+        }
+    }
+
+    pub fn with_execute<M, E>(
+        mut self,
+        execute_fn: fn(ExecuteCtx, M) -> Result<Response, E>,
+    ) -> Self
+    where
+        M: DeserializeOwned + 'static,
+        E: Into<GenericError> + 'static,
+    {
+        self.contract.execute = Some(Box::new(move |ctx, msg| {
+            with_msg(msg, |msg| execute_fn(ctx, msg).into())
+        }));
+        self
+    }
+
+    pub fn with_query<M, E>(mut self, query_fn: fn(QueryCtx, M) -> Result<Binary, E>) -> Self
+    where
+        M: DeserializeOwned + 'static,
+        E: Into<GenericError> + 'static,
+    {
+        self.contract.query = Some(Box::new(move |ctx, msg| {
+            with_msg(msg, |msg| query_fn(ctx, msg).into())
+        }));
+        self
+    }
+
+    pub fn with_migrate<M, E>(
+        mut self,
+        migrate_fn: fn(MigrateCtx, M) -> Result<Response, E>,
+    ) -> Self
+    where
+        M: DeserializeOwned + 'static,
+        E: Into<GenericError> + 'static,
+    {
+        self.contract.migrate = Some(Box::new(move |ctx, msg| {
+            with_msg(msg, |msg| migrate_fn(ctx, msg).into())
+        }));
+        self
+    }
+
+    pub fn with_reply<M, E>(mut self, reply_fn: fn(ReplyCtx, M) -> Result<Response, E>) -> Self
+    where
+        M: DeserializeOwned + 'static,
+        E: Into<GenericError> + 'static,
+    {
+        self.contract.reply = Some(Box::new(move |ctx, msg| {
+            with_msg(msg, |msg| reply_fn(ctx, msg).into())
+        }));
+        self
+    }
+
+    pub fn with_receive<E>(mut self, receive_fn: fn(ReceiveCtx) -> Result<Response, E>) -> Self
+    where
+        E: Into<GenericError> + 'static,
+    {
+        self.contract.receive = Some(Box::new(move |ctx| receive_fn(ctx).into()));
+        self
+    }
+
+    pub fn with_before_tx<E>(
+        mut self,
+        before_tx_fn: fn(BeforeTxCtx, Tx) -> Result<Response, E>,
+    ) -> Self
+    where
+        E: Into<GenericError> + 'static,
+    {
+        self.contract.before_tx = Some(Box::new(move |ctx, tx| {
+            before_tx_fn(ctx, tx.clone()).into()
+        }));
+        self
+    }
+
+    pub fn with_before_block<E>(
+        mut self,
+        before_block_fn: fn(BeforeBlockCtx) -> Result<Response, E>,
+    ) -> Self
+    where
+        E: Into<GenericError> + 'static,
+    {
+        self.contract.before_block = Some(Box::new(move |ctx| before_block_fn(ctx).into()));
+        self
+    }
+
+    pub fn with_after_block<E>(
+        mut self,
+        after_block_fn: fn(AfterBlockCtx) -> Result<Response, E>,
+    ) -> Self
+    where
+        E: Into<GenericError> + 'static,
+    {
+        self.contract.after_block = Some(Box::new(move |ctx| after_block_fn(ctx).into()));
+        self
+    }
+
+    pub fn with_transfer<E>(
+        mut self,
+        transfer_fn: fn(TransferCtx, TransferMsg) -> Result<Response, E>,
+    ) -> Self
+    where
+        E: Into<GenericError> + 'static,
+    {
+        self.contract.transfer = Some(Box::new(move |ctx, msg| {
+            transfer_fn(ctx, msg.clone()).into()
+        }));
+        self
+    }
+
+    pub fn with_query_bank<E>(
+        mut self,
+        query_bank_fn: fn(QueryCtx, BankQuery) -> Result<BankQueryResponse, E>,
+    ) -> Self
+    where
+        E: Into<GenericError> + 'static,
+    {
+        self.contract.query_bank = Some(Box::new(move |ctx, msg| {
+            query_bank_fn(ctx, msg.clone()).into()
+        }));
+        self
+    }
+
+    pub fn with_withhold_fee<E>(
+        mut self,
+        withhold_fee_fn: fn(TaxmanCtx, Tx) -> Result<Response, E>,
+    ) -> Self
+    where
+        E: Into<GenericError> + 'static,
+    {
+        self.contract.withhold_fee = Some(Box::new(move |ctx, tx| {
+            withhold_fee_fn(ctx, tx.clone()).into()
+        }));
+        self
+    }
+
+    pub fn with_finalize_fee<E>(
+        mut self,
+        finalize_fee_fn: fn(TaxmanCtx, Tx, TxOutcome) -> Result<Response, E>,
+    ) -> Self
+    where
+        E: Into<GenericError> + 'static,
+    {
+        self.contract.finalize_fee = Some(Box::new(move |ctx, tx, outcome| {
+            finalize_fee_fn(ctx, tx.clone(), outcome.clone()).into()
+        }));
+        self
+    }
+
+    pub fn build(self) -> NativeContract {
+        self.contract
+    }
+}
+
+/// Deserialize the message, and if successful, call the entry point with it.
+fn with_msg<M, T, F>(msg_bytes: &[u8], call: F) -> GenericResult<T>
+where
+    M: DeserializeOwned,
+    F: FnOnce(M) -> GenericResult<T>,
+{
+    match from_json(msg_bytes) {
+        Ok(msg) => call(msg),
+        Err(err) => GenericResult::Err(err.into()),
+    }
+}
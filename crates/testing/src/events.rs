@@ -0,0 +1,22 @@
+use cw_std::Event;
+
+/// Whether any of the events is of the given type, and has all the given
+/// attributes. The event may have other attributes besides these.
+pub fn has_event(events: &[Event], ty: &str, attrs: &[(&str, &str)]) -> bool {
+    events.iter().any(|event| {
+        event.r#type == ty
+            && attrs.iter().all(|(key, value)| {
+                event.attributes.iter().any(|attr| attr.key == *key && attr.value == *value)
+            })
+    })
+}
+
+/// Assert that any of the events is of the given type, and has all the given
+/// attributes. Print the events on failure.
+#[track_caller]
+pub fn assert_event(events: &[Event], ty: &str, attrs: &[(&str, &str)]) {
+    assert!(
+        has_event(events, ty, attrs),
+        "no `{ty}` event with attributes {attrs:?} was found in events: {events:#?}"
+    );
+}
@@ -0,0 +1,11 @@
+mod app;
+mod contract;
+mod events;
+mod vm;
+
+pub use crate::{
+    app::TestApp,
+    contract::{ContractBuilder, NativeContract},
+    events::{assert_event, has_event},
+    vm::NativeVm,
+};
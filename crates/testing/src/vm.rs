@@ -0,0 +1,436 @@
+use {
+    crate::NativeContract,
+    cw_app::{AppError, AppResult, Instance, Querier, Vm, CONTRACT_NAMESPACE},
+    cw_db::prefixed_bounds,
+    cw_std::{
+        concat, hash, testing::MockApi, trim, AfterBlockCtx, Addr, BankQuery, BankQueryResponse,
+        BeforeBlockCtx, BeforeTxCtx, Binary, BlockInfo, Context, ExecuteCtx, GenericResult, Hash,
+        InstantiateCtx, MigrateCtx, Order, QueryCtx, ReceiveCtx, Record, ReplyCtx, Response,
+        StdError, Storage, TaxmanCtx, TransferCtx, TransferMsg, Tx, TxOutcome,
+    },
+    cw_vm::GasTracker,
+    std::{cell::RefCell, collections::HashMap, rc::Rc},
+};
+
+// ------------------------------------ vm -------------------------------------
+
+/// A `Vm` that runs contracts compiled natively.
+///
+/// Contracts are registered with the VM, which gives each of them a unique
+/// placeholder byte code. Uploading this code to the chain with a `StoreCode`
+/// message makes the contract available under the code's hash, same as a Wasm
+/// contract would be.
+#[derive(Default, Clone)]
+pub struct NativeVm {
+    contracts: Rc<RefCell<HashMap<Hash, Rc<NativeContract>>>>,
+}
+
+impl NativeVm {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a contract, and return the byte code to be uploaded for it.
+    pub fn register(&self, contract: NativeContract) -> Binary {
+        let mut contracts = self.contracts.borrow_mut();
+        let code = format!("cw-testing/native-contract/{}", contracts.len()).into_bytes();
+        contracts.insert(hash(&code), Rc::new(contract));
+        code.into()
+    }
+}
+
+impl Vm for NativeVm {
+    fn validate_code(&self, code: &[u8]) -> AppResult<()> {
+        let code_hash = hash(code);
+        if !self.contracts.borrow().contains_key(&code_hash) {
+            return Err(contract_not_found(&code_hash));
+        }
+
+        Ok(())
+    }
+
+    fn build_instance<S>(
+        &self,
+        store:       S,
+        block:       &BlockInfo,
+        gas_tracker: GasTracker,
+        contract:    &Addr,
+        code_hash:   &Hash,
+    ) -> AppResult<Box<dyn Instance>>
+    where
+        S: Storage + Clone + 'static,
+    {
+        let Some(native_contract) = self.contracts.borrow().get(code_hash).cloned() else {
+            return Err(contract_not_found(code_hash));
+        };
+
+        Ok(Box::new(NativeInstance {
+            contract: native_contract,
+            substore: ContractStore::new(store.clone(), contract),
+            querier:  Querier::new(store, self.clone(), block.clone(), gas_tracker),
+        }))
+    }
+}
+
+// --------------------------------- instance ----------------------------------
+
+struct NativeInstance<S> {
+    contract: Rc<NativeContract>,
+    substore: ContractStore<S>,
+    querier:  Querier<S, NativeVm>,
+}
+
+impl<S: Storage + Clone + 'static> Instance for NativeInstance<S> {
+    fn call_instantiate(
+        &mut self,
+        ctx: &Context,
+        msg: &[u8],
+    ) -> AppResult<GenericResult<Response>> {
+        let ctx = InstantiateCtx {
+            store:           &mut self.substore,
+            api:             &MockApi,
+            querier:         &self.querier,
+            chain_id:        ctx.chain_id.clone(),
+            block_height:    ctx.block_height,
+            block_timestamp: ctx.block_timestamp,
+            block_hash:      ctx.block_hash.clone(),
+            contract:        ctx.contract.clone(),
+            sender:          ctx.sender.clone().expect("host failed to provide a sender"),
+            funds:           ctx.funds.clone().expect("host failed to provide funds"),
+        };
+
+        Ok((self.contract.instantiate)(ctx, msg))
+    }
+
+    fn call_execute(
+        &mut self,
+        ctx: &Context,
+        msg: &[u8],
+    ) -> AppResult<GenericResult<Response>> {
+        let Some(execute_fn) = &self.contract.execute else {
+            return Err(missing_entry_point("execute"));
+        };
+        let ctx = ExecuteCtx {
+            store:           &mut self.substore,
+            api:             &MockApi,
+            querier:         &self.querier,
+            chain_id:        ctx.chain_id.clone(),
+            block_height:    ctx.block_height,
+            block_timestamp: ctx.block_timestamp,
+            block_hash:      ctx.block_hash.clone(),
+            contract:        ctx.contract.clone(),
+            sender:          ctx.sender.clone().expect("host failed to provide a sender"),
+            funds:           ctx.funds.clone().expect("host failed to provide funds"),
+        };
+
+        Ok(execute_fn(ctx, msg))
+    }
+
+    fn call_query(
+        &mut self,
+        ctx: &Context,
+        msg: &[u8],
+    ) -> AppResult<GenericResult<Binary>> {
+        let Some(query_fn) = &self.contract.query else {
+            return Err(missing_entry_point("query"));
+        };
+        let ctx = QueryCtx {
+            store:           &self.substore,
+            api:             &MockApi,
+            querier:         &self.querier,
+            chain_id:        ctx.chain_id.clone(),
+            block_height:    ctx.block_height,
+            block_timestamp: ctx.block_timestamp,
+            block_hash:      ctx.block_hash.clone(),
+            contract:        ctx.contract.clone(),
+        };
+
+        Ok(query_fn(ctx, msg))
+    }
+
+    fn call_migrate(
+        &mut self,
+        ctx: &Context,
+        msg: &[u8],
+    ) -> AppResult<GenericResult<Response>> {
+        let Some(migrate_fn) = &self.contract.migrate else {
+            return Err(missing_entry_point("migrate"));
+        };
+        let ctx = MigrateCtx {
+            store:           &mut self.substore,
+            api:             &MockApi,
+            querier:         &self.querier,
+            chain_id:        ctx.chain_id.clone(),
+            block_height:    ctx.block_height,
+            block_timestamp: ctx.block_timestamp,
+            block_hash:      ctx.block_hash.clone(),
+            contract:        ctx.contract.clone(),
+            sender:          ctx.sender.clone().expect("host failed to provide a sender"),
+        };
+
+        Ok(migrate_fn(ctx, msg))
+    }
+
+    fn call_reply(
+        &mut self,
+        ctx: &Context,
+        msg: &[u8],
+    ) -> AppResult<GenericResult<Response>> {
+        let Some(reply_fn) = &self.contract.reply else {
+            return Err(missing_entry_point("reply"));
+        };
+        let submsg_result =
+            ctx.submsg_result.clone().expect("host failed to specify submsg result");
+        let ctx = ReplyCtx {
+            store:           &mut self.substore,
+            api:             &MockApi,
+            querier:         &self.querier,
+            chain_id:        ctx.chain_id.clone(),
+            block_height:    ctx.block_height,
+            block_timestamp: ctx.block_timestamp,
+            block_hash:      ctx.block_hash.clone(),
+            contract:        ctx.contract.clone(),
+            submsg_result,
+        };
+
+        Ok(reply_fn(ctx, msg))
+    }
+
+    fn call_receive(&mut self, ctx: &Context) -> AppResult<GenericResult<Response>> {
+        let Some(receive_fn) = &self.contract.receive else {
+            return Err(missing_entry_point("receive"));
+        };
+        let ctx = ReceiveCtx {
+            store:           &mut self.substore,
+            api:             &MockApi,
+            querier:         &self.querier,
+            chain_id:        ctx.chain_id.clone(),
+            block_height:    ctx.block_height,
+            block_timestamp: ctx.block_timestamp,
+            block_hash:      ctx.block_hash.clone(),
+            contract:        ctx.contract.clone(),
+            sender:          ctx.sender.clone().expect("host failed to specify sender"),
+            funds:           ctx.funds.clone().expect("host failed to specify funds"),
+        };
+
+        Ok(receive_fn(ctx))
+    }
+
+    fn call_before_tx(
+        &mut self,
+        ctx: &Context,
+        tx:  &Tx,
+    ) -> AppResult<GenericResult<Response>> {
+        let Some(before_tx_fn) = &self.contract.before_tx else {
+            return Err(missing_entry_point("before_tx"));
+        };
+        let simulate = ctx.simulate.expect("host failed to specify whether it's simulation mode");
+        let ctx = BeforeTxCtx {
+            store:           &mut self.substore,
+            api:             &MockApi,
+            querier:         &self.querier,
+            chain_id:        ctx.chain_id.clone(),
+            block_height:    ctx.block_height,
+            block_timestamp: ctx.block_timestamp,
+            block_hash:      ctx.block_hash.clone(),
+            contract:        ctx.contract.clone(),
+            simulate,
+        };
+
+        Ok(before_tx_fn(ctx, tx))
+    }
+
+    fn call_before_block(&mut self, ctx: &Context) -> AppResult<GenericResult<Response>> {
+        let Some(before_block_fn) = &self.contract.before_block else {
+            return Err(missing_entry_point("before_block"));
+        };
+        let ctx = BeforeBlockCtx {
+            store:           &mut self.substore,
+            api:             &MockApi,
+            querier:         &self.querier,
+            chain_id:        ctx.chain_id.clone(),
+            block_height:    ctx.block_height,
+            block_timestamp: ctx.block_timestamp,
+            block_hash:      ctx.block_hash.clone(),
+            contract:        ctx.contract.clone(),
+        };
+
+        Ok(before_block_fn(ctx))
+    }
+
+    fn call_after_block(&mut self, ctx: &Context) -> AppResult<GenericResult<Response>> {
+        let Some(after_block_fn) = &self.contract.after_block else {
+            return Err(missing_entry_point("after_block"));
+        };
+        let ctx = AfterBlockCtx {
+            store:           &mut self.substore,
+            api:             &MockApi,
+            querier:         &self.querier,
+            chain_id:        ctx.chain_id.clone(),
+            block_height:    ctx.block_height,
+            block_timestamp: ctx.block_timestamp,
+            block_hash:      ctx.block_hash.clone(),
+            contract:        ctx.contract.clone(),
+        };
+
+        Ok(after_block_fn(ctx))
+    }
+
+    fn call_transfer(
+        &mut self,
+        ctx: &Context,
+        msg: &TransferMsg,
+    ) -> AppResult<GenericResult<Response>> {
+        let Some(transfer_fn) = &self.contract.transfer else {
+            return Err(missing_entry_point("transfer"));
+        };
+        let ctx = TransferCtx {
+            store:           &mut self.substore,
+            api:             &MockApi,
+            querier:         &self.querier,
+            chain_id:        ctx.chain_id.clone(),
+            block_height:    ctx.block_height,
+            block_timestamp: ctx.block_timestamp,
+            block_hash:      ctx.block_hash.clone(),
+            contract:        ctx.contract.clone(),
+        };
+
+        Ok(transfer_fn(ctx, msg))
+    }
+
+    fn call_query_bank(
+        &mut self,
+        ctx: &Context,
+        msg: &BankQuery,
+    ) -> AppResult<GenericResult<BankQueryResponse>> {
+        let Some(query_bank_fn) = &self.contract.query_bank else {
+            return Err(missing_entry_point("query_bank"));
+        };
+        let ctx = QueryCtx {
+            store:           &self.substore,
+            api:             &MockApi,
+            querier:         &self.querier,
+            chain_id:        ctx.chain_id.clone(),
+            block_height:    ctx.block_height,
+            block_timestamp: ctx.block_timestamp,
+            block_hash:      ctx.block_hash.clone(),
+            contract:        ctx.contract.clone(),
+        };
+
+        Ok(query_bank_fn(ctx, msg))
+    }
+
+    fn call_withhold_fee(
+        &mut self,
+        ctx: &Context,
+        tx:  &Tx,
+    ) -> AppResult<GenericResult<Response>> {
+        let Some(withhold_fee_fn) = &self.contract.withhold_fee else {
+            return Err(missing_entry_point("withhold_fee"));
+        };
+        let simulate = ctx.simulate.expect("host failed to specify whether it's simulation mode");
+        let ctx = TaxmanCtx {
+            store:           &mut self.substore,
+            api:             &MockApi,
+            querier:         &self.querier,
+            chain_id:        ctx.chain_id.clone(),
+            block_height:    ctx.block_height,
+            block_timestamp: ctx.block_timestamp,
+            block_hash:      ctx.block_hash.clone(),
+            contract:        ctx.contract.clone(),
+            simulate,
+        };
+
+        Ok(withhold_fee_fn(ctx, tx))
+    }
+
+    fn call_finalize_fee(
+        &mut self,
+        ctx:     &Context,
+        tx:      &Tx,
+        outcome: &TxOutcome,
+    ) -> AppResult<GenericResult<Response>> {
+        let Some(finalize_fee_fn) = &self.contract.finalize_fee else {
+            return Err(missing_entry_point("finalize_fee"));
+        };
+        let simulate = ctx.simulate.expect("host failed to specify whether it's simulation mode");
+        let ctx = TaxmanCtx {
+            store:           &mut self.substore,
+            api:             &MockApi,
+            querier:         &self.querier,
+            chain_id:        ctx.chain_id.clone(),
+            block_height:    ctx.block_height,
+            block_timestamp: ctx.block_timestamp,
+            block_hash:      ctx.block_hash.clone(),
+            contract:        ctx.contract.clone(),
+            simulate,
+        };
+
+        Ok(finalize_fee_fn(ctx, tx, outcome))
+    }
+}
+
+fn contract_not_found(code_hash: &Hash) -> AppError {
+    let msg = format!("no native contract is registered with code hash `{code_hash}`");
+    StdError::Generic(msg).into()
+}
+
+fn missing_entry_point(name: &str) -> AppError {
+    StdError::Generic(format!("native contract doesn't have the `{name}` entry point")).into()
+}
+
+// ---------------------------------- storage ----------------------------------
+
+/// A contract's substore, i.e. the part of the chain's state under the
+/// contract's namespace.
+///
+/// Does the same as `cw_db::PrefixStore`, but implements `cw_std::Storage`
+/// instead of `BackendStorage`, as that's what native contracts work with. The
+/// bounds of scans are prefixed by the same function as `PrefixStore` uses.
+#[derive(Clone)]
+struct ContractStore<S> {
+    store:     S,
+    namespace: Vec<u8>,
+}
+
+impl<S> ContractStore<S> {
+    fn new(store: S, contract: &Addr) -> Self {
+        Self {
+            store,
+            namespace: concat(CONTRACT_NAMESPACE, contract),
+        }
+    }
+}
+
+impl<S: Storage + Clone> Storage for ContractStore<S> {
+    fn read(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.store.read(&concat(&self.namespace, key))
+    }
+
+    fn scan<'a>(
+        &'a self,
+        min:   Option<&[u8]>,
+        max:   Option<&[u8]>,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = Record> + 'a> {
+        let (min, max) = prefixed_bounds(&self.namespace, min, max);
+        let iter = self
+            .store
+            .scan(Some(&min), Some(&max), order)
+            .map(|(k, v)| (trim(&self.namespace, &k), v));
+        Box::new(iter)
+    }
+
+    fn write(&mut self, key: &[u8], value: &[u8]) {
+        self.store.write(&concat(&self.namespace, key), value);
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.store.remove(&concat(&self.namespace, key));
+    }
+
+    fn remove_range(&mut self, min: Option<&[u8]>, max: Option<&[u8]>) {
+        let (min, max) = prefixed_bounds(&self.namespace, min, max);
+        self.store.remove_range(Some(&min), Some(&max));
+    }
+}
@@ -10,7 +10,10 @@ use {
         hash, to_json, Addr, BankQuery, Coin, Coins, Context, Timestamp, TransferMsg, Uint128,
         Uint64,
     },
-    cw_vm::{BackendQuerier, BackendStorage, Instance, MockBackendQuerier, MockBackendStorage},
+    cw_vm::{
+        BackendQuerier, BackendStorage, GasTracker, Instance, MockBackendQuerier,
+        MockBackendStorage,
+    },
     lazy_static::lazy_static,
     std::{env, fs::File, io::Read, path::PathBuf},
 };
@@ -69,7 +72,9 @@ fn main() -> anyhow::Result<()> {
     wasm_file.read_to_end(&mut wasm_byte_code)?;
 
     let store = MockBackendStorage::new();
-    let mut instance = Instance::build_from_code(store, MockBackendQuerier, &wasm_byte_code)?;
+    let gas_tracker = GasTracker::new_limitless();
+    let mut instance =
+        Instance::build_from_code(store, MockBackendQuerier, &wasm_byte_code, gas_tracker.clone())?;
 
     // deploy the contract
     instantiate(&mut instance)?;
@@ -91,6 +96,8 @@ fn main() -> anyhow::Result<()> {
     // 0x6: 64 uosmo
     query_balances(&mut instance)?;
 
+    println!("⛽ Gas used: {}", gas_tracker.used());
+
     println!("✅ Done!");
 
     Ok(())
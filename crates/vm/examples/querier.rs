@@ -0,0 +1,119 @@
+//! How to run this example:
+//!
+//! $ just optimize
+//! $ cargo run -p cw-vm --example querier
+
+use {
+    cfg_if::cfg_if,
+    cw_mock_querier::QueryMsg,
+    cw_std::{hash, to_json, Addr, Coin, Coins, Context, Empty, QueryRequest, Timestamp, Uint64},
+    cw_vm::{GasTracker, Instance, MockBackendQuerier, MockBackendStorage},
+    std::{env, fs::File, io::Read, path::PathBuf},
+};
+
+fn main() -> anyhow::Result<()> {
+    tracing_subscriber::fmt().with_max_level(tracing::Level::DEBUG).init();
+
+    println!("🤖 Reading wasm byte code from file");
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR")?);
+    let wasm_file_path = manifest_dir.join({
+        cfg_if! {
+            if #[cfg(target_arch = "aarch64")] {
+                "../../artifacts/cw_mock_querier-aarch64.wasm"
+            } else {
+                "../../artifacts/cw_mock_querier.wasm"
+            }
+        }
+    });
+    let mut wasm_file = File::open(wasm_file_path)?;
+    let mut wasm_byte_code = vec![];
+    wasm_file.read_to_end(&mut wasm_byte_code)?;
+    let code_hash = hash(&wasm_byte_code);
+
+    // two instances of the querier contract, at addresses 0x1 and 0x2. the
+    // second one is registered with the querier, so the first one can query it
+    let querier = MockBackendQuerier::new()
+        .with_balances(Addr::mock(1), Coins::from(Coin::new("uatom", 100)))
+        .with_balances(Addr::mock(2), Coins::from(Coin::new("uosmo", 888)))
+        .with_account(Addr::mock(1), code_hash.clone(), None)
+        .with_account(Addr::mock(2), code_hash, None)
+        .with_code(wasm_byte_code.clone());
+    let gas_tracker = GasTracker::new_limitless();
+
+    let mut instance = Instance::build_from_code(
+        MockBackendStorage::new(),
+        querier.clone(),
+        &wasm_byte_code,
+        gas_tracker.clone(),
+    )?;
+    let mut other = Instance::build_from_code(
+        MockBackendStorage::new(),
+        querier.clone(),
+        &wasm_byte_code,
+        gas_tracker.clone(),
+    )?;
+
+    println!("🤖 Instantiating contracts");
+    instance.call_instantiate(&mock_context(Addr::mock(1)), to_json(&Empty {})?)?;
+    other.call_instantiate(&mock_context(Addr::mock(2)), to_json(&Empty {})?)?;
+    querier.register_instance(Addr::mock(2), other);
+
+    println!("🤖 Querying balances...");
+    query_chain(&mut instance, QueryRequest::Balances {
+        address:     Addr::mock(1),
+        start_after: None,
+        limit:       None,
+    })?;
+
+    println!("🤖 Querying accounts...");
+    query_chain(&mut instance, QueryRequest::Accounts {
+        start_after: None,
+        limit:       None,
+    })?;
+
+    println!("🤖 Querying the other contract, which queries its own balances...");
+    query_chain(&mut instance, QueryRequest::WasmSmart {
+        contract: Addr::mock(2),
+        msg:      to_json(&QueryMsg::QueryChain {
+            request: QueryRequest::Balances {
+                address:     Addr::mock(2),
+                start_after: None,
+                limit:       None,
+            },
+        })?,
+    })?;
+
+    println!("⛽ Gas used: {}", gas_tracker.used());
+
+    println!("✅ Done!");
+
+    Ok(())
+}
+
+fn query_chain(
+    instance: &mut Instance<MockBackendStorage, MockBackendQuerier>,
+    request:  QueryRequest,
+) -> anyhow::Result<()> {
+    let data = instance
+        .call_query(&mock_context(Addr::mock(1)), to_json(&QueryMsg::QueryChain { request })?)?
+        .into_std_result()?;
+
+    println!("{data}");
+
+    Ok(())
+}
+
+// same as in the bank example, a context that doesn't resemble a real chain
+fn mock_context(contract: Addr) -> Context {
+    Context {
+        chain_id:        "dev-1".into(),
+        block_height:    Uint64::new(0),
+        block_timestamp: Timestamp::from_seconds(0),
+        block_hash:      hash(""),
+        contract,
+        sender:          None,
+        funds:           None,
+        simulate:        None,
+        submsg_result:   None,
+    }
+}
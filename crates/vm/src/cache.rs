@@ -0,0 +1,260 @@
+use {
+    crate::{operator_cost, VmError, VmResult},
+    cw_std::{Hash, StdResult},
+    lru::LruCache,
+    std::{
+        fs,
+        num::NonZeroUsize,
+        path::{Path, PathBuf},
+        sync::{Arc, Mutex, MutexGuard},
+    },
+    tracing::{debug, warn},
+    wasmer::{CompilerConfig, Engine, Module, Singlepass, Store},
+    wasmer_middlewares::Metering,
+};
+
+// ------------------------------ compiled module ------------------------------
+
+/// A compiled Wasm module, together with the engine that compiled (or
+/// deserialized) it.
+///
+/// The module's machine code is owned by the engine rather than by the module
+/// itself, so the engine must be kept alive for as long as the module is used.
+/// Instances of the module are run in stores created from this same engine.
+#[derive(Clone)]
+pub struct CompiledModule {
+    pub(crate) engine: Engine,
+    pub(crate) module: Module,
+}
+
+impl CompiledModule {
+    pub fn compile(wasm_byte_code: &[u8]) -> VmResult<Self> {
+        // for now we use the singlepass compiler, with the metering middleware
+        // which injects gas accounting into the Wasm byte code.
+        // the initial metering points is zero; the instance sets it to the gas
+        // tracker's remaining gas once it's created.
+        // a metering middleware can only be used to compile one module, so we
+        // need to create a new compiler each time.
+        let mut compiler = Singlepass::default();
+        compiler.push_middleware(Arc::new(Metering::new(0, operator_cost)));
+        let store = Store::new(compiler);
+        let module = Module::new(&store, wasm_byte_code)?;
+
+        Ok(Self {
+            engine: store.engine().clone(),
+            module,
+        })
+    }
+}
+
+// -------------------------------- module cache -------------------------------
+
+/// Version of the modules serialized to disk.
+///
+/// Serialized modules contain the machine code produced by the compiler and the
+/// metering middleware, including the gas schedule. Deserializing a module with
+/// an outdated schedule would be silently accepted, so the disk cache is
+/// namespaced by this version, and it must be bumped whenever `operator_cost`
+/// or the compiler configuration changes.
+pub const MODULE_CACHE_VERSION: &str = "v1";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ModuleCacheMetrics {
+    /// Number of times a module was found in the in-memory cache.
+    pub hits_memory:     u64,
+    /// Number of times a module wasn't in memory, but was loaded from disk.
+    pub hits_disk:       u64,
+    /// Number of times a module had to be compiled from byte code.
+    pub misses:          u64,
+    /// Number of modules currently held in memory.
+    pub elements_memory: usize,
+}
+
+/// Caches compiled Wasm modules by their code hashes, such that we don't need
+/// to compile the same byte code every time a contract is called.
+///
+/// The most recently used modules are kept in memory. If a directory is given,
+/// compiled modules are also serialized to files in it, such that they survive
+/// restarts and don't need to be compiled again.
+///
+/// Clones of the cache share the same underlying modules.
+#[derive(Clone)]
+pub struct ModuleCache {
+    inner: Arc<Mutex<ModuleCacheInner>>,
+}
+
+struct ModuleCacheInner {
+    memory:  LruCache<Hash, CompiledModule>,
+    dir:     Option<PathBuf>,
+    metrics: ModuleCacheMetrics,
+}
+
+impl ModuleCache {
+    /// Create a new module cache holding up to `capacity` modules in memory,
+    /// optionally with a directory for the serialized modules.
+    ///
+    /// The modules are saved in a subdirectory named by `MODULE_CACHE_VERSION`,
+    /// such that modules compiled with a different gas schedule aren't reused.
+    pub fn new(capacity: NonZeroUsize, dir: Option<PathBuf>) -> VmResult<Self> {
+        let dir = dir.map(|dir| dir.join(MODULE_CACHE_VERSION));
+        if let Some(dir) = &dir {
+            fs::create_dir_all(dir)?;
+        }
+
+        Ok(Self {
+            inner: Arc::new(Mutex::new(ModuleCacheInner {
+                memory: LruCache::new(capacity),
+                dir,
+                metrics: ModuleCacheMetrics::default(),
+            })),
+        })
+    }
+
+    /// Get the module of the given code hash, first from memory, then from
+    /// disk. If it's in neither, compile it from the byte code returned by
+    /// `load_code`, and save it to both.
+    pub fn get_or_build<F, B>(&self, code_hash: &Hash, load_code: F) -> VmResult<CompiledModule>
+    where
+        F: FnOnce() -> StdResult<B>,
+        B: AsRef<[u8]>,
+    {
+        let dir = {
+            let mut inner = self.lock()?;
+            if let Some(module) = inner.memory.get(code_hash).cloned() {
+                inner.metrics.hits_memory += 1;
+                return Ok(module);
+            }
+            inner.dir.clone()
+        };
+
+        // loading from disk and compiling can be slow, so don't hold the lock
+        // while doing so, which would block calls to all other contracts. if
+        // two threads miss on the same module at the same time, it may be
+        // built twice, which is harmless.
+        let (module, from_disk) = if let Some(module) = load_from_disk(dir.as_deref(), code_hash) {
+            (module, true)
+        } else {
+            let module = CompiledModule::compile(load_code()?.as_ref())?;
+            save_to_disk(dir.as_deref(), code_hash, &module);
+            (module, false)
+        };
+
+        let mut inner = self.lock()?;
+        if from_disk {
+            inner.metrics.hits_disk += 1;
+        } else {
+            inner.metrics.misses += 1;
+        }
+        inner.memory.put(code_hash.clone(), module.clone());
+
+        Ok(module)
+    }
+
+    pub fn metrics(&self) -> VmResult<ModuleCacheMetrics> {
+        let inner = self.lock()?;
+
+        Ok(ModuleCacheMetrics {
+            elements_memory: inner.memory.len(),
+            ..inner.metrics
+        })
+    }
+
+    fn lock(&self) -> VmResult<MutexGuard<ModuleCacheInner>> {
+        self.inner.lock().map_err(|_| VmError::FailedLockModuleCache)
+    }
+}
+
+// the disk cache is only an optimization, so failing to read or write it isn't
+// an error. we simply fall back to compiling the byte code.
+fn load_from_disk(dir: Option<&Path>, code_hash: &Hash) -> Option<CompiledModule> {
+    let path = dir?.join(code_hash.to_string());
+    if !path.exists() {
+        return None;
+    }
+
+    // the metering middleware has already been injected into the serialized
+    // module, so the engine here is only used to load and run it, and doesn't
+    // need the middleware
+    let engine = Store::new(Singlepass::default()).engine().clone();
+
+    // SAFETY: the files in this directory are only written by `save_to_disk`
+    // below. if a file was produced by an incompatible version of wasmer,
+    // deserialization fails and the module is compiled again.
+    match unsafe { Module::deserialize_from_file(&engine, &path) } {
+        Ok(module) => {
+            debug!(code_hash = code_hash.to_string(), "Loaded module from disk");
+            Some(CompiledModule { engine, module })
+        },
+        Err(err) => {
+            warn!(
+                code_hash = code_hash.to_string(),
+                err = err.to_string(),
+                "Failed to load module from disk"
+            );
+            None
+        },
+    }
+}
+
+fn save_to_disk(dir: Option<&Path>, code_hash: &Hash, module: &CompiledModule) {
+    let Some(dir) = dir else {
+        return;
+    };
+
+    if let Err(err) = module.module.serialize_to_file(dir.join(code_hash.to_string())) {
+        warn!(
+            code_hash = code_hash.to_string(),
+            err = err.to_string(),
+            "Failed to save module to disk"
+        );
+    }
+}
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {super::*, cw_std::hash, std::env};
+
+    // the smallest valid Wasm module, consisting of only the magic number and
+    // the version
+    const EMPTY_MODULE: &[u8] = b"\0asm\x01\0\0\0";
+
+    #[test]
+    fn caching_works() {
+        let dir = env::temp_dir().join("_cw_vm_module_cache");
+        let _ = fs::remove_dir_all(&dir);
+
+        let capacity = NonZeroUsize::new(1).unwrap();
+        let code_hash = hash(EMPTY_MODULE);
+        let code_not_needed =
+            || -> StdResult<&'static [u8]> { panic!("byte code should not be loaded") };
+
+        // the first time the module is compiled, the second time it's found in
+        // memory
+        let cache = ModuleCache::new(capacity, Some(dir.clone())).unwrap();
+        cache.get_or_build(&code_hash, || Ok(EMPTY_MODULE)).unwrap();
+        cache.get_or_build(&code_hash, code_not_needed).unwrap();
+        assert_eq!(cache.metrics().unwrap(), ModuleCacheMetrics {
+            hits_memory:     1,
+            hits_disk:       0,
+            misses:          1,
+            elements_memory: 1,
+        });
+
+        // a new cache using the same directory should find the module on disk
+        let cache = ModuleCache::new(capacity, Some(dir.clone())).unwrap();
+        cache.get_or_build(&code_hash, code_not_needed).unwrap();
+        assert_eq!(cache.metrics().unwrap(), ModuleCacheMetrics {
+            hits_memory:     0,
+            hits_disk:       1,
+            misses:          0,
+            elements_memory: 1,
+        });
+
+        // the module should have been saved under the current cache version
+        assert!(dir.join(MODULE_CACHE_VERSION).join(code_hash.to_string()).exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
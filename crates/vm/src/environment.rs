@@ -1,11 +1,12 @@
 use {
-    crate::{VmError, VmResult},
+    crate::{GasTracker, VmError, VmResult},
     std::{
         borrow::{Borrow, BorrowMut},
         ptr::NonNull,
         sync::{Arc, RwLock},
     },
     wasmer::{AsStoreMut, AsStoreRef, Instance, Memory, MemoryView, Value},
+    wasmer_middlewares::metering::{get_remaining_points, set_remaining_points, MeteringPoints},
 };
 
 // TODO: add explaination on why these fields need to be Options
@@ -26,17 +27,25 @@ impl<S, Q> ContextData<S, Q> {
 }
 
 pub struct Environment<S, Q> {
-    memory: Option<Memory>,
-    data:   Arc<RwLock<ContextData<S, Q>>>,
+    memory:         Option<Memory>,
+    data:           Arc<RwLock<ContextData<S, Q>>>,
+    gas_tracker:    GasTracker,
+    /// The amount of metering points the Wasm instance had the last time we
+    /// synced it with the gas tracker. The difference between this and the
+    /// instance's current remaining points is the gas consumed by Wasm
+    /// execution that hasn't been accounted for in the tracker yet.
+    gas_checkpoint: u64,
 }
 
 unsafe impl<S, Q> Send for Environment<S, Q> {}
 
 impl<S, Q> Environment<S, Q> {
-    pub fn new(store: S, querier: Q) -> Self {
+    pub fn new(store: S, querier: Q, gas_tracker: GasTracker) -> Self {
         Self {
-            memory: None,
-            data:   Arc::new(RwLock::new(ContextData::new(store, querier))),
+            memory:         None,
+            data:           Arc::new(RwLock::new(ContextData::new(store, querier))),
+            gas_tracker,
+            gas_checkpoint: 0,
         }
     }
 
@@ -47,6 +56,13 @@ impl<S, Q> Environment<S, Q> {
             .map(|mem| mem.view(wasm_store))
     }
 
+    /// A handle to the context data, which can be used to access the storage
+    /// without borrowing the Wasm store or instance this environment belongs
+    /// to, e.g. while the instance is being called.
+    pub fn context_data(&self) -> Arc<RwLock<ContextData<S, Q>>> {
+        Arc::clone(&self.data)
+    }
+
     pub fn with_context_data<C, T, E>(&self, callback: C) -> VmResult<T>
     where
         C: FnOnce(&ContextData<S, Q>) -> Result<T, E>,
@@ -90,8 +106,50 @@ impl<S, Q> Environment<S, Q> {
         })
     }
 
+    /// Charge the gas tracker for the Wasm operators executed since the last
+    /// checkpoint, plus the given amount of gas consumed outside of Wasm (e.g.
+    /// by a host function), then reset the instance's metering points to the
+    /// tracker's remaining gas.
+    ///
+    /// This needs to be called after each Wasm function call, and at the start
+    /// of each import function, such that the gas tracker is up-to-date when
+    /// other instances sharing the same tracker are invoked (e.g. by the
+    /// `query_chain` import).
+    pub fn consume_gas(
+        &mut self,
+        wasm_store:    &mut impl AsStoreMut,
+        external_cost: u64,
+    ) -> VmResult<()> {
+        let points = self.with_wasm_instance(|wasm_instance| -> VmResult<_> {
+            Ok(get_remaining_points(&mut *wasm_store, wasm_instance))
+        })?;
+
+        let wasm_cost = match points {
+            MeteringPoints::Remaining(remaining) => self.gas_checkpoint.saturating_sub(remaining),
+            // the Wasm execution was aborted because metering points ran out.
+            // the operator that failed needed more points than what was left,
+            // so we charge one more than the checkpoint, which always errors.
+            MeteringPoints::Exhausted => {
+                self.gas_tracker.consume(self.gas_checkpoint.saturating_add(1))?;
+                let limit = self.gas_tracker.limit().unwrap_or(u64::MAX);
+                return Err(VmError::out_of_gas(limit, self.gas_tracker.used()));
+            },
+        };
+
+        self.gas_tracker.consume(wasm_cost.saturating_add(external_cost))?;
+
+        let remaining = self.gas_tracker.remaining();
+        self.with_wasm_instance(|wasm_instance| -> VmResult<_> {
+            set_remaining_points(&mut *wasm_store, wasm_instance, remaining);
+            Ok(())
+        })?;
+        self.gas_checkpoint = remaining;
+
+        Ok(())
+    }
+
     pub fn call_function1(
-        &self,
+        &mut self,
         wasm_store: &mut impl AsStoreMut,
         name:       &str,
         args:       &[Value],
@@ -108,7 +166,7 @@ impl<S, Q> Environment<S, Q> {
     }
 
     pub fn call_function0(
-        &self,
+        &mut self,
         wasm_store: &mut impl AsStoreMut,
         name:       &str,
         args:       &[Value],
@@ -125,7 +183,7 @@ impl<S, Q> Environment<S, Q> {
     }
 
     fn call_function(
-        &self,
+        &mut self,
         wasm_store: &mut impl AsStoreMut,
         name:       &str,
         args:       &[Value],
@@ -139,6 +197,13 @@ impl<S, Q> Environment<S, Q> {
             Ok(f.clone())
         })?;
 
-        func.call(wasm_store, args).map_err(Into::into)
+        let res = func.call(wasm_store, args);
+
+        // charge gas for the Wasm execution regardless of whether it succeeded.
+        // if we ran out of gas, the call returns a runtime error which isn't
+        // very informative. in this case we return the OutOfGas error instead.
+        self.consume_gas(wasm_store, 0)?;
+
+        res.map_err(Into::into)
     }
 }
@@ -1,8 +1,11 @@
 use {
     thiserror::Error,
-    cw_std::StdError,
-    std::string::FromUtf8Error,
-    wasmer::{CompileError, ExportError, InstantiationError, MemoryAccessError, RuntimeError},
+    cw_std::{CodedError, StdError, VmErrorCode},
+    std::{io, string::FromUtf8Error},
+    wasmer::{
+        wasmparser::BinaryReaderError, CompileError, ExportError, InstantiationError,
+        MemoryAccessError, RuntimeError,
+    },
 };
 
 #[derive(Debug, Error)]
@@ -13,6 +16,9 @@ pub enum VmError {
     #[error(transparent)]
     FromUtf8(#[from] FromUtf8Error),
 
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
     #[error(transparent)]
     Export(#[from] ExportError),
 
@@ -43,6 +49,9 @@ pub enum VmError {
     #[error("Failed to write lock ContextData")]
     FailedWriteLock,
 
+    #[error("Failed to lock ModuleCache")]
+    FailedLockModuleCache,
+
     #[error("Cannot find iterator with ID {iterator_id}")]
     IteratorNotFound {
         iterator_id: i32,
@@ -64,6 +73,68 @@ pub enum VmError {
 
     #[error("Unexpected return type: {0}")]
     ReturnType(&'static str),
+
+    #[error("Out of gas! limit: {limit}, used: {used}")]
+    OutOfGas {
+        limit: u64,
+        used:  u64,
+    },
+
+    #[error("Invalid Wasm byte code: {0}")]
+    InvalidWasm(String),
+
+    #[error("Wasm byte code is too large! size: {size}, max: {max}")]
+    WasmTooLarge {
+        size: usize,
+        max:  usize,
+    },
+
+    #[error("Wasm module defines too many functions! count: {count}, max: {max}")]
+    TooManyFunctions {
+        count: usize,
+        max:   usize,
+    },
+
+    #[error("Wasm module is missing required export `{name}`")]
+    MissingExport {
+        name: String,
+    },
+
+    #[error("Wasm module imports unsupported function `{module}.{name}`")]
+    UnsupportedImport {
+        module: String,
+        name:   String,
+    },
+
+    #[error("Wasm module uses floating point numbers, which are not allowed")]
+    FloatsNotAllowed,
+}
+
+impl VmError {
+    pub fn out_of_gas(limit: u64, used: u64) -> Self {
+        Self::OutOfGas { limit, used }
+    }
+
+    pub fn wasm_too_large(size: usize, max: usize) -> Self {
+        Self::WasmTooLarge { size, max }
+    }
+
+    pub fn too_many_functions(count: usize, max: usize) -> Self {
+        Self::TooManyFunctions { count, max }
+    }
+
+    pub fn missing_export(name: impl Into<String>) -> Self {
+        Self::MissingExport {
+            name: name.into(),
+        }
+    }
+
+    pub fn unsupported_import(module: impl Into<String>, name: impl Into<String>) -> Self {
+        Self::UnsupportedImport {
+            module: module.into(),
+            name:   name.into(),
+        }
+    }
 }
 
 impl From<CompileError> for VmError {
@@ -72,6 +143,12 @@ impl From<CompileError> for VmError {
     }
 }
 
+impl From<BinaryReaderError> for VmError {
+    fn from(err: BinaryReaderError) -> Self {
+        Self::InvalidWasm(err.to_string())
+    }
+}
+
 impl From<InstantiationError> for VmError {
     fn from(err: InstantiationError) -> Self {
         Self::Instantiation(err.to_string())
@@ -85,4 +162,49 @@ impl From<VmError> for RuntimeError {
     }
 }
 
+impl VmError {
+    pub const CODESPACE: &'static str = VmErrorCode::CODESPACE;
+}
+
+/// Codes in the `vm` codespace. Once assigned, a code must not be changed, so
+/// new variants must be given new codes.
+impl CodedError for VmError {
+    fn codespace(&self) -> &str {
+        match self {
+            Self::Std(err) => err.codespace(),
+            _ => Self::CODESPACE,
+        }
+    }
+
+    fn code(&self) -> u32 {
+        let code = match self {
+            Self::Std(err) => return err.code(),
+            Self::FromUtf8(_) => VmErrorCode::FromUtf8,
+            Self::Io(_) => VmErrorCode::Io,
+            Self::Export(_) => VmErrorCode::Export,
+            Self::MemoryAccess(_) => VmErrorCode::MemoryAccess,
+            Self::Runtime(_) => VmErrorCode::Runtime,
+            Self::Instantiation(_) => VmErrorCode::Instantiation,
+            Self::MemoryNotSet => VmErrorCode::MemoryNotSet,
+            Self::StoreNotSet => VmErrorCode::StoreNotSet,
+            Self::WasmerInstanceNotSet => VmErrorCode::WasmerInstanceNotSet,
+            Self::FailedReadLock => VmErrorCode::FailedReadLock,
+            Self::FailedWriteLock => VmErrorCode::FailedWriteLock,
+            Self::FailedLockModuleCache => VmErrorCode::FailedLockModuleCache,
+            Self::IteratorNotFound { .. } => VmErrorCode::IteratorNotFound,
+            Self::RegionTooSmall { .. } => VmErrorCode::RegionTooSmall,
+            Self::ReturnCount { .. } => VmErrorCode::ReturnCount,
+            Self::ReturnType(_) => VmErrorCode::ReturnType,
+            Self::OutOfGas { .. } => VmErrorCode::OutOfGas,
+            Self::InvalidWasm(_) => VmErrorCode::InvalidWasm,
+            Self::WasmTooLarge { .. } => VmErrorCode::WasmTooLarge,
+            Self::TooManyFunctions { .. } => VmErrorCode::TooManyFunctions,
+            Self::MissingExport { .. } => VmErrorCode::MissingExport,
+            Self::UnsupportedImport { .. } => VmErrorCode::UnsupportedImport,
+            Self::FloatsNotAllowed => VmErrorCode::FloatsNotAllowed,
+        };
+        code as u32
+    }
+}
+
 pub type VmResult<T> = std::result::Result<T, VmError>;
@@ -0,0 +1,159 @@
+use {
+    crate::{VmError, VmResult},
+    std::sync::{Arc, RwLock},
+    wasmer::wasmparser::Operator,
+};
+
+// ------------------------------- gas schedule --------------------------------
+
+/// Gas cost per Wasm operator executed. For now, we charge a flat rate for all
+/// operators. We may want a more fine-grained schedule in the future.
+pub const GAS_PER_OPERATOR: u64 = 1;
+
+pub const GAS_COST_DB_READ_BASE:                 u64 = 1000;
+pub const GAS_COST_DB_READ_PER_BYTE:             u64 = 2;
+pub const GAS_COST_DB_WRITE_BASE:                u64 = 2000;
+pub const GAS_COST_DB_WRITE_PER_BYTE:            u64 = 30;
+pub const GAS_COST_DB_REMOVE:                    u64 = 1000;
+pub const GAS_COST_DB_REMOVE_RANGE:              u64 = 5000;
+pub const GAS_COST_DB_SCAN:                      u64 = 1000;
+pub const GAS_COST_DB_NEXT_BASE:                 u64 = 500;
+pub const GAS_COST_DB_NEXT_PER_BYTE:             u64 = 2;
+pub const GAS_COST_QUERY_CHAIN:                  u64 = 5000;
+pub const GAS_COST_DEBUG:                        u64 = 100;
+pub const GAS_COST_SECP256K1_VERIFY:             u64 = 77000;
+pub const GAS_COST_SECP256R1_VERIFY:             u64 = 95000;
+pub const GAS_COST_SECP256K1_PUBKEY_RECOVER:     u64 = 80000;
+pub const GAS_COST_ED25519_VERIFY:               u64 = 41000;
+pub const GAS_COST_ED25519_BATCH_VERIFY_BASE:    u64 = 20000;
+pub const GAS_COST_ED25519_BATCH_VERIFY_PER_SIG: u64 = 25000;
+pub const GAS_COST_BLS12_381_VERIFY_BASE:        u64 = 300000;
+pub const GAS_COST_BLS12_381_VERIFY_PER_MSG:     u64 = 150000;
+pub const GAS_COST_BLS12_381_VERIFY_PER_PK:      u64 = 2000;
+pub const GAS_COST_SHA2_256_BASE:                u64 = 500;
+pub const GAS_COST_SHA2_256_PER_BYTE:            u64 = 5;
+pub const GAS_COST_SHA3_256_BASE:                u64 = 500;
+pub const GAS_COST_SHA3_256_PER_BYTE:            u64 = 7;
+pub const GAS_COST_KECCAK256_BASE:               u64 = 500;
+pub const GAS_COST_KECCAK256_PER_BYTE:           u64 = 7;
+pub const GAS_COST_BLAKE3_BASE:                  u64 = 500;
+pub const GAS_COST_BLAKE3_PER_BYTE:              u64 = 2;
+
+/// The cost function used by the metering middleware.
+///
+/// The costs are compiled into the modules, so `MODULE_CACHE_VERSION` must be
+/// bumped if this is changed.
+pub fn operator_cost(_op: &Operator) -> u64 {
+    GAS_PER_OPERATOR
+}
+
+// -------------------------------- gas tracker --------------------------------
+
+struct GasTrackerInner {
+    limit: Option<u64>,
+    used:  u64,
+}
+
+/// Tracks the gas consumption of a transaction or a query.
+///
+/// A single tracker is shared by all Wasm instances created while processing
+/// one transaction (e.g. the contract being executed, the contracts it calls
+/// via submessages, and the ones it queries), so it's clonable and each clone
+/// points to the same underlying counter.
+#[derive(Clone)]
+pub struct GasTracker {
+    inner: Arc<RwLock<GasTrackerInner>>,
+}
+
+impl GasTracker {
+    /// Create a new tracker with the given gas limit.
+    pub fn new_limited(limit: u64) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(GasTrackerInner {
+                limit: Some(limit),
+                used:  0,
+            })),
+        }
+    }
+
+    /// Create a new tracker without a gas limit. Used for genesis messages and
+    /// for queries, where there isn't a gas limit specified by the user.
+    pub fn new_limitless() -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(GasTrackerInner {
+                limit: None,
+                used:  0,
+            })),
+        }
+    }
+
+    pub fn limit(&self) -> Option<u64> {
+        self.inner.read().expect("failed to read lock GasTracker").limit
+    }
+
+    pub fn used(&self) -> u64 {
+        self.inner.read().expect("failed to read lock GasTracker").used
+    }
+
+    /// The amount of gas left. For a limitless tracker, this is `u64::MAX`.
+    pub fn remaining(&self) -> u64 {
+        let inner = self.inner.read().expect("failed to read lock GasTracker");
+        inner.limit.map_or(u64::MAX, |limit| limit - inner.used)
+    }
+
+    /// Consume the given amount of gas. Error if the limit is exceeded, in which
+    /// case the used amount is capped at the limit.
+    pub fn consume(&self, amount: u64) -> VmResult<()> {
+        let mut inner = self.inner.write().expect("failed to write lock GasTracker");
+        let used = inner.used.saturating_add(amount);
+
+        if let Some(limit) = inner.limit {
+            if used > limit {
+                inner.used = limit;
+                return Err(VmError::out_of_gas(limit, used));
+            }
+        }
+
+        inner.used = used;
+
+        Ok(())
+    }
+}
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn consuming_gas_works() -> VmResult<()> {
+        let tracker = GasTracker::new_limited(100);
+        let cloned = tracker.clone();
+
+        tracker.consume(30)?;
+        cloned.consume(50)?;
+        assert_eq!(tracker.used(), 80);
+        assert_eq!(cloned.remaining(), 20);
+
+        // exceeding the limit should fail, and used gas is capped at the limit
+        assert!(matches!(
+            tracker.consume(21),
+            Err(VmError::OutOfGas { limit: 100, used: 101 })
+        ));
+        assert_eq!(tracker.used(), 100);
+        assert_eq!(tracker.remaining(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn limitless_tracker_works() -> VmResult<()> {
+        let tracker = GasTracker::new_limitless();
+        tracker.consume(u64::MAX / 2)?;
+        tracker.consume(u64::MAX / 2)?;
+        assert_eq!(tracker.limit(), None);
+        assert_eq!(tracker.remaining(), u64::MAX);
+        Ok(())
+    }
+}
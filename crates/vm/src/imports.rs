@@ -1,6 +1,18 @@
 use {
-    crate::{read_from_memory, write_to_memory, BackendQuerier, BackendStorage, Environment, VmResult},
-    cw_std::{from_json, to_json, QueryRequest, Record},
+    crate::{
+        read_from_memory, write_to_memory, BackendQuerier, BackendStorage, Environment, VmResult,
+        GAS_COST_BLAKE3_BASE, GAS_COST_BLAKE3_PER_BYTE, GAS_COST_BLS12_381_VERIFY_BASE,
+        GAS_COST_BLS12_381_VERIFY_PER_MSG, GAS_COST_BLS12_381_VERIFY_PER_PK,
+        GAS_COST_DB_NEXT_BASE, GAS_COST_DB_NEXT_PER_BYTE, GAS_COST_DB_READ_BASE,
+        GAS_COST_DB_READ_PER_BYTE, GAS_COST_DB_REMOVE, GAS_COST_DB_REMOVE_RANGE, GAS_COST_DB_SCAN,
+        GAS_COST_DB_WRITE_BASE, GAS_COST_DB_WRITE_PER_BYTE, GAS_COST_DEBUG,
+        GAS_COST_ED25519_BATCH_VERIFY_BASE, GAS_COST_ED25519_BATCH_VERIFY_PER_SIG,
+        GAS_COST_ED25519_VERIFY, GAS_COST_KECCAK256_BASE, GAS_COST_KECCAK256_PER_BYTE,
+        GAS_COST_QUERY_CHAIN, GAS_COST_SECP256K1_PUBKEY_RECOVER, GAS_COST_SECP256K1_VERIFY,
+        GAS_COST_SECP256R1_VERIFY, GAS_COST_SHA2_256_BASE, GAS_COST_SHA2_256_PER_BYTE,
+        GAS_COST_SHA3_256_BASE, GAS_COST_SHA3_256_PER_BYTE,
+    },
+    cw_std::{from_json, to_json, Binary, QueryRequest, Record},
     tracing::info,
     wasmer::FunctionEnvMut,
 };
@@ -17,6 +29,10 @@ where
 
     let key = read_from_memory(env, &wasm_store, key_ptr)?;
     let maybe_value = env.with_context_data(|ctx| ctx.store.read(&key))?;
+    let value_len = maybe_value.as_ref().map_or(0, |value| value.len() as u64);
+    let cost = GAS_COST_DB_READ_BASE + GAS_COST_DB_READ_PER_BYTE * value_len;
+    env.consume_gas(&mut wasm_store, cost)?;
+
     // if doesn't exist, we return a zero pointer
     let Some(value) = maybe_value else {
         return Ok(0);
@@ -35,7 +51,9 @@ where
     S: BackendStorage + 'static,
     Q: 'static,
 {
-    let (env, wasm_store) = fe.data_and_store_mut();
+    let (env, mut wasm_store) = fe.data_and_store_mut();
+
+    env.consume_gas(&mut wasm_store, GAS_COST_DB_SCAN)?;
 
     let min = if min_ptr != 0 {
         Some(read_from_memory(env, &wasm_store, min_ptr)?)
@@ -75,7 +93,12 @@ where
 
     let (env, mut wasm_store) = fe.data_and_store_mut();
 
-    let Some(record) = env.with_context_data_mut(|ctx| ctx.store.next(iterator_id))? else {
+    let maybe_record = env.with_context_data_mut(|ctx| ctx.store.next(iterator_id))?;
+    let record_len = maybe_record.as_ref().map_or(0, |(k, v)| (k.len() + v.len()) as u64);
+    let cost = GAS_COST_DB_NEXT_BASE + GAS_COST_DB_NEXT_PER_BYTE * record_len;
+    env.consume_gas(&mut wasm_store, cost)?;
+
+    let Some(record) = maybe_record else {
         // returning a zero memory address informs the Wasm module that the
         // iterator has reached its end, and no data is loaded into memory.
         return Ok(0);
@@ -93,11 +116,15 @@ where
     S: BackendStorage + 'static,
     Q: 'static,
 {
-    let (env, wasm_store) = fe.data_and_store_mut();
+    let (env, mut wasm_store) = fe.data_and_store_mut();
 
     let key = read_from_memory(env, &wasm_store, key_ptr)?;
     let value = read_from_memory(env, &wasm_store, value_ptr)?;
 
+    let data_len = (key.len() + value.len()) as u64;
+    let cost = GAS_COST_DB_WRITE_BASE + GAS_COST_DB_WRITE_PER_BYTE * data_len;
+    env.consume_gas(&mut wasm_store, cost)?;
+
     env.with_context_data_mut(|ctx| ctx.store.write(&key, &value))
 }
 
@@ -109,19 +136,55 @@ where
     S: BackendStorage + 'static,
     Q: 'static,
 {
-    let (env, wasm_store) = fe.data_and_store_mut();
+    let (env, mut wasm_store) = fe.data_and_store_mut();
+
+    env.consume_gas(&mut wasm_store, GAS_COST_DB_REMOVE)?;
 
     let key = read_from_memory(env, &wasm_store, key_ptr)?;
 
     env.with_context_data_mut(|ctx| ctx.store.remove(&key))
 }
 
+pub fn db_remove_range<S, Q>(
+    mut fe:  FunctionEnvMut<Environment<S, Q>>,
+    min_ptr: u32,
+    max_ptr: u32,
+) -> VmResult<()>
+where
+    S: BackendStorage + 'static,
+    Q: 'static,
+{
+    let (env, mut wasm_store) = fe.data_and_store_mut();
+
+    env.consume_gas(&mut wasm_store, GAS_COST_DB_REMOVE_RANGE)?;
+
+    let min = if min_ptr != 0 {
+        Some(read_from_memory(env, &wasm_store, min_ptr)?)
+    } else {
+        None
+    };
+    let max = if max_ptr != 0 {
+        Some(read_from_memory(env, &wasm_store, max_ptr)?)
+    } else {
+        None
+    };
+
+    // besides the base cost, charge the same as `db_remove` for each record
+    // deleted, such that deleting many records isn't cheaper than deleting them
+    // one by one
+    let count = env
+        .with_context_data_mut(|ctx| ctx.store.remove_range(min.as_deref(), max.as_deref()))?;
+    env.consume_gas(&mut wasm_store, GAS_COST_DB_REMOVE * count as u64)
+}
+
 pub fn debug<S, Q>(mut fe: FunctionEnvMut<Environment<S, Q>>, msg_ptr: u32) -> VmResult<()>
 where
     S: 'static,
     Q: 'static,
 {
-    let (env, wasm_store) = fe.data_and_store_mut();
+    let (env, mut wasm_store) = fe.data_and_store_mut();
+
+    env.consume_gas(&mut wasm_store, GAS_COST_DEBUG)?;
 
     let msg_bytes = read_from_memory(env, &wasm_store, msg_ptr)?;
     let msg = String::from_utf8(msg_bytes)?;
@@ -137,12 +200,19 @@ where
 {
     let (env, mut wasm_store) = fe.data_and_store_mut();
 
+    // the querier shares the same gas tracker as this instance, so before
+    // making the query, we need to bring the tracker up-to-date. after the
+    // query, sync the instance's metering points with the gas the query used.
+    env.consume_gas(&mut wasm_store, GAS_COST_QUERY_CHAIN)?;
+
     let req_bytes = read_from_memory(env, &wasm_store, req_ptr)?;
     let req: QueryRequest = from_json(req_bytes)?;
 
     let res = env.with_context_data(|ctx| ctx.querier.query_chain(req))?;
     let res_bytes = to_json(&res)?;
 
+    env.consume_gas(&mut wasm_store, 0)?;
+
     write_to_memory(env, &mut wasm_store, &res_bytes)
 }
 
@@ -156,7 +226,9 @@ where
     S: 'static,
     Q: 'static,
 {
-    let (env, wasm_store) = fe.data_and_store_mut();
+    let (env, mut wasm_store) = fe.data_and_store_mut();
+
+    env.consume_gas(&mut wasm_store, GAS_COST_SECP256K1_VERIFY)?;
 
     let msg_hash = read_from_memory(env, &wasm_store, msg_hash_ptr)?;
     let sig = read_from_memory(env, &wasm_store, sig_ptr)?;
@@ -178,7 +250,9 @@ where
     S: 'static,
     Q: 'static,
 {
-    let (env, wasm_store) = fe.data_and_store_mut();
+    let (env, mut wasm_store) = fe.data_and_store_mut();
+
+    env.consume_gas(&mut wasm_store, GAS_COST_SECP256R1_VERIFY)?;
 
     let msg_hash = read_from_memory(env, &wasm_store, msg_hash_ptr)?;
     let sig = read_from_memory(env, &wasm_store, sig_ptr)?;
@@ -189,3 +263,193 @@ where
         Err(_) => Ok(1),
     }
 }
+
+pub fn secp256k1_pubkey_recover<S, Q>(
+    mut fe: FunctionEnvMut<Environment<S, Q>>,
+    msg_hash_ptr: u32,
+    sig_ptr:      u32,
+    recovery_id:  u32,
+    compressed:   u32,
+) -> VmResult<u32>
+where
+    S: 'static,
+    Q: 'static,
+{
+    let (env, mut wasm_store) = fe.data_and_store_mut();
+
+    env.consume_gas(&mut wasm_store, GAS_COST_SECP256K1_PUBKEY_RECOVER)?;
+
+    let msg_hash = read_from_memory(env, &wasm_store, msg_hash_ptr)?;
+    let sig = read_from_memory(env, &wasm_store, sig_ptr)?;
+
+    // a recovery ID that doesn't fit in a u8 is definitely invalid
+    let Ok(recovery_id) = u8::try_from(recovery_id) else {
+        return Ok(0);
+    };
+
+    // return a zero pointer if recovery fails, similar to how db_read returns
+    // a zero pointer if the key doesn't exist
+    match cw_crypto::secp256k1_pubkey_recover(&msg_hash, &sig, recovery_id, compressed != 0) {
+        Ok(pk) => write_to_memory(env, &mut wasm_store, &pk),
+        Err(_) => Ok(0),
+    }
+}
+
+pub fn ed25519_verify<S, Q>(
+    mut fe: FunctionEnvMut<Environment<S, Q>>,
+    msg_ptr: u32,
+    sig_ptr: u32,
+    pk_ptr:  u32,
+) -> VmResult<i32>
+where
+    S: 'static,
+    Q: 'static,
+{
+    let (env, mut wasm_store) = fe.data_and_store_mut();
+
+    env.consume_gas(&mut wasm_store, GAS_COST_ED25519_VERIFY)?;
+
+    let msg = read_from_memory(env, &wasm_store, msg_ptr)?;
+    let sig = read_from_memory(env, &wasm_store, sig_ptr)?;
+    let pk = read_from_memory(env, &wasm_store, pk_ptr)?;
+
+    match cw_crypto::ed25519_verify(&msg, &sig, &pk) {
+        Ok(()) => Ok(0),
+        Err(_) => Ok(1),
+    }
+}
+
+// the messages, signatures, and public keys are each passed in as a JSON array
+// of base64-encoded binaries
+pub fn ed25519_batch_verify<S, Q>(
+    mut fe: FunctionEnvMut<Environment<S, Q>>,
+    msgs_ptr: u32,
+    sigs_ptr: u32,
+    pks_ptr:  u32,
+) -> VmResult<i32>
+where
+    S: 'static,
+    Q: 'static,
+{
+    let (env, mut wasm_store) = fe.data_and_store_mut();
+
+    env.consume_gas(&mut wasm_store, GAS_COST_ED25519_BATCH_VERIFY_BASE)?;
+
+    let msgs: Vec<Binary> = from_json(read_from_memory(env, &wasm_store, msgs_ptr)?)?;
+    let sigs: Vec<Binary> = from_json(read_from_memory(env, &wasm_store, sigs_ptr)?)?;
+    let pks: Vec<Binary> = from_json(read_from_memory(env, &wasm_store, pks_ptr)?)?;
+
+    env.consume_gas(&mut wasm_store, GAS_COST_ED25519_BATCH_VERIFY_PER_SIG * sigs.len() as u64)?;
+
+    let msgs = msgs.iter().map(|msg| msg.as_ref()).collect::<Vec<_>>();
+    let sigs = sigs.iter().map(|sig| sig.as_ref()).collect::<Vec<_>>();
+    let pks = pks.iter().map(|pk| pk.as_ref()).collect::<Vec<_>>();
+
+    match cw_crypto::ed25519_batch_verify(&msgs, &sigs, &pks) {
+        Ok(()) => Ok(0),
+        Err(_) => Ok(1),
+    }
+}
+
+// if only one message is given, it's signed by all public keys; otherwise, the
+// i-th message is signed by the i-th public key.
+// the messages and public keys are each passed in as a JSON array of
+// base64-encoded binaries, same as in ed25519_batch_verify.
+pub fn bls12_381_aggregate_verify<S, Q>(
+    mut fe: FunctionEnvMut<Environment<S, Q>>,
+    msgs_ptr: u32,
+    sig_ptr:  u32,
+    pks_ptr:  u32,
+) -> VmResult<i32>
+where
+    S: 'static,
+    Q: 'static,
+{
+    let (env, mut wasm_store) = fe.data_and_store_mut();
+
+    env.consume_gas(&mut wasm_store, GAS_COST_BLS12_381_VERIFY_BASE)?;
+
+    let msgs: Vec<Binary> = from_json(read_from_memory(env, &wasm_store, msgs_ptr)?)?;
+    let sig = read_from_memory(env, &wasm_store, sig_ptr)?;
+    let pks: Vec<Binary> = from_json(read_from_memory(env, &wasm_store, pks_ptr)?)?;
+
+    let cost = GAS_COST_BLS12_381_VERIFY_PER_MSG * msgs.len() as u64
+        + GAS_COST_BLS12_381_VERIFY_PER_PK * pks.len() as u64;
+    env.consume_gas(&mut wasm_store, cost)?;
+
+    let msgs = msgs.iter().map(|msg| msg.as_ref()).collect::<Vec<_>>();
+    let pks = pks.iter().map(|pk| pk.as_ref()).collect::<Vec<_>>();
+
+    match cw_crypto::bls12_381_aggregate_verify(&msgs, &sig, &pks) {
+        Ok(()) => Ok(0),
+        Err(_) => Ok(1),
+    }
+}
+
+pub fn sha2_256<S, Q>(
+    mut fe:   FunctionEnvMut<Environment<S, Q>>,
+    data_ptr: u32,
+) -> VmResult<u32>
+where
+    S: 'static,
+    Q: 'static,
+{
+    let (env, mut wasm_store) = fe.data_and_store_mut();
+
+    let data = read_from_memory(env, &wasm_store, data_ptr)?;
+    let cost = GAS_COST_SHA2_256_BASE + GAS_COST_SHA2_256_PER_BYTE * data.len() as u64;
+    env.consume_gas(&mut wasm_store, cost)?;
+
+    write_to_memory(env, &mut wasm_store, &cw_crypto::sha2_256(&data))
+}
+
+pub fn sha3_256<S, Q>(
+    mut fe:   FunctionEnvMut<Environment<S, Q>>,
+    data_ptr: u32,
+) -> VmResult<u32>
+where
+    S: 'static,
+    Q: 'static,
+{
+    let (env, mut wasm_store) = fe.data_and_store_mut();
+
+    let data = read_from_memory(env, &wasm_store, data_ptr)?;
+    let cost = GAS_COST_SHA3_256_BASE + GAS_COST_SHA3_256_PER_BYTE * data.len() as u64;
+    env.consume_gas(&mut wasm_store, cost)?;
+
+    write_to_memory(env, &mut wasm_store, &cw_crypto::sha3_256(&data))
+}
+
+pub fn keccak256<S, Q>(
+    mut fe:   FunctionEnvMut<Environment<S, Q>>,
+    data_ptr: u32,
+) -> VmResult<u32>
+where
+    S: 'static,
+    Q: 'static,
+{
+    let (env, mut wasm_store) = fe.data_and_store_mut();
+
+    let data = read_from_memory(env, &wasm_store, data_ptr)?;
+    let cost = GAS_COST_KECCAK256_BASE + GAS_COST_KECCAK256_PER_BYTE * data.len() as u64;
+    env.consume_gas(&mut wasm_store, cost)?;
+
+    write_to_memory(env, &mut wasm_store, &cw_crypto::keccak256(&data))
+}
+
+pub fn blake3<S, Q>(
+    mut fe:   FunctionEnvMut<Environment<S, Q>>,
+    data_ptr: u32,
+) -> VmResult<u32>
+where
+    S: 'static,
+    Q: 'static,
+{
+    let (env, mut wasm_store) = fe.data_and_store_mut();
+
+    let data = read_from_memory(env, &wasm_store, data_ptr)?;
+    let cost = GAS_COST_BLAKE3_BASE + GAS_COST_BLAKE3_PER_BYTE * data.len() as u64;
+    env.consume_gas(&mut wasm_store, cost)?;
+
+    write_to_memory(env, &mut wasm_store, &cw_crypto::blake3(&data))
+}
@@ -1,16 +1,18 @@
 use {
     crate::{
-        db_next, db_read, db_remove, db_scan, db_write, debug, query_chain, read_then_wipe,
-        secp256k1_verify, secp256r1_verify, write_to_memory, BackendQuerier, BackendStorage,
-        Environment, VmError, VmResult,
+        blake3, bls12_381_aggregate_verify, db_next, db_read, db_remove, db_remove_range, db_scan,
+        db_write, debug, ed25519_batch_verify, ed25519_verify, keccak256, query_chain,
+        read_then_wipe, secp256k1_pubkey_recover, secp256k1_verify, secp256r1_verify, sha2_256,
+        sha3_256, write_to_memory,
+        BackendQuerier, BackendStorage, CompiledModule, ContextData, Environment, GasTracker,
+        VmError, VmResult,
     },
     cw_std::{
         from_json, to_json, BankQuery, BankQueryResponse, Binary, Context, GenericResult, Response,
-        TransferMsg, Tx,
-    },
-    wasmer::{
-        imports, Function, FunctionEnv, Instance as WasmerInstance, Module, Singlepass, Store,
+        TransferMsg, Tx, TxOutcome,
     },
+    std::sync::{Arc, RwLock},
+    wasmer::{imports, Function, FunctionEnv, Instance as WasmerInstance, Store},
 };
 
 pub struct Instance<S, Q> {
@@ -24,17 +26,30 @@ where
     S: BackendStorage + 'static,
     Q: BackendQuerier + 'static,
 {
-    pub fn build_from_code(store: S, querier: Q, wasm_byte_code: &[u8]) -> VmResult<Self> {
-        // create Wasm store
-        // for now we use the singlepass compiler
-        let mut wasm_store = Store::new(Singlepass::default());
+    pub fn build_from_code(
+        store:          S,
+        querier:        Q,
+        wasm_byte_code: &[u8],
+        gas_tracker:    GasTracker,
+    ) -> VmResult<Self> {
+        let module = CompiledModule::compile(wasm_byte_code)?;
+        Self::build_from_module(store, querier, &module, gas_tracker)
+    }
 
-        // compile Wasm byte code into module
-        let module = Module::new(&wasm_store, wasm_byte_code)?;
+    pub fn build_from_module(
+        store:       S,
+        querier:     Q,
+        module:      &CompiledModule,
+        gas_tracker: GasTracker,
+    ) -> VmResult<Self> {
+        // create Wasm store, using the engine that compiled the module
+        let mut wasm_store = Store::new(module.engine.clone());
 
         // create function environment and register imports
         // note: memory/store/instance in the env hasn't been set yet at this point
-        let fe = FunctionEnv::new(&mut wasm_store, Environment::new(store, querier));
+        // note: when adding or removing imports here, `SUPPORTED_IMPORTS` needs to
+        // be updated accordingly
+        let fe = FunctionEnv::new(&mut wasm_store, Environment::new(store, querier, gas_tracker));
         let import_obj = imports! {
             "env" => {
                 "db_read" => Function::new_typed_with_env(&mut wasm_store, &fe, db_read),
@@ -42,22 +57,35 @@ where
                 "db_next" => Function::new_typed_with_env(&mut wasm_store, &fe, db_next),
                 "db_write" => Function::new_typed_with_env(&mut wasm_store, &fe, db_write),
                 "db_remove" => Function::new_typed_with_env(&mut wasm_store, &fe, db_remove),
+                "db_remove_range" => Function::new_typed_with_env(&mut wasm_store, &fe, db_remove_range),
                 "debug" => Function::new_typed_with_env(&mut wasm_store, &fe, debug),
                 "query_chain" => Function::new_typed_with_env(&mut wasm_store, &fe, query_chain),
                 "secp256k1_verify" => Function::new_typed_with_env(&mut wasm_store, &fe, secp256k1_verify),
-                "secp256r1_verify" => Function::new_typed_with_env(&mut wasm_store, &fe, secp256r1_verify)
+                "secp256r1_verify" => Function::new_typed_with_env(&mut wasm_store, &fe, secp256r1_verify),
+                "secp256k1_pubkey_recover" => Function::new_typed_with_env(&mut wasm_store, &fe, secp256k1_pubkey_recover),
+                "ed25519_verify" => Function::new_typed_with_env(&mut wasm_store, &fe, ed25519_verify),
+                "ed25519_batch_verify" => Function::new_typed_with_env(&mut wasm_store, &fe, ed25519_batch_verify),
+                "bls12_381_aggregate_verify" => Function::new_typed_with_env(&mut wasm_store, &fe, bls12_381_aggregate_verify),
+                "sha2_256" => Function::new_typed_with_env(&mut wasm_store, &fe, sha2_256),
+                "sha3_256" => Function::new_typed_with_env(&mut wasm_store, &fe, sha3_256),
+                "keccak256" => Function::new_typed_with_env(&mut wasm_store, &fe, keccak256),
+                "blake3" => Function::new_typed_with_env(&mut wasm_store, &fe, blake3)
             }
         };
 
         // create wasmer instance
-        let wasm_instance = WasmerInstance::new(&mut wasm_store, &module, &import_obj)?;
+        let wasm_instance = WasmerInstance::new(&mut wasm_store, &module.module, &import_obj)?;
         let wasm_instance = Box::new(wasm_instance);
 
         // set memory/store/instance in the env
-        let env = fe.as_mut(&mut wasm_store);
+        let mut fe_mut = fe.clone().into_mut(&mut wasm_store);
+        let (env, mut wasm_store_mut) = fe_mut.data_and_store_mut();
         env.set_memory(&wasm_instance)?;
         env.set_wasm_instance(wasm_instance.as_ref())?;
 
+        // give the instance as many metering points as the gas left
+        env.consume_gas(&mut wasm_store_mut, 0)?;
+
         Ok(Self {
             _wasm_instance: wasm_instance,
             wasm_store,
@@ -65,6 +93,13 @@ where
         })
     }
 
+    /// A handle to the instance's context data, through which its storage can
+    /// be read directly, without calling the Wasm module or borrowing the
+    /// instance.
+    pub fn context_data(&self) -> Arc<RwLock<ContextData<S, Q>>> {
+        self.fe.as_ref(&self.wasm_store).context_data()
+    }
+
     pub fn call_instantiate(
         &mut self,
         ctx: &Context,
@@ -124,6 +159,16 @@ where
         from_json(res_bytes).map_err(Into::into)
     }
 
+    pub fn call_before_block(&mut self, ctx: &Context) -> VmResult<GenericResult<Response>> {
+        let res_bytes = self.call_in_0_out_1("before_block", ctx)?;
+        from_json(res_bytes).map_err(Into::into)
+    }
+
+    pub fn call_after_block(&mut self, ctx: &Context) -> VmResult<GenericResult<Response>> {
+        let res_bytes = self.call_in_0_out_1("after_block", ctx)?;
+        from_json(res_bytes).map_err(Into::into)
+    }
+
     pub fn call_transfer(
         &mut self,
         ctx: &Context,
@@ -142,6 +187,26 @@ where
         from_json(res_bytes).map_err(Into::into)
     }
 
+    pub fn call_withhold_fee(
+        &mut self,
+        ctx: &Context,
+        tx:  &Tx,
+    ) -> VmResult<GenericResult<Response>> {
+        let res_bytes = self.call_in_1_out_1("withhold_fee", ctx, to_json(tx)?)?;
+        from_json(res_bytes).map_err(Into::into)
+    }
+
+    pub fn call_finalize_fee(
+        &mut self,
+        ctx:     &Context,
+        tx:      &Tx,
+        outcome: &TxOutcome,
+    ) -> VmResult<GenericResult<Response>> {
+        let res_bytes =
+            self.call_in_2_out_1("finalize_fee", ctx, to_json(tx)?, to_json(outcome)?)?;
+        from_json(res_bytes).map_err(Into::into)
+    }
+
     /// Call the a Wasm export function. This method expects no input (besides
     /// the context) and exactly 1 output.
     fn call_in_0_out_1(&mut self, name: &str, ctx: &Context) -> VmResult<Vec<u8>> {
@@ -177,4 +242,31 @@ where
 
         read_then_wipe(env, &mut wasm_store, res_ptr)
     }
+
+    /// Call the a Wasm export function. This method expects exactly 2 inputs
+    /// (besides the context) and exactly 1 output.
+    fn call_in_2_out_1(
+        &mut self,
+        name: &str,
+        ctx:  &Context,
+        msg1: impl AsRef<[u8]>,
+        msg2: impl AsRef<[u8]>,
+    ) -> VmResult<Vec<u8>> {
+        let mut fe_mut = self.fe.clone().into_mut(&mut self.wasm_store);
+        let (env, mut wasm_store) = fe_mut.data_and_store_mut();
+
+        let ctx_ptr = write_to_memory(env, &mut wasm_store, &to_json(ctx)?)?;
+        let msg1_ptr = write_to_memory(env, &mut wasm_store, msg1.as_ref())?;
+        let msg2_ptr = write_to_memory(env, &mut wasm_store, msg2.as_ref())?;
+        let res_ptr: u32 = env
+            .call_function1(&mut wasm_store, name, &[
+                ctx_ptr.into(),
+                msg1_ptr.into(),
+                msg2_ptr.into(),
+            ])?
+            .try_into()
+            .map_err(VmError::ReturnType)?;
+
+        read_then_wipe(env, &mut wasm_store, res_ptr)
+    }
 }
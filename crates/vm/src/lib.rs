@@ -1,22 +1,43 @@
+mod cache;
 mod environment;
 mod error;
+mod gas;
 mod imports;
 mod instance;
 mod memory;
 mod region;
 mod testing;
 mod traits;
+mod validate;
 
 pub use {
+    cache::{CompiledModule, ModuleCache, ModuleCacheMetrics, MODULE_CACHE_VERSION},
     environment::{ContextData, Environment},
     error::{VmError, VmResult},
+    gas::{
+        operator_cost, GasTracker, GAS_COST_BLAKE3_BASE, GAS_COST_BLAKE3_PER_BYTE,
+        GAS_COST_BLS12_381_VERIFY_BASE, GAS_COST_BLS12_381_VERIFY_PER_MSG,
+        GAS_COST_BLS12_381_VERIFY_PER_PK, GAS_COST_DB_NEXT_BASE, GAS_COST_DB_NEXT_PER_BYTE,
+        GAS_COST_DB_READ_BASE, GAS_COST_DB_READ_PER_BYTE, GAS_COST_DB_REMOVE,
+        GAS_COST_DB_REMOVE_RANGE, GAS_COST_DB_SCAN, GAS_COST_DB_WRITE_BASE,
+        GAS_COST_DB_WRITE_PER_BYTE, GAS_COST_DEBUG, GAS_COST_ED25519_BATCH_VERIFY_BASE,
+        GAS_COST_ED25519_BATCH_VERIFY_PER_SIG, GAS_COST_ED25519_VERIFY, GAS_COST_KECCAK256_BASE,
+        GAS_COST_KECCAK256_PER_BYTE, GAS_COST_QUERY_CHAIN, GAS_COST_SECP256K1_PUBKEY_RECOVER,
+        GAS_COST_SECP256K1_VERIFY, GAS_COST_SECP256R1_VERIFY, GAS_COST_SHA2_256_BASE,
+        GAS_COST_SHA2_256_PER_BYTE, GAS_COST_SHA3_256_BASE, GAS_COST_SHA3_256_PER_BYTE,
+        GAS_PER_OPERATOR,
+    },
     imports::{
-        db_next, db_read, db_remove, db_scan, db_write, debug, query_chain, secp256k1_verify,
-        secp256r1_verify,
+        blake3, bls12_381_aggregate_verify, db_next, db_read, db_remove, db_remove_range, db_scan,
+        db_write, debug, ed25519_batch_verify, ed25519_verify, keccak256, query_chain,
+        secp256k1_pubkey_recover, secp256k1_verify, secp256r1_verify, sha2_256, sha3_256,
     },
     instance::Instance,
     memory::{read_from_memory, read_then_wipe, write_to_memory},
     region::Region,
     testing::{MockBackendQuerier, MockBackendStorage},
     traits::{BackendQuerier, BackendStorage},
+    validate::{
+        validate_wasm, MAX_WASM_FUNCTIONS, MAX_WASM_SIZE, REQUIRED_EXPORTS, SUPPORTED_IMPORTS,
+    },
 };
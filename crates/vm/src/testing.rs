@@ -1,22 +1,305 @@
 use {
-    crate::{BackendQuerier, BackendStorage, VmError, VmResult},
-    cw_std::{GenericResult, Order, QueryRequest, QueryResponse, Record},
+    crate::{BackendQuerier, BackendStorage, ContextData, Instance, VmError, VmResult},
+    cw_std::{
+        hash, AccountResponse, Addr, Binary, Coin, Coins, Context, GenericResult, Hash, Order,
+        QueryRequest, QueryResponse, Record, StdError, Timestamp, Uint128, Uint64,
+        WasmRawResponse, WasmSmartResponse,
+    },
     std::{
+        cell::RefCell,
         collections::{BTreeMap, HashMap},
         iter::Peekable,
+        mem,
         ops::Bound,
+        rc::Rc,
+        sync::{Arc, RwLock},
         vec,
     },
 };
 
-pub struct MockBackendQuerier;
+const MOCK_CHAIN_ID: &str = "dev-1";
+const DEFAULT_PAGE_LIMIT: u32 = 30;
+
+type MockInstance = Instance<MockBackendStorage, MockBackendQuerier>;
+
+type MockContextData = Arc<RwLock<ContextData<MockBackendStorage, MockBackendQuerier>>>;
+
+/// A querier for testing contracts in the VM without the app. Bank, account
+/// and code queries are answered using data preset by the test. Wasm queries
+/// are answered by calling the instances registered with the querier.
+///
+/// Clones of the querier share the same data and instances, so a clone can be
+/// given to each instance, including the ones that are then registered.
+#[derive(Default, Clone)]
+pub struct MockBackendQuerier {
+    state: Rc<RefCell<MockQuerierState>>,
+}
+
+#[derive(Default)]
+struct MockQuerierState {
+    balances:  BTreeMap<Addr, Coins>,
+    accounts:  BTreeMap<Addr, AccountResponse>,
+    codes:     BTreeMap<Hash, Binary>,
+    instances: HashMap<Addr, Rc<RefCell<MockInstance>>>,
+    // the instances' context data, through which their storages are read for
+    // `WasmRaw` queries. unlike the instances, these can be accessed while the
+    // instances are being called.
+    contexts:  HashMap<Addr, MockContextData>,
+}
+
+impl MockBackendQuerier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_balances(self, address: Addr, coins: Coins) -> Self {
+        self.state.borrow_mut().balances.insert(address, coins);
+        self
+    }
+
+    pub fn with_account(self, address: Addr, code_hash: Hash, admin: Option<Addr>) -> Self {
+        self.state.borrow_mut().accounts.insert(address.clone(), AccountResponse {
+            address,
+            code_hash,
+            admin,
+        });
+        self
+    }
+
+    pub fn with_code(self, wasm_byte_code: impl Into<Binary>) -> Self {
+        let wasm_byte_code = wasm_byte_code.into();
+        self.state.borrow_mut().codes.insert(hash(&wasm_byte_code), wasm_byte_code);
+        self
+    }
+
+    /// Register an instance as the contract at the given address, such that
+    /// `WasmSmart` and `WasmRaw` queries to this address are answered by it.
+    ///
+    /// Note: while an instance is being called, `WasmSmart` queries to it fail,
+    /// as the same Wasm instance can't be entered again. `WasmRaw` queries
+    /// don't enter the instance, so they work.
+    pub fn register_instance(&self, address: Addr, instance: MockInstance) {
+        let mut state = self.state.borrow_mut();
+        state.contexts.insert(address.clone(), instance.context_data());
+        state.instances.insert(address, Rc::new(RefCell::new(instance)));
+    }
+
+    fn query(&self, req: QueryRequest) -> VmResult<QueryResponse> {
+        match req {
+            QueryRequest::Balance {
+                address,
+                denom,
+            } => self.query_balance(address, denom).map(QueryResponse::Balance),
+            QueryRequest::Balances {
+                address,
+                start_after,
+                limit,
+            } => Ok(QueryResponse::Balances(self.query_balances(address, start_after, limit))),
+            QueryRequest::Supply {
+                denom,
+            } => self.query_supply(denom).map(QueryResponse::Supply),
+            QueryRequest::Supplies {
+                start_after,
+                limit,
+            } => self.query_supplies(start_after, limit).map(QueryResponse::Supplies),
+            QueryRequest::Code {
+                hash,
+            } => self.query_code(hash).map(QueryResponse::Code),
+            QueryRequest::Codes {
+                start_after,
+                limit,
+            } => Ok(QueryResponse::Codes(self.query_codes(start_after, limit))),
+            QueryRequest::Account {
+                address,
+            } => self.query_account(address).map(QueryResponse::Account),
+            QueryRequest::Accounts {
+                start_after,
+                limit,
+            } => Ok(QueryResponse::Accounts(self.query_accounts(start_after, limit))),
+            QueryRequest::WasmRaw {
+                contract,
+                key,
+            } => self.query_wasm_raw(contract, key).map(QueryResponse::WasmRaw),
+            QueryRequest::WasmSmart {
+                contract,
+                msg,
+            } => self.query_wasm_smart(contract, msg).map(QueryResponse::WasmSmart),
+            _ => Err(StdError::Generic("query not supported by MockBackendQuerier".into()).into()),
+        }
+    }
+
+    fn query_balance(&self, address: Addr, denom: String) -> VmResult<Coin> {
+        let state = self.state.borrow();
+        let amount = state.balances.get(&address).map(|coins| coins.amount_of(&denom));
+        Ok(Coin {
+            denom,
+            amount: amount.unwrap_or(Uint128::ZERO),
+        })
+    }
+
+    fn query_balances(
+        &self,
+        address:     Addr,
+        start_after: Option<String>,
+        limit:       Option<u32>,
+    ) -> Coins {
+        let state = self.state.borrow();
+        let Some(coins) = state.balances.get(&address) else {
+            return Coins::new_empty();
+        };
+
+        paginate_coins(coins.clone(), start_after, limit)
+    }
+
+    fn query_supply(&self, denom: String) -> VmResult<Coin> {
+        let supplies = self.supplies()?;
+        Ok(Coin {
+            amount: supplies.amount_of(&denom),
+            denom,
+        })
+    }
+
+    fn query_supplies(&self, start_after: Option<String>, limit: Option<u32>) -> VmResult<Coins> {
+        Ok(paginate_coins(self.supplies()?, start_after, limit))
+    }
+
+    /// The total supplies of all denoms, which are the sums of the preset
+    /// balances.
+    fn supplies(&self) -> VmResult<Coins> {
+        let mut supplies = Coins::new_empty();
+        for coins in self.state.borrow().balances.values() {
+            for coin in coins {
+                supplies.increase_amount(coin.denom, *coin.amount)?;
+            }
+        }
+        Ok(supplies)
+    }
+
+    fn query_code(&self, hash: Hash) -> VmResult<Binary> {
+        let state = self.state.borrow();
+        let Some(wasm_byte_code) = state.codes.get(&hash) else {
+            return Err(StdError::data_not_found::<Binary>(hash.as_ref()).into());
+        };
+
+        Ok(wasm_byte_code.clone())
+    }
+
+    fn query_codes(&self, start_after: Option<Hash>, limit: Option<u32>) -> Vec<Hash> {
+        let state = self.state.borrow();
+        let start = start_after.map_or(Bound::Unbounded, Bound::Excluded);
+        state
+            .codes
+            .range((start, Bound::Unbounded))
+            .take(limit.unwrap_or(DEFAULT_PAGE_LIMIT) as usize)
+            .map(|(hash, _)| hash.clone())
+            .collect()
+    }
+
+    fn query_account(&self, address: Addr) -> VmResult<AccountResponse> {
+        let state = self.state.borrow();
+        let Some(account) = state.accounts.get(&address) else {
+            return Err(StdError::data_not_found::<AccountResponse>(address.as_ref()).into());
+        };
+
+        Ok(account.clone())
+    }
+
+    fn query_accounts(
+        &self,
+        start_after: Option<Addr>,
+        limit:       Option<u32>,
+    ) -> Vec<AccountResponse> {
+        let state = self.state.borrow();
+        let start = start_after.map_or(Bound::Unbounded, Bound::Excluded);
+        state
+            .accounts
+            .range((start, Bound::Unbounded))
+            .take(limit.unwrap_or(DEFAULT_PAGE_LIMIT) as usize)
+            .map(|(_, account)| account.clone())
+            .collect()
+    }
+
+    fn query_wasm_raw(&self, contract: Addr, key: Binary) -> VmResult<WasmRawResponse> {
+        let context = self.context_data(&contract)?;
+        let value = context.read().map_err(|_| VmError::FailedReadLock)?.store.read(&key)?;
+
+        Ok(WasmRawResponse {
+            contract,
+            key,
+            value: value.map(Binary::from),
+        })
+    }
+
+    fn query_wasm_smart(&self, contract: Addr, msg: Binary) -> VmResult<WasmSmartResponse> {
+        let instance = self.instance(&contract)?;
+        let mut instance = instance.try_borrow_mut().map_err(|_| instance_busy(&contract))?;
+
+        let ctx = Context {
+            chain_id:        MOCK_CHAIN_ID.into(),
+            block_height:    Uint64::new(0),
+            block_timestamp: Timestamp::from_seconds(0),
+            block_hash:      hash(""),
+            contract,
+            sender:          None,
+            funds:           None,
+            simulate:        None,
+            submsg_result:   None,
+        };
+        let data = instance.call_query(&ctx, &msg)?.into_std_result()?;
+
+        Ok(WasmSmartResponse {
+            contract: ctx.contract,
+            data,
+        })
+    }
+
+    // the instance is cloned out of the state, such that the state isn't
+    // borrowed while the instance is called, in case it makes queries itself.
+    fn instance(&self, address: &Addr) -> VmResult<Rc<RefCell<MockInstance>>> {
+        self.state
+            .borrow()
+            .instances
+            .get(address)
+            .cloned()
+            .ok_or_else(|| instance_not_found(address))
+    }
+
+    fn context_data(&self, address: &Addr) -> VmResult<MockContextData> {
+        self.state
+            .borrow()
+            .contexts
+            .get(address)
+            .cloned()
+            .ok_or_else(|| instance_not_found(address))
+    }
+}
 
 impl BackendQuerier for MockBackendQuerier {
-    fn query_chain(&self, _req: QueryRequest) -> VmResult<GenericResult<QueryResponse>> {
-        todo!("MockBackendQuerier isn't implemented")
+    fn query_chain(&self, req: QueryRequest) -> VmResult<GenericResult<QueryResponse>> {
+        // same as in the app, an error in processing the query is returned to
+        // the contract instead of aborting the call
+        Ok(self.query(req).into())
     }
 }
 
+fn paginate_coins(coins: Coins, start_after: Option<String>, limit: Option<u32>) -> Coins {
+    let coins = coins
+        .into_iter()
+        .skip_while(|coin| start_after.as_ref().is_some_and(|start| coin.denom <= *start))
+        .take(limit.unwrap_or(DEFAULT_PAGE_LIMIT) as usize)
+        .collect();
+
+    Coins::from_vec_unchecked(coins)
+}
+
+fn instance_not_found(address: &Addr) -> VmError {
+    StdError::Generic(format!("no instance registered at {address}")).into()
+}
+
+fn instance_busy(address: &Addr) -> VmError {
+    StdError::Generic(format!("instance at {address} is being called and can't be queried")).into()
+}
+
 #[derive(Default, Debug, Clone)]
 pub struct MockBackendStorage {
     data:         BTreeMap<Vec<u8>, Vec<u8>>,
@@ -81,6 +364,24 @@ impl BackendStorage for MockBackendStorage {
 
         Ok(())
     }
+
+    fn remove_range(&mut self, min: Option<&[u8]>, max: Option<&[u8]>) -> VmResult<usize> {
+        // split off the records that are >= min, then put back the ones that
+        // are >= max. what's left are the ones to be removed.
+        let mut removed = match min {
+            Some(min) => self.data.split_off(min),
+            None => mem::take(&mut self.data),
+        };
+        if let Some(max) = max {
+            self.data.append(&mut removed.split_off(max));
+        }
+
+        // whenever KV data is mutated, delete all existing iterators to avoid
+        // race conditions.
+        self.iterators.clear();
+
+        Ok(removed.len())
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -138,7 +439,91 @@ impl Iterator for MockBackendIter {
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use {super::*, crate::GasTracker, wasmer::wat2wasm};
+
+    // a contract that answers every query with the same data, "pong". the
+    // response is preset in memory: a region at 0, pointing to the data at 16.
+    // `allocate` is a bump allocator, and `deallocate` does nothing.
+    const MOCK_CONTRACT: &str = r#"
+        (module
+            (memory (export "memory") 1)
+            (data (i32.const 0) "\10\00\00\00\11\00\00\00\11\00\00\00")
+            (data (i32.const 16) "{\"ok\":\"cG9uZw==\"}")
+            (global $next (mut i32) (i32.const 1024))
+            (func (export "allocate") (param $capacity i32) (result i32)
+                (local $ptr i32)
+                (local.set $ptr (global.get $next))
+                (i32.store (local.get $ptr) (i32.add (local.get $ptr) (i32.const 12)))
+                (i32.store offset=4 (local.get $ptr) (local.get $capacity))
+                (i32.store offset=8 (local.get $ptr) (i32.const 0))
+                (global.set $next
+                    (i32.add (local.get $ptr) (i32.add (local.get $capacity) (i32.const 12))))
+                (local.get $ptr))
+            (func (export "deallocate") (param i32))
+            (func (export "query") (param i32 i32) (result i32) (i32.const 0)))
+    "#;
+
+    fn query(querier: &MockBackendQuerier, req: QueryRequest) -> VmResult<QueryResponse> {
+        querier.query_chain(req)?.into_std_result().map_err(Into::into)
+    }
+
+    #[test]
+    fn querying_preset_data_works() -> VmResult<()> {
+        let code_hash = hash(b"code");
+        let querier = MockBackendQuerier::new()
+            .with_balances(Addr::mock(1), Coins::from_vec_unchecked(vec![
+                Coin::new("uatom", 100),
+                Coin::new("uosmo", 888),
+            ]))
+            .with_balances(Addr::mock(2), Coins::from(Coin::new("uatom", 50)))
+            .with_account(Addr::mock(3), code_hash.clone(), None)
+            .with_code(b"code".to_vec());
+
+        let balance = query(&querier, QueryRequest::Balance {
+            address: Addr::mock(2),
+            denom:   "uosmo".into(),
+        })?;
+        assert_eq!(balance.as_balance(), Coin::new("uosmo", 0));
+
+        let balances = query(&querier, QueryRequest::Balances {
+            address:     Addr::mock(1),
+            start_after: Some("uatom".into()),
+            limit:       None,
+        })?;
+        assert_eq!(balances.as_balances(), Coins::from(Coin::new("uosmo", 888)));
+
+        // supplies are the sums of the balances
+        let supply = query(&querier, QueryRequest::Supply {
+            denom: "uatom".into(),
+        })?;
+        assert_eq!(supply.as_supply(), Coin::new("uatom", 150));
+
+        let account = query(&querier, QueryRequest::Account {
+            address: Addr::mock(3),
+        })?;
+        assert_eq!(account.as_account().code_hash, code_hash);
+
+        let codes = query(&querier, QueryRequest::Codes {
+            start_after: None,
+            limit:       None,
+        })?;
+        assert_eq!(codes.as_codes(), vec![code_hash]);
+
+        // querying something that doesn't exist returns an error to the
+        // contract, instead of failing the call
+        let res = querier.query_chain(QueryRequest::Account {
+            address: Addr::mock(4),
+        })?;
+        assert!(matches!(res, GenericResult::Err(_)));
+
+        let res = querier.query_chain(QueryRequest::WasmSmart {
+            contract: Addr::mock(3),
+            msg:      b"{}".to_vec().into(),
+        })?;
+        assert!(matches!(res, GenericResult::Err(_)));
+
+        Ok(())
+    }
 
     #[test]
     fn backend_iterator_works() -> VmResult<()> {
@@ -169,4 +554,81 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn backend_removing_range_works() -> VmResult<()> {
+        let mut store = MockBackendStorage::new();
+        for key in 1..=5 {
+            store.write(&[key], &[key])?;
+        }
+
+        // removing the range invalidates existing iterators
+        let iterator_id = store.scan(None, None, Order::Ascending)?;
+        assert_eq!(store.remove_range(Some(&[2]), Some(&[4]))?, 2);
+        assert!(matches!(store.next(iterator_id), Err(VmError::IteratorNotFound { .. })));
+
+        // min bound is inclusive, max bound is exclusive
+        assert_eq!(store.read(&[1])?, Some(vec![1]));
+        assert_eq!(store.read(&[2])?, None);
+        assert_eq!(store.read(&[3])?, None);
+        assert_eq!(store.read(&[4])?, Some(vec![4]));
+
+        // min > max; nothing is removed
+        assert_eq!(store.remove_range(Some(&[5]), Some(&[1]))?, 0);
+
+        // unbounded on both sides; everything is removed
+        assert_eq!(store.remove_range(None, None)?, 3);
+        let iterator_id = store.scan(None, None, Order::Ascending)?;
+        assert_eq!(store.next(iterator_id)?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn querying_wasm_works() -> VmResult<()> {
+        let wasm = wat2wasm(MOCK_CONTRACT.as_bytes()).unwrap();
+        let mut store = MockBackendStorage::new();
+        store.write(b"foo", b"bar")?;
+
+        let querier = MockBackendQuerier::new();
+        let instance =
+            Instance::build_from_code(store, querier.clone(), &wasm, GasTracker::new_limitless())?;
+        querier.register_instance(Addr::mock(2), instance);
+
+        let smart = query(&querier, QueryRequest::WasmSmart {
+            contract: Addr::mock(2),
+            msg:      b"{}".to_vec().into(),
+        })?;
+        assert_eq!(smart.as_wasm_smart().data, Binary::from(b"pong".to_vec()));
+
+        let raw = query(&querier, QueryRequest::WasmRaw {
+            contract: Addr::mock(2),
+            key:      b"foo".to_vec().into(),
+        })?;
+        assert_eq!(raw.as_wasm_raw().value, Some(Binary::from(b"bar".to_vec())));
+
+        // while the instance is being called, it can't be entered again, but
+        // its storage can still be read
+        let instance = querier.instance(&Addr::mock(2))?;
+        let _guard = instance.borrow_mut();
+        assert!(query(&querier, QueryRequest::WasmSmart {
+            contract: Addr::mock(2),
+            msg:      b"{}".to_vec().into(),
+        })
+        .is_err());
+        let raw = query(&querier, QueryRequest::WasmRaw {
+            contract: Addr::mock(2),
+            key:      b"foo".to_vec().into(),
+        })?;
+        assert_eq!(raw.as_wasm_raw().value, Some(Binary::from(b"bar".to_vec())));
+
+        // contracts that aren't registered can't be queried
+        assert!(query(&querier, QueryRequest::WasmRaw {
+            contract: Addr::mock(3),
+            key:      b"foo".to_vec().into(),
+        })
+        .is_err());
+
+        Ok(())
+    }
 }
@@ -56,4 +56,12 @@ pub trait BackendStorage {
     /// IMPORTANT: to avoid race conditions, calling this method MUST result in
     /// all existing iterators being dropped.
     fn remove(&mut self, key: &[u8]) -> Result<(), Self::Err>;
+
+    /// Delete all data within the given bounds. Same as in `scan`, minimum
+    /// bound is inclusive, while maximum bound is exclusive. Return the number
+    /// of records deleted, as gas is charged for each of them.
+    ///
+    /// IMPORTANT: to avoid race conditions, calling this method MUST result in
+    /// all existing iterators being dropped.
+    fn remove_range(&mut self, min: Option<&[u8]>, max: Option<&[u8]>) -> Result<usize, Self::Err>;
 }
@@ -0,0 +1,289 @@
+use {
+    crate::{VmError, VmResult},
+    wasmer::wasmparser::{
+        ExternalKind, Operator, Parser, Payload, TypeRef, ValType, Validator, WasmFeatures,
+    },
+};
+
+/// Maximum size of Wasm byte code that can be uploaded, in bytes.
+pub const MAX_WASM_SIZE: usize = 3 * 1024 * 1024;
+
+/// Maximum number of functions a Wasm module can define, not including the
+/// imported ones.
+pub const MAX_WASM_FUNCTIONS: usize = 10_000;
+
+/// Exports that every contract must have. The host calls `allocate` and
+/// `deallocate` to manage the contract's memory, and every contract needs to
+/// be instantiated.
+pub const REQUIRED_EXPORTS: &[&str] = &["allocate", "deallocate", "instantiate"];
+
+/// Functions the host provides under the `env` namespace. This must be kept in
+/// sync with the imports registered in `Instance::build_from_module`.
+pub const SUPPORTED_IMPORTS: &[&str] = &[
+    "db_read",
+    "db_scan",
+    "db_next",
+    "db_write",
+    "db_remove",
+    "db_remove_range",
+    "debug",
+    "query_chain",
+    "secp256k1_verify",
+    "secp256r1_verify",
+    "secp256k1_pubkey_recover",
+    "ed25519_verify",
+    "ed25519_batch_verify",
+    "bls12_381_aggregate_verify",
+    "sha2_256",
+    "sha3_256",
+    "keccak256",
+    "blake3",
+];
+
+/// Perform static checks on Wasm byte code, such that obviously unusable code
+/// is rejected at upload time, instead of failing when someone attempts to
+/// instantiate it.
+///
+/// The checks are:
+/// - the code doesn't exceed `MAX_WASM_SIZE`;
+/// - the code is a valid Wasm module;
+/// - the module exports all of `REQUIRED_EXPORTS`;
+/// - the module only imports functions from `SUPPORTED_IMPORTS`;
+/// - the module doesn't use floating point numbers, which are a potential
+///   source of non-determinism;
+/// - the module doesn't define more than `MAX_WASM_FUNCTIONS` functions.
+pub fn validate_wasm(wasm_byte_code: &[u8]) -> VmResult<()> {
+    if wasm_byte_code.len() > MAX_WASM_SIZE {
+        return Err(VmError::wasm_too_large(wasm_byte_code.len(), MAX_WASM_SIZE));
+    }
+
+    // SIMD instructions operate on floats (among others), so we disable them
+    // altogether, such that we only have to look for scalar float operators
+    let features = WasmFeatures {
+        simd: false,
+        relaxed_simd: false,
+        ..Default::default()
+    };
+    Validator::new_with_features(features)
+        .validate_all(wasm_byte_code)
+        .map_err(|err| VmError::InvalidWasm(err.to_string()))?;
+
+    let mut exports = vec![];
+    let mut num_functions = 0;
+
+    // the byte code has been validated above, so parsing errors are not
+    // expected here. we still handle them instead of unwrapping, to be safe.
+    for payload in Parser::new(0).parse_all(wasm_byte_code) {
+        match payload? {
+            Payload::ImportSection(reader) => {
+                for import in reader {
+                    let import = import?;
+                    if import.module != "env"
+                        || !SUPPORTED_IMPORTS.contains(&import.name)
+                        || !matches!(import.ty, TypeRef::Func(_))
+                    {
+                        return Err(VmError::unsupported_import(import.module, import.name));
+                    }
+                }
+            },
+            Payload::ExportSection(reader) => {
+                for export in reader {
+                    let export = export?;
+                    if matches!(export.kind, ExternalKind::Func) {
+                        exports.push(export.name);
+                    }
+                }
+            },
+            Payload::GlobalSection(reader) => {
+                for global in reader {
+                    if is_float(&global?.ty.content_type) {
+                        return Err(VmError::FloatsNotAllowed);
+                    }
+                }
+            },
+            Payload::FunctionSection(reader) => {
+                num_functions += reader.into_iter().count();
+            },
+            Payload::CodeSectionEntry(body) => {
+                for local in body.get_locals_reader()? {
+                    let (_, ty) = local?;
+                    if is_float(&ty) {
+                        return Err(VmError::FloatsNotAllowed);
+                    }
+                }
+                for op in body.get_operators_reader()? {
+                    if is_float_operator(&op?) {
+                        return Err(VmError::FloatsNotAllowed);
+                    }
+                }
+            },
+            _ => (),
+        }
+    }
+
+    if num_functions > MAX_WASM_FUNCTIONS {
+        return Err(VmError::too_many_functions(num_functions, MAX_WASM_FUNCTIONS));
+    }
+
+    for name in REQUIRED_EXPORTS {
+        if !exports.contains(name) {
+            return Err(VmError::missing_export(*name));
+        }
+    }
+
+    Ok(())
+}
+
+fn is_float(ty: &ValType) -> bool {
+    matches!(ty, ValType::F32 | ValType::F64)
+}
+
+fn is_float_operator(op: &Operator) -> bool {
+    matches!(
+        op,
+        Operator::F32Load { .. }
+            | Operator::F64Load { .. }
+            | Operator::F32Store { .. }
+            | Operator::F64Store { .. }
+            | Operator::F32Const { .. }
+            | Operator::F64Const { .. }
+            | Operator::F32Eq
+            | Operator::F32Ne
+            | Operator::F32Lt
+            | Operator::F32Gt
+            | Operator::F32Le
+            | Operator::F32Ge
+            | Operator::F64Eq
+            | Operator::F64Ne
+            | Operator::F64Lt
+            | Operator::F64Gt
+            | Operator::F64Le
+            | Operator::F64Ge
+            | Operator::F32Abs
+            | Operator::F32Neg
+            | Operator::F32Ceil
+            | Operator::F32Floor
+            | Operator::F32Trunc
+            | Operator::F32Nearest
+            | Operator::F32Sqrt
+            | Operator::F32Add
+            | Operator::F32Sub
+            | Operator::F32Mul
+            | Operator::F32Div
+            | Operator::F32Min
+            | Operator::F32Max
+            | Operator::F32Copysign
+            | Operator::F64Abs
+            | Operator::F64Neg
+            | Operator::F64Ceil
+            | Operator::F64Floor
+            | Operator::F64Trunc
+            | Operator::F64Nearest
+            | Operator::F64Sqrt
+            | Operator::F64Add
+            | Operator::F64Sub
+            | Operator::F64Mul
+            | Operator::F64Div
+            | Operator::F64Min
+            | Operator::F64Max
+            | Operator::F64Copysign
+            | Operator::I32TruncF32S
+            | Operator::I32TruncF32U
+            | Operator::I32TruncF64S
+            | Operator::I32TruncF64U
+            | Operator::I64TruncF32S
+            | Operator::I64TruncF32U
+            | Operator::I64TruncF64S
+            | Operator::I64TruncF64U
+            | Operator::I32TruncSatF32S
+            | Operator::I32TruncSatF32U
+            | Operator::I32TruncSatF64S
+            | Operator::I32TruncSatF64U
+            | Operator::I64TruncSatF32S
+            | Operator::I64TruncSatF32U
+            | Operator::I64TruncSatF64S
+            | Operator::I64TruncSatF64U
+            | Operator::F32ConvertI32S
+            | Operator::F32ConvertI32U
+            | Operator::F32ConvertI64S
+            | Operator::F32ConvertI64U
+            | Operator::F32DemoteF64
+            | Operator::F64ConvertI32S
+            | Operator::F64ConvertI32U
+            | Operator::F64ConvertI64S
+            | Operator::F64ConvertI64U
+            | Operator::F64PromoteF32
+            | Operator::I32ReinterpretF32
+            | Operator::I64ReinterpretF64
+            | Operator::F32ReinterpretI32
+            | Operator::F64ReinterpretI64
+    )
+}
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {super::*, test_case::test_case, wasmer::wat2wasm};
+
+    const EXPORTS: &str = r#"
+        (func (export "allocate") (param i32) (result i32) local.get 0)
+        (func (export "deallocate") (param i32))
+        (func (export "instantiate") (param i32 i32) (result i32) i32.const 0)
+    "#;
+
+    fn module(body: &str) -> Vec<u8> {
+        wat2wasm(format!("(module {body})").as_bytes()).unwrap().into_owned()
+    }
+
+    #[test]
+    fn valid_module_passes() {
+        let wasm = module(&format!(r#"(import "env" "debug" (func (param i32))) {EXPORTS}"#));
+        assert!(validate_wasm(&wasm).is_ok());
+    }
+
+    #[test_case(
+        b"\0asm\x01\0\0\0garbage".to_vec(),
+        |err| matches!(err, VmError::InvalidWasm(_));
+        "invalid wasm"
+    )]
+    #[test_case(
+        module(r#"(func (export "allocate") (param i32) (result i32) local.get 0)"#),
+        |err| matches!(err, VmError::MissingExport { name } if name == "deallocate");
+        "missing export"
+    )]
+    #[test_case(
+        module(&format!(r#"(import "env" "abort" (func)) {EXPORTS}"#)),
+        |err| matches!(err, VmError::UnsupportedImport { name, .. } if name == "abort");
+        "unsupported import"
+    )]
+    #[test_case(
+        module(&format!(r#"(import "wasi" "debug" (func (param i32))) {EXPORTS}"#)),
+        |err| matches!(err, VmError::UnsupportedImport { module, .. } if module == "wasi");
+        "unsupported import module"
+    )]
+    #[test_case(
+        module(&format!(r#"(func (result i32) f32.const 1 i32.trunc_f32_s) {EXPORTS}"#)),
+        |err| matches!(err, VmError::FloatsNotAllowed);
+        "float operator"
+    )]
+    #[test_case(
+        module(&format!(r#"(func (local f64)) {EXPORTS}"#)),
+        |err| matches!(err, VmError::FloatsNotAllowed);
+        "float local"
+    )]
+    #[test_case(
+        vec![0; MAX_WASM_SIZE + 1],
+        |err| matches!(err, VmError::WasmTooLarge { .. });
+        "too large"
+    )]
+    #[test_case(
+        module(&format!("{}{EXPORTS}", "(func)".repeat(MAX_WASM_FUNCTIONS))),
+        |err| matches!(err, VmError::TooManyFunctions { .. });
+        "too many functions"
+    )]
+    fn invalid_module_fails(wasm: Vec<u8>, expect: fn(&VmError) -> bool) {
+        let err = validate_wasm(&wasm).unwrap_err();
+        assert!(expect(&err), "unexpected error: {err}");
+    }
+}
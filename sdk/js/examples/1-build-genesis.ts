@@ -64,7 +64,7 @@ const keystorePassword = "123";
     AdminOptionKind.SetToNone,
   );
 
-  builder.setConfig({ bank });
+  builder.setConfig({ bank, beginBlockers: [], endBlockers: [] });
   builder.writeToFile();
 
   console.log("✅ done!");
@@ -26,6 +26,7 @@ export {
   BlockInfo,
   Coin,
   Config,
+  Cronjob,
   GenesisState,
   InfoResponse,
   Message,
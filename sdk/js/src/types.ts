@@ -176,6 +176,15 @@ export type AccountStateResponse = {
 export type Config = {
   owner?: string;
   bank: string;
+  taxman?: string;
+  beginBlockers: Cronjob[];
+  endBlockers: Cronjob[];
+  maxMsgDepth?: number;
+};
+
+export type Cronjob = {
+  contract: string;
+  interval: string;
 };
 
 export type BlockInfo = {
@@ -73,8 +73,12 @@ fn main() -> anyhow::Result<()> {
 
     // set config
     builder.set_config(Config {
-        owner: None,
-        bank:  bank.clone(),
+        owner:          None,
+        bank:           bank.clone(),
+        taxman:         None,
+        begin_blockers: vec![],
+        end_blockers:   vec![],
+        max_msg_depth:  None,
     })?;
 
     // build the final genesis state and write to file
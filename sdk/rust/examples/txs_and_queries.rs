@@ -32,6 +32,7 @@ async fn main() -> anyhow::Result<()> {
         sender:      USER.clone(),
         chain_id:    None,
         sequence:    None,
+        gas_limit:   None,
     };
 
     // create client
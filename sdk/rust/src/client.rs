@@ -2,10 +2,11 @@ use {
     crate::{AdminOption, SigningKey},
     anyhow::{bail, ensure},
     cw_account::{QueryMsg, StateResponse},
-    cw_jmt::Proof,
+    cw_jmt::{verify_proof, Proof},
     cw_std::{
-        from_json, hash, to_json, AccountResponse, Addr, Binary, Coin, Coins, Config, Hash,
-        InfoResponse, Message, QueryRequest, QueryResponse, Tx, WasmRawResponse,
+        from_json, hash, to_json, Account, AccountResponse, Addr, Binary, Coin, Coins, Config,
+        GenericResult, Hash, InfoResponse, Message, QueryRequest, QueryResponse, SimulateResponse,
+        Tx, TxResponse, WasmRawResponse, ACCOUNTS, CODES, CONTRACT_NAMESPACE,
     },
     serde::{de::DeserializeOwned, ser::Serialize},
     std::any::type_name,
@@ -16,11 +17,22 @@ use {
     },
 };
 
+/// The gas limit to use when simulating a transaction for the purpose of
+/// estimating its gas consumption.
+const SIMULATE_GAS_LIMIT: u64 = 100_000_000;
+
+/// When the gas limit is estimated by simulation, the simulated gas usage is
+/// multiplied by this factor, to give some room for state changes that may
+/// happen between the simulation and the tx's execution.
+const GAS_ADJUSTMENT: f64 = 1.3;
+
 pub struct SigningOptions {
     pub signing_key: SigningKey,
     pub sender:      Addr,
     pub chain_id:    Option<String>,
     pub sequence:    Option<u32>,
+    /// If not provided, the gas limit is estimated by simulating the tx.
+    pub gas_limit:   Option<u64>,
 }
 
 pub struct Client {
@@ -119,6 +131,42 @@ impl Client {
         Ok(from_json(res.value)?)
     }
 
+    /// Query the app with a Merkle proof. Returns the query response, the proof,
+    /// and the app hash that the proof is to be verified against.
+    ///
+    /// The app hash resulting from block `N` is included in the header of block
+    /// `N + 1`, which we fetch from Tendermint RPC. If height isn't specified,
+    /// we query the state right before the latest block, since that's the most
+    /// recent state whose app hash is available in a header.
+    async fn query_app_with_proof(
+        &self,
+        req: &QueryRequest,
+        height: Option<u64>,
+    ) -> anyhow::Result<(QueryResponse, Proof, Hash)> {
+        let (height, header) = match height {
+            Some(height) => (height, self.block(Some(height + 1)).await?.block.header),
+            None => {
+                let header = self.block(None).await?.block.header;
+                (header.height.value() - 1, header)
+            },
+        };
+        ensure!(height > 0, "can't prove a query against the state prior to genesis");
+
+        let data = to_json(req)?.to_vec();
+        let res = self.query("/app", data.clone(), Some(height), true).await?;
+        ensure!(res.proof.is_some());
+        let proof = res.proof.unwrap();
+        ensure!(proof.ops.len() == 1);
+        ensure!(proof.ops[0].field_type == type_name::<Proof>());
+        ensure!(proof.ops[0].key == data);
+
+        Ok((
+            from_json(res.value)?,
+            from_json(&proof.ops[0].data)?,
+            header.app_hash.as_bytes().try_into()?,
+        ))
+    }
+
     pub async fn query_info(&self, height: Option<u64>) -> anyhow::Result<InfoResponse> {
         let res = self.query_app(&QueryRequest::Info {}, height).await?;
         Ok(res.as_info())
@@ -165,6 +213,21 @@ impl Client {
         Ok(res.as_code())
     }
 
+    /// Query a Wasm byte code, and verify it with a Merkle proof against the
+    /// app hash in the block header.
+    pub async fn query_code_with_proof(
+        &self,
+        code_hash: Hash,
+        height: Option<u64>,
+    ) -> anyhow::Result<Binary> {
+        let storage_key = CODES.storage_key(&code_hash);
+        let (res, proof, app_hash) =
+            self.query_app_with_proof(&QueryRequest::Code { hash: code_hash }, height).await?;
+        let code = res.as_code();
+        verify_proof(&app_hash, &hash(storage_key), Some(&hash(to_json(&code)?)), &proof)?;
+        Ok(code)
+    }
+
     pub async fn query_codes(
         &self,
         start_after: Option<Hash>,
@@ -180,6 +243,25 @@ impl Client {
         Ok(res.as_account())
     }
 
+    /// Query an account, and verify it with a Merkle proof against the app hash
+    /// in the block header.
+    pub async fn query_account_with_proof(
+        &self,
+        address: Addr,
+        height: Option<u64>,
+    ) -> anyhow::Result<AccountResponse> {
+        let storage_key = ACCOUNTS.storage_key(&address);
+        let (res, proof, app_hash) =
+            self.query_app_with_proof(&QueryRequest::Account { address }, height).await?;
+        let res = res.as_account();
+        let account = Account {
+            code_hash: res.code_hash.clone(),
+            admin:     res.admin.clone(),
+        };
+        verify_proof(&app_hash, &hash(storage_key), Some(&hash(to_json(&account)?)), &proof)?;
+        Ok(res)
+    }
+
     pub async fn query_accounts(
         &self,
         start_after: Option<Addr>,
@@ -200,6 +282,23 @@ impl Client {
         Ok(res.as_wasm_raw())
     }
 
+    /// Query a raw key in a contract's storage, and verify the value (or the
+    /// absence of it) with a Merkle proof against the app hash in the block
+    /// header.
+    pub async fn query_wasm_raw_with_proof(
+        &self,
+        contract: Addr,
+        key: Binary,
+        height: Option<u64>,
+    ) -> anyhow::Result<WasmRawResponse> {
+        let storage_key = [CONTRACT_NAMESPACE, contract.as_ref(), key.as_ref()].concat();
+        let (res, proof, app_hash) =
+            self.query_app_with_proof(&QueryRequest::WasmRaw { contract, key }, height).await?;
+        let res = res.as_wasm_raw();
+        verify_proof(&app_hash, &hash(storage_key), res.value.as_ref().map(hash).as_ref(), &proof)?;
+        Ok(res)
+    }
+
     pub async fn query_wasm_smart<M: Serialize, R: DeserializeOwned>(
         &self,
         contract: Addr,
@@ -211,6 +310,36 @@ impl Client {
         Ok(from_json(res.as_wasm_smart().data)?)
     }
 
+    /// Query a transaction and its outcome by hash, from the node's local tx
+    /// index.
+    pub async fn query_tx(&self, hash: Hash) -> anyhow::Result<TxResponse> {
+        let res = self.query_app(&QueryRequest::Tx { hash }, None).await?;
+        Ok(res.as_tx())
+    }
+
+    /// Search for transactions that emitted an event of the given type with an
+    /// attribute of the given key and value, from the node's local tx index.
+    pub async fn search_txs(
+        &self,
+        event_type:  String,
+        key:         String,
+        value:       String,
+        start_after: Option<Hash>,
+        limit:       Option<u32>,
+    ) -> anyhow::Result<Vec<TxResponse>> {
+        let req = QueryRequest::SearchTxs { event_type, key, value, start_after, limit };
+        let res = self.query_app(&req, None).await?;
+        Ok(res.as_search_txs())
+    }
+
+    /// Simulate a transaction against the latest state, without committing any
+    /// state changes. Returns the gas consumption, and the events emitted or
+    /// the error if the tx fails.
+    pub async fn simulate(&self, tx: &Tx) -> anyhow::Result<SimulateResponse> {
+        let res = self.query("/simulate", to_json(tx)?.to_vec(), None, false).await?;
+        Ok(from_json(res.value)?)
+    }
+
     // ------------------------------ tx methods -------------------------------
 
     /// Create, sign, and broadcast a transaction without confirmation.
@@ -226,12 +355,69 @@ impl Client {
         Ok(maybe_res.unwrap())
     }
 
+    /// Broadcast a transaction that has already been signed, e.g. one sent by
+    /// a multisig account, as assembled by `MultisigTx::into_tx`.
+    pub async fn broadcast_tx(&self, tx: &Tx) -> anyhow::Result<tx_sync::Response> {
+        let tx_bytes = to_json(tx)?;
+        Ok(self.inner.broadcast_tx_sync(tx_bytes).await?)
+    }
+
     pub async fn send_tx_with_confirmation(
         &self,
         msgs: Vec<Message>,
         sign_opts: &SigningOptions,
         confirm_fn: fn(&Tx) -> anyhow::Result<bool>,
     ) -> anyhow::Result<Option<tx_sync::Response>> {
+        let tx = self.create_and_sign_tx(msgs, sign_opts).await?;
+
+        if confirm_fn(&tx)? {
+            let tx_bytes = to_json(&tx)?;
+            Ok(Some(self.inner.broadcast_tx_sync(tx_bytes).await?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Create and sign a transaction, and simulate it without broadcasting.
+    /// If the gas limit isn't specified in the signing options, a large gas
+    /// limit is used.
+    pub async fn simulate_tx(
+        &self,
+        msgs: Vec<Message>,
+        sign_opts: &SigningOptions,
+    ) -> anyhow::Result<SimulateResponse> {
+        let gas_limit = sign_opts.gas_limit.unwrap_or(SIMULATE_GAS_LIMIT);
+        let tx = self.create_and_sign_tx_with_gas_limit(msgs, sign_opts, gas_limit).await?;
+        self.simulate(&tx).await
+    }
+
+    /// Create and sign a transaction. If the gas limit isn't specified in the
+    /// signing options, estimate it by simulating the tx.
+    pub async fn create_and_sign_tx(
+        &self,
+        msgs: Vec<Message>,
+        sign_opts: &SigningOptions,
+    ) -> anyhow::Result<Tx> {
+        let gas_limit = match sign_opts.gas_limit {
+            None => {
+                let res = self.simulate_tx(msgs.clone(), sign_opts).await?;
+                if let GenericResult::Err(err) = res.result {
+                    bail!("failed to estimate gas limit! simulation failed: {err}");
+                }
+                (res.gas_used.u64() as f64 * GAS_ADJUSTMENT).ceil() as u64
+            },
+            Some(gas_limit) => gas_limit,
+        };
+
+        self.create_and_sign_tx_with_gas_limit(msgs, sign_opts, gas_limit).await
+    }
+
+    async fn create_and_sign_tx_with_gas_limit(
+        &self,
+        msgs: Vec<Message>,
+        sign_opts: &SigningOptions,
+        gas_limit: u64,
+    ) -> anyhow::Result<Tx> {
         let chain_id = match &sign_opts.chain_id {
             None => self.query_info(None).await?.chain_id,
             Some(id) => id.to_string(),
@@ -250,19 +436,13 @@ impl Client {
             Some(seq) => seq,
         };
 
-        let tx = sign_opts.signing_key.create_and_sign_tx(
+        sign_opts.signing_key.create_and_sign_tx(
             msgs,
             sign_opts.sender.clone(),
             &chain_id,
             sequence,
-        )?;
-
-        if confirm_fn(&tx)? {
-            let tx_bytes = to_json(&tx)?;
-            Ok(Some(self.inner.broadcast_tx_sync(tx_bytes).await?))
-        } else {
-            Ok(None)
-        }
+            gas_limit,
+        )
     }
 
     pub async fn update_config(
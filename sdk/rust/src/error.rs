@@ -0,0 +1,103 @@
+use {
+    cw_account::AccountErrorCode,
+    cw_bank::BankErrorCode,
+    cw_multisig::MultisigErrorCode,
+    cw_std::{AppErrorCode, GenericError, StdErrorCode, VmErrorCode},
+};
+
+/// The kind of an error returned by the chain, decoded from its codespace and
+/// code, e.g. those in a tx result or an ABCI query response.
+///
+/// Errors from codespaces this SDK knows of are decoded into the respective
+/// code enums; the rest, including errors from other contracts, are kept in
+/// the raw form.
+///
+/// NOTE: An error returned by a contract keeps the codespace and code reported
+/// by the contract, which the chain doesn't check. Any contract can claim the
+/// `std` or `bank` codespace, for example, so the decoded kind only tells which
+/// contract the error came from if the contracts involved are trusted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChainError {
+    App(AppErrorCode),
+    Vm(VmErrorCode),
+    Std(StdErrorCode),
+    Bank(BankErrorCode),
+    Account(AccountErrorCode),
+    Multisig(MultisigErrorCode),
+    Other {
+        codespace: String,
+        code:      u32,
+    },
+}
+
+impl ChainError {
+    /// Decode an error from its codespace and code. Return `None` if the code
+    /// is zero, which indicates success.
+    pub fn decode(codespace: &str, code: u32) -> Option<Self> {
+        if code == 0 {
+            return None;
+        }
+
+        let decoded = match codespace {
+            AppErrorCode::CODESPACE => AppErrorCode::from_code(code).map(Self::App),
+            VmErrorCode::CODESPACE => VmErrorCode::from_code(code).map(Self::Vm),
+            StdErrorCode::CODESPACE => StdErrorCode::from_code(code).map(Self::Std),
+            BankErrorCode::CODESPACE => BankErrorCode::from_code(code).map(Self::Bank),
+            AccountErrorCode::CODESPACE => AccountErrorCode::from_code(code).map(Self::Account),
+            MultisigErrorCode::CODESPACE => MultisigErrorCode::from_code(code).map(Self::Multisig),
+            _ => None,
+        };
+
+        Some(decoded.unwrap_or_else(|| Self::Other {
+            codespace: codespace.to_string(),
+            code,
+        }))
+    }
+}
+
+impl From<&GenericError> for ChainError {
+    fn from(err: &GenericError) -> Self {
+        // a `GenericError` always has a non-zero code, but fall back to the
+        // raw form to be safe
+        Self::decode(&err.codespace, err.code).unwrap_or_else(|| Self::Other {
+            codespace: err.codespace.clone(),
+            code:      err.code,
+        })
+    }
+}
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decoding_works() {
+        let err = GenericError::from(cw_bank::BankError::FundsNotAccepted);
+        assert_eq!(ChainError::from(&err), ChainError::Bank(BankErrorCode::FundsNotAccepted));
+
+        let err = GenericError::from(cw_multisig::MultisigError::ZeroThreshold);
+        assert_eq!(ChainError::from(&err), ChainError::Multisig(MultisigErrorCode::ZeroThreshold));
+
+        let err = GenericError::from(cw_std::StdError::VerificationFailed);
+        assert_eq!(ChainError::from(&err), ChainError::Std(StdErrorCode::VerificationFailed));
+
+        // a std error returned by a contract keeps the std codespace
+        let std_err = cw_std::StdError::Generic("foo".into());
+        let err = GenericError::from(cw_account::AccountError::from(std_err));
+        assert_eq!(err.codespace, StdErrorCode::CODESPACE);
+        assert_eq!(ChainError::from(&err), ChainError::Std(StdErrorCode::Generic));
+
+        let app_err = ChainError::decode("app", 15);
+        assert_eq!(app_err, Some(ChainError::App(AppErrorCode::HeightPruned)));
+        let vm_err = ChainError::decode("vm", 17);
+        assert_eq!(vm_err, Some(ChainError::Vm(VmErrorCode::OutOfGas)));
+
+        assert_eq!(ChainError::decode("bank", 0), None);
+        assert_eq!(ChainError::decode("bank", 420), Some(ChainError::Other {
+            codespace: "bank".into(),
+            code:      420,
+        }));
+    }
+}
@@ -1,11 +1,15 @@
 mod client;
+mod error;
 mod genesis_builder;
+mod multisig;
 mod signing_key;
 mod types;
 
 pub use crate::{
     client::{Client, SigningOptions},
+    error::ChainError,
     genesis_builder::GenesisBuilder,
+    multisig::MultisigTx,
     signing_key::{Keystore, SigningKey},
     types::AdminOption,
 };
@@ -0,0 +1,75 @@
+use {
+    crate::SigningKey,
+    cw_multisig::Credential,
+    cw_std::{to_json, Addr, Binary, Message, SignDoc, Tx, Uint64},
+    serde::{Deserialize, Serialize},
+    std::{collections::BTreeMap, fs, path::Path},
+};
+
+/// A transaction to be sent by a multisig account, of which the signatures are
+/// gathered from the members one by one.
+///
+/// Members don't need to be online at the same time. The transaction can be
+/// written to a file, passed to the next member who adds their signature, and
+/// so on, until enough signatures are collected to meet the threshold.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MultisigTx {
+    pub sign_doc:   SignDoc,
+    pub signatures: BTreeMap<String, Binary>,
+}
+
+impl MultisigTx {
+    pub fn new(
+        msgs:      Vec<Message>,
+        sender:    Addr,
+        chain_id:  &str,
+        sequence:  u32,
+        gas_limit: u64,
+    ) -> Self {
+        Self {
+            sign_doc:   SignDoc::new(chain_id, sender, sequence, Uint64::new(gas_limit), msgs),
+            signatures: BTreeMap::new(),
+        }
+    }
+
+    /// Read a partially signed transaction from a file.
+    pub fn from_file(filename: &Path) -> anyhow::Result<Self> {
+        let tx_str = fs::read_to_string(filename)?;
+        serde_json::from_str(&tx_str).map_err(Into::into)
+    }
+
+    /// Save the partially signed transaction to a file.
+    pub fn write_to_file(&self, filename: &Path) -> anyhow::Result<()> {
+        let tx_str = serde_json::to_string_pretty(self)?;
+        fs::write(filename, tx_str.as_bytes()).map_err(Into::into)
+    }
+
+    /// Sign the transaction as the member of the given name, using the given
+    /// signing key. Overwrites the member's existing signature, if any.
+    pub fn sign(&mut self, member: impl Into<String>, key: &SigningKey) -> anyhow::Result<()> {
+        let signature = key.sign_doc(&self.sign_doc)?;
+        self.add_signature(member, signature);
+        Ok(())
+    }
+
+    /// Add a signature that the member of the given name produced elsewhere,
+    /// e.g. by `SigningKey::sign_doc`.
+    pub fn add_signature(&mut self, member: impl Into<String>, signature: Binary) {
+        self.signatures.insert(member.into(), signature);
+    }
+
+    /// Assemble the transaction, with the signatures gathered so far as the
+    /// credential.
+    pub fn into_tx(self) -> anyhow::Result<Tx> {
+        let credential = to_json(&Credential {
+            signatures: self.signatures,
+        })?;
+
+        Ok(Tx {
+            sender:    self.sign_doc.sender,
+            gas_limit: self.sign_doc.gas_limit,
+            msgs:      self.sign_doc.msgs,
+            credential,
+        })
+    }
+}
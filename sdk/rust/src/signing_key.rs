@@ -2,7 +2,7 @@ use {
     aes_gcm::{aead::Aead, AeadCore, Aes256Gcm, Key, KeyInit},
     bip32::{Mnemonic, PublicKey, XPrv},
     cw_crypto::Identity256,
-    cw_std::{Addr, Binary, Message, Tx},
+    cw_std::{Addr, Binary, Message, SignDoc, Tx, Uint64},
     k256::ecdsa::Signature,
     pbkdf2::pbkdf2_hmac,
     rand::{rngs::OsRng, Rng},
@@ -93,21 +93,30 @@ impl SigningKey {
 
     pub fn create_and_sign_tx(
         &self,
-        msgs:     Vec<Message>,
-        sender:   Addr,
-        chain_id: &str,
-        sequence: u32,
+        msgs:      Vec<Message>,
+        sender:    Addr,
+        chain_id:  &str,
+        sequence:  u32,
+        gas_limit: u64,
     ) -> anyhow::Result<Tx> {
-        let sign_bytes = cw_account::sign_bytes(&msgs, &sender, chain_id, sequence)?;
-        let sign_bytes = Identity256::from_bytes(&sign_bytes);
-        let signature: Signature = self.inner.sign_digest(sign_bytes);
+        let gas_limit = Uint64::new(gas_limit);
+        let sign_doc = SignDoc::new(chain_id, sender, sequence, gas_limit, msgs);
+        let signature = self.sign_doc(&sign_doc)?;
         Ok(Tx {
-            sender,
-            msgs,
-            credential: signature.to_vec().into(),
+            sender:     sign_doc.sender,
+            gas_limit,
+            msgs:       sign_doc.msgs,
+            credential: signature,
         })
     }
 
+    /// Sign the sign bytes of the given sign doc, returning the signature.
+    pub fn sign_doc(&self, sign_doc: &SignDoc) -> anyhow::Result<Binary> {
+        let sign_bytes = Identity256::from_bytes(&sign_doc.sign_bytes()?.into_slice());
+        let signature: Signature = self.inner.sign_digest(sign_bytes);
+        Ok(signature.to_vec().into())
+    }
+
     pub fn privkey(&self) -> [u8; 32] {
         self.inner.to_bytes().into()
     }